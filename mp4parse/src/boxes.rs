@@ -165,6 +165,13 @@ box_database!(
     VPCodecConfigurationBox           0x7670_6343, // "vpcC"
     AV1SampleEntry                    0x6176_3031, // "av01"
     AV1CodecConfigurationBox          0x6176_3143, // "av1C"
+    DVA1SampleEntry                   0x6476_6131, // "dva1" - Dolby Vision with AVC
+    DVH1SampleEntry                   0x6476_6831, // "dvh1" - Dolby Vision with HEVC
+    DVHESampleEntry                   0x6476_6865, // "dvhe" - Dolby Vision with HEVC
+    DAV1SampleEntry                   0x6461_7631, // "dav1" - Dolby Vision with AV1
+    DVCConfigurationBox               0x6476_6343, // "dvcC" - Dolby Vision profiles up to 7
+    DVVConfigurationBox               0x6476_7643, // "dvvC" - Dolby Vision profiles 8 to 10
+    DVWConfigurationBox               0x6476_7743, // "dvwC" - Dolby Vision profiles above 10
     FLACSampleEntry                   0x664c_6143, // "fLaC"
     FLACSpecificBox                   0x6466_4c61, // "dfLa"
     OpusSampleEntry                   0x4f70_7573, // "Opus"
@@ -173,10 +180,26 @@ box_database!(
     ProtectedAudioSampleEntry         0x656e_6361, // "enca" - Need to check official name in spec.
     MovieExtendsBox                   0x6d76_6578, // "mvex"
     MovieExtendsHeaderBox             0x6d65_6864, // "mehd"
+    TrackExtendsBox                   0x7472_6578, // "trex"
+    MovieFragmentBox                  0x6d6f_6f66, // "moof"
+    MovieFragmentHeaderBox            0x6d66_6864, // "mfhd"
+    TrackFragmentBox                  0x7472_6166, // "traf"
+    TrackFragmentHeaderBox            0x7466_6864, // "tfhd"
+    TrackFragmentBaseMediaDecodeTimeBox 0x7466_6474, // "tfdt"
+    TrackRunBox                       0x7472_756e, // "trun"
+    SegmentIndexBox                   0x7369_6478, // "sidx"
+    MovieFragmentRandomAccessBox      0x6d66_7261, // "mfra"
+    TrackFragmentRandomAccessBox      0x7466_7261, // "tfra"
+    MovieFragmentRandomAccessOffsetBox 0x6d66_726f, // "mfro"
     QTWaveAtom                        0x7761_7665, // "wave" - quicktime atom
     ProtectionSystemSpecificHeaderBox 0x7073_7368, // "pssh"
     SchemeInformationBox              0x7363_6869, // "schi"
     TrackEncryptionBox                0x7465_6e63, // "tenc"
+    SampleEncryptionBox               0x7365_6e63, // "senc"
+    SampleAuxiliaryInformationSizesBox 0x7361_697a, // "saiz"
+    SampleAuxiliaryInformationOffsetsBox 0x7361_696f, // "saio"
+    SampleGroupDescriptionBox         0x7367_7064, // "sgpd"
+    SampleToGroupBox                  0x7362_6770, // "sbgp"
     ProtectionSchemeInfoBox           0x7369_6e66, // "sinf"
     OriginalFormatBox                 0x6672_6d61, // "frma"
     SchemeTypeBox                     0x7363_686d, // "schm"
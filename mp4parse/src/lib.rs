@@ -30,7 +30,7 @@ use std::collections::HashSet;
 use std::convert::{TryFrom, TryInto as _};
 use std::fmt;
 use std::io::Cursor;
-use std::io::{Read, Take};
+use std::io::{Read, Seek, SeekFrom, Take};
 
 #[macro_use]
 mod macros;
@@ -65,6 +65,18 @@ pub const AVIF_BRAND: FourCC = FourCC { value: *b"avif" };
 /// See <https://aomediacodec.github.io/av1-avif/#image-and-image-collection-brand>
 pub const AVIS_BRAND: FourCC = FourCC { value: *b"avis" };
 
+/// The brands to identify HEVC image items
+/// The 'heic' and 'heix' brands indicate structural requirements on files
+/// See HEIF (ISO 23008-12:2017) § B.4.1
+pub const HEIC_BRAND: FourCC = FourCC { value: *b"heic" };
+pub const HEIX_BRAND: FourCC = FourCC { value: *b"heix" };
+
+/// The brands to identify HEVC image sequences
+/// The 'hevc' and 'hevx' brands indicate structural requirements on files
+/// See HEIF (ISO 23008-12:2017) § B.4.2
+pub const HEVC_BRAND: FourCC = FourCC { value: *b"hevc" };
+pub const HEVX_BRAND: FourCC = FourCC { value: *b"hevx" };
+
 /// A trait to indicate a type can be infallibly converted to `u64`.
 /// This should only be implemented for infallible conversions, so only unsigned types are valid.
 trait ToU64 {
@@ -175,6 +187,7 @@ pub enum Status {
     Eof = 4,
     Io = 5,
     Oom = 6,
+    A1lxBadLayerSize,
     A1lxEssential,
     A1opNoEssential,
     AlacBadMagicCookieSize,
@@ -184,6 +197,7 @@ pub enum Status {
     BoxBadSize,
     BoxBadWideSize,
     CheckParserStateErr,
+    ClapBadCrop,
     ColrBadQuantity,
     ColrBadQuantityBMFF,
     ColrBadSize,
@@ -203,8 +217,12 @@ pub enum Status {
     EsdsBadAudioSampleEntry,
     EsdsBadDescriptor,
     EsdsDecSpecificInfoTagQuantity,
+    ExifBadTiffHeaderOffset,
     FtypBadSize,
     FtypNotFirst,
+    GridBadTileCount,
+    GridBadVersion,
+    GridTileMissing,
     HdlrNameNoNul,
     HdlrNameNotUtf8,
     HdlrNotFirst,
@@ -213,6 +231,7 @@ pub enum Status {
     HdlrTypeNotPict,
     HdlrUnsupportedVersion,
     HdrlBadQuantity,
+    HvccMissing,
     IdatBadQuantity,
     IdatMissing,
     IinfBadChild,
@@ -252,6 +271,7 @@ pub enum Status {
     MehdBadVersion,
     MetaBadQuantity,
     MissingAvifOrAvisBrand,
+    MissingHeicOrHevcBrand,
     MissingMif1Brand,
     MoovBadQuantity,
     MoovMissing,
@@ -266,10 +286,35 @@ pub enum Status {
     PixiMissing,
     PsshSizeOverflow,
     ReadBufErr,
+    SaioBadSize,
+    SaizBadInfo,
+    SaizBadSampleCount,
+    SaizBadSize,
+    SbgpBadIndex,
+    SbgpBadSize,
     SchiQuantity,
+    SencBadSampleCount,
+    SencBadSize,
+    SgpdBadSize,
+    SidxBadReference,
+    SidxBadTimescale,
+    SidxBadVersion,
+    SpsBadBitDepth,
+    SpsBadChromaFormat,
+    SpsBadCrop,
+    SpsBadNalUnitType,
+    SpsBadRefPicSets,
     StsdBadAudioSampleEntry,
     StsdBadVideoSampleEntry,
+    TfdtBadVersion,
+    TfhdBadQuantity,
+    TfraBadSize,
+    TfraBadVersion,
     TkhdBadVersion,
+    TmapBadMetadata,
+    TrunBadSampleCount,
+    TrunBadSize,
+    TrunBadVersion,
     TxformBeforeIspe,
     TxformNoEssential,
     TxformOrder,
@@ -286,6 +331,7 @@ pub enum Feature {
     Clap,
     Colr,
     Grid,
+    Hvcc,
     Imir,
     Ipro,
     Irot,
@@ -298,16 +344,22 @@ pub enum Feature {
 impl Feature {
     fn supported(self) -> bool {
         match self {
-            Self::Auxc
+            Self::A1lx
+            | Self::A1op
+            | Self::Auxc
             | Self::Av1c
             | Self::Avis
+            | Self::Clap
             | Self::Colr
+            | Self::Grid
+            | Self::Hvcc
             | Self::Imir
             | Self::Irot
             | Self::Ispe
+            | Self::Lsel
             | Self::Pasp
             | Self::Pixi => true,
-            Self::A1lx | Self::A1op | Self::Clap | Self::Grid | Self::Ipro | Self::Lsel => false,
+            Self::Ipro => false,
         }
     }
 }
@@ -320,13 +372,14 @@ impl TryFrom<&ItemProperty> for Feature {
             ItemProperty::AuxiliaryType(_) => Self::Auxc,
             ItemProperty::AV1Config(_) => Self::Av1c,
             ItemProperty::Channels(_) => Self::Pixi,
-            ItemProperty::CleanAperture => Self::Clap,
+            ItemProperty::CleanAperture(_) => Self::Clap,
             ItemProperty::Colour(_) => Self::Colr,
+            ItemProperty::HEVCConfig(_) => Self::Hvcc,
             ItemProperty::ImageSpatialExtents(_) => Self::Ispe,
-            ItemProperty::LayeredImageIndexing => Self::A1lx,
+            ItemProperty::LayeredImageIndexing(_) => Self::A1lx,
             ItemProperty::LayerSelection(_) => Self::Lsel,
             ItemProperty::Mirroring(_) => Self::Imir,
-            ItemProperty::OperatingPointSelector => Self::A1op,
+            ItemProperty::OperatingPointSelector(_) => Self::A1op,
             ItemProperty::PixelAspectRatio(_) => Self::Pasp,
             ItemProperty::Rotation(_) => Self::Irot,
             item_property => {
@@ -430,6 +483,10 @@ impl From<Status> for &str {
             | Status::Oom => {
                 panic!("Status -> Error is only for specific parsing errors")
             }
+            Status::A1lxBadLayerSize => {
+                "AV1LayeredImageIndexingProperty (a1lx) layer sizes exceed the size of the item \
+                 per https://aomediacodec.github.io/av1-avif/#layered-image-indexing-property-description"
+            }
             Status::A1lxEssential => {
                 "AV1LayeredImageIndexingProperty (a1lx) shall not be marked as essential \
                  per https://aomediacodec.github.io/av1-avif/#layered-image-indexing-property-description"
@@ -461,6 +518,10 @@ impl From<Status> for &str {
             Status::CheckParserStateErr => {
                 "unread box content or bad parser sync"
             }
+            Status::ClapBadCrop => {
+                "The clean aperture shall be a whole number of pixels within the image \
+                 per MIAF (ISO 23000-22:2019) § 7.3.6.7"
+            }
             Status::ColrBadQuantity => {
                 "Each item shall have at most one property association with a
                  ColourInformationBox (colr) for a given value of colour_type \
@@ -523,6 +584,10 @@ impl From<Status> for &str {
             Status::EsdsDecSpecificInfoTagQuantity => {
                 "There can be only one DecSpecificInfoTag descriptor"
             }
+            Status::ExifBadTiffHeaderOffset => {
+                "Exif item exif_tiff_header_offset shall be within the item \
+                 per HEIF (ISO 23008-12:2017) § A.2.1"
+            }
             Status::FtypBadSize => {
                 "invalid ftyp size"
             }
@@ -530,6 +595,17 @@ impl From<Status> for &str {
                 "The FileTypeBox shall be placed as early as possible in the file \
                  per ISOBMFF (ISO 14496-12:2020) § 4.3.1"
             }
+            Status::GridBadTileCount => {
+                "The number of 'dimg' references of a 'grid' item shall be rows * columns \
+                 per HEIF (ISO 23008-12:2017) § 6.6.2.3.1"
+            }
+            Status::GridBadVersion => {
+                "ImageGrid version shall be 0 \
+                 per HEIF (ISO 23008-12:2017) § 6.6.2.3.2"
+            }
+            Status::GridTileMissing => {
+                "A 'grid' item references a tile item which has no location"
+            }
             Status::HdlrNameNoNul => {
                 "The HandlerBox 'name' field shall be null-terminated \
                  per ISOBMFF (ISO 14496-12:2020) § 8.4.3.2"
@@ -562,6 +638,11 @@ impl From<Status> for &str {
                 "There shall be exactly one hdlr box \
                  per ISOBMFF (ISO 14496-12:2020) § 8.4.3.1"
             }
+            Status::HvccMissing => {
+                "One HEVC configuration item property (hvcC) is mandatory for an \
+                 image item of type 'hvc1' \
+                 per HEIF (ISO 23008-12:2017) § B.2.3.3"
+            }
             Status::IdatBadQuantity => {
                 "There shall be zero or one idat boxes \
                  per ISOBMFF (ISO 14496-12:2020) § 8.11.11"
@@ -610,11 +691,11 @@ impl From<Status> for &str {
                 "offset calculation overflow"
             }
             Status::ImageItemType => {
-                "Image item type is neither 'av01' nor 'grid'"
+                "Image item type is neither the coded image type ('av01' or 'hvc1') nor 'grid'"
             }
             Status::InfeFlagsNonzero => {
-                "'infe' flags field shall be 0 \
-                 per ISOBMFF (ISO 14496-12:2020) § 8.11.6.2"
+                "'infe' flags other than the hidden flag shall be 0 \
+                 per ISOBMFF (ISO 14496-12:2020) § 8.11.6.2 and HEIF (ISO 23008-12:2017) § 6.4.2"
             }
             Status::InvalidUtf8 => {
                 "invalid utf8"
@@ -703,6 +784,11 @@ impl From<Status> for &str {
                  of the FileTypeBox \
                  per https://aomediacodec.github.io/av1-avif/#file-constraints"
             }
+            Status::MissingHeicOrHevcBrand => {
+                "The file shall list 'heic', 'heix', 'hevc' or 'hevx' in the \
+                 compatible_brands field of the FileTypeBox \
+                 per HEIF (ISO 23008-12:2017) § B.4"
+            }
             Status::MissingMif1Brand => {
                 "The FileTypeBox should contain 'mif1' in the compatible_brands list \
                  per MIAF (ISO 23000-22:2019/Amd. 2:2021) § 7.2.1.2"
@@ -752,18 +838,98 @@ impl From<Status> for &str {
             Status::ReadBufErr => {
                 "failed buffer read"
             }
+            Status::SaioBadSize => {
+                "saio entry_count exceeds the size of the box"
+            }
+            Status::SaizBadInfo => {
+                "sample auxiliary information doesn't match its saiz size"
+            }
+            Status::SaizBadSampleCount => {
+                "saiz sample_count doesn't match the samples of its track fragment"
+            }
+            Status::SaizBadSize => {
+                "saiz sample_count exceeds the size of the box"
+            }
+            Status::SbgpBadIndex => {
+                "sbgp group_description_index doesn't match an sgpd entry"
+            }
+            Status::SbgpBadSize => {
+                "sbgp entry_count exceeds the size of the box"
+            }
             Status::SchiQuantity => {
                 "tenc box should be only one at most in sinf box"
             }
+            Status::SencBadSampleCount => {
+                "senc sample_count doesn't match the samples of its track fragment"
+            }
+            Status::SencBadSize => {
+                "senc sample_count exceeds the size of the box"
+            }
+            Status::SgpdBadSize => {
+                "sgpd entry_count exceeds the size of the box"
+            }
+            Status::SidxBadReference => {
+                "sidx references a segment index that wasn't found"
+            }
+            Status::SidxBadTimescale => {
+                "sidx timescales differ within a track"
+            }
+            Status::SidxBadVersion => {
+                "unhandled sidx version"
+            }
+            Status::SpsBadBitDepth => {
+                "SPS bit depth exceeds the maximum \
+                 per ITU-T H.264 (08/2021) § 7.4.2.1.1 and H.265 (08/2021) § 7.4.3.2.1"
+            }
+            Status::SpsBadChromaFormat => {
+                "SPS chroma_format_idc shall be in the range of 0 to 3 \
+                 per ITU-T H.264 (08/2021) § 7.4.2.1.1 and H.265 (08/2021) § 7.4.3.2.1"
+            }
+            Status::SpsBadCrop => {
+                "SPS cropping window is empty or exceeds the decoded picture"
+            }
+            Status::SpsBadNalUnitType => {
+                "parameter set NAL unit isn't an SPS"
+            }
+            Status::SpsBadRefPicSets => {
+                "SPS short-term reference picture sets exceed the maximum \
+                 per ITU-T H.265 (08/2021) § 7.4.3.2.1 and § 7.4.8"
+            }
             Status::StsdBadAudioSampleEntry => {
                 "malformed audio sample entry"
             }
             Status::StsdBadVideoSampleEntry => {
                 "malformed video sample entry"
             }
+            Status::TfdtBadVersion => {
+                "unhandled tfdt version"
+            }
+            Status::TfhdBadQuantity => {
+                "There shall be exactly one tfhd box in a traf box \
+                 per ISOBMFF (ISO 14496-12:2020) § 8.8.7.1"
+            }
+            Status::TfraBadSize => {
+                "tfra number_of_entry exceeds the size of the box"
+            }
+            Status::TfraBadVersion => {
+                "unhandled tfra version"
+            }
             Status::TkhdBadVersion => {
                 "unhandled tkhd version"
             }
+            Status::TmapBadMetadata => {
+                "ToneMapImage (tmap) gain map metadata is truncated or has a zero denominator \
+                 per ISO 21496-1:2025 § C.2.2"
+            }
+            Status::TrunBadSampleCount => {
+                "trun sample_count exceeds the size of the mdat content holding the samples"
+            }
+            Status::TrunBadSize => {
+                "trun sample_count exceeds the size of the box"
+            }
+            Status::TrunBadVersion => {
+                "unhandled trun version"
+            }
             Status::TxformBeforeIspe => {
                 "Every image item shall be associated with one property of \
                  type ImageSpatialExtentsProperty (ispe), prior to the \
@@ -1096,6 +1262,34 @@ pub enum SampleEntry {
     Unknown,
 }
 
+impl SampleEntry {
+    /// The codecs parameter of the MIME type of the sample entry's media, as
+    /// used by MSE and DASH manifests, e.g. "avc1.64001f" or "mp4a.40.2".
+    /// See RFC 6381 § 3
+    pub fn codec_string(&self) -> Result<TryString> {
+        match self {
+            SampleEntry::Audio(audio) => audio.codec_string(),
+            SampleEntry::Video(video) => video.codec_string(),
+            SampleEntry::Unknown => Err(Error::Unsupported("no codec string for sample entry")),
+        }
+    }
+}
+
+/// Append formatted text to a codec string, failing only if allocating fails.
+fn write_codec_string(codec_string: &mut TryString, args: fmt::Arguments) -> Result<()> {
+    struct Writer<'a>(&'a mut TryString);
+
+    impl fmt::Write for Writer<'_> {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.0
+                .extend_from_slice(s.as_bytes())
+                .map_err(|_| fmt::Error)
+        }
+    }
+
+    fmt::Write::write_fmt(&mut Writer(codec_string), args).map_err(|_| Error::OutOfMemory)
+}
+
 #[derive(Debug)]
 pub struct TrackReferenceBox {
     pub references: TryVec<TrackReferenceEntry>,
@@ -1125,8 +1319,14 @@ pub struct TrackReference {
 #[derive(Debug, Default)]
 pub struct ES_Descriptor {
     pub audio_codec: CodecType,
+    /// The objectTypeIndication of the DecoderConfigDescriptor, e.g. 0x40
+    /// for MPEG-4 audio
+    pub object_type_indication: Option<u8>,
     pub audio_object_type: Option<u16>,
     pub extended_audio_object_type: Option<u16>,
+    /// The audioObjectType explicitly signalling SBR, 5 for HE-AAC or 29 for
+    /// HE-AAC v2, for which `extended_audio_object_type` is 5 in both cases.
+    pub sbr_audio_object_type: Option<u16>,
     pub audio_sample_rate: Option<u32>,
     pub audio_channel_count: Option<u16>,
     #[cfg(feature = "mp4v")]
@@ -1160,14 +1360,57 @@ pub struct AudioSampleEntry {
     pub protection_info: TryVec<ProtectionSchemeInfoBox>,
 }
 
+impl AudioSampleEntry {
+    /// See [`SampleEntry::codec_string`]
+    pub fn codec_string(&self) -> Result<TryString> {
+        let mut codec_string = TryString::new();
+        match &self.codec_specific {
+            // See RFC 6381 § 3.3
+            AudioCodecSpecific::ES_Descriptor(esds) => match esds.object_type_indication {
+                Some(0x40) => {
+                    // Explicitly signalled SBR is reported as HE-AAC (v2), as
+                    // browsers expect, rather than as the underlying AAC-LC
+                    let audio_object_type =
+                        esds.sbr_audio_object_type
+                            .or(esds.audio_object_type)
+                            .ok_or(Error::Unsupported("no codec string for esds"))?;
+                    write_codec_string(
+                        &mut codec_string,
+                        format_args!("mp4a.40.{audio_object_type}"),
+                    )?;
+                }
+                Some(object_type_indication) => write_codec_string(
+                    &mut codec_string,
+                    format_args!("mp4a.{object_type_indication:02X}"),
+                )?,
+                None => return Err(Error::Unsupported("no codec string for esds")),
+            },
+            AudioCodecSpecific::FLACSpecificBox(_) => codec_string.extend_from_slice(b"fLaC")?,
+            AudioCodecSpecific::OpusSpecificBox(_) => codec_string.extend_from_slice(b"opus")?,
+            AudioCodecSpecific::ALACSpecificBox(_) => codec_string.extend_from_slice(b"alac")?,
+            AudioCodecSpecific::MP3 => codec_string.extend_from_slice(b"mp3")?,
+            AudioCodecSpecific::LPCM => codec_string.extend_from_slice(b"lpcm")?,
+            #[cfg(feature = "3gpp")]
+            AudioCodecSpecific::AMRSpecificBox(_) => {
+                if self.codec_type == CodecType::AMRWB {
+                    codec_string.extend_from_slice(b"sawb")?
+                } else {
+                    codec_string.extend_from_slice(b"samr")?
+                }
+            }
+        }
+        Ok(codec_string)
+    }
+}
+
 #[derive(Debug)]
 pub enum VideoCodecSpecific {
-    AVCConfig(TryVec<u8>),
+    AVCConfig(AVCConfigBox),
     VPxConfig(VPxConfigBox),
     AV1Config(AV1ConfigBox),
     ESDSConfig(TryVec<u8>),
     H263Config(TryVec<u8>),
-    HEVCConfig(TryVec<u8>),
+    HEVCConfig(HEVCConfigBox),
 }
 
 /// Mastering display colour volume from an `mdcv` box (ISO 14496-12).
@@ -1185,6 +1428,25 @@ pub struct MasteringDisplayColourVolume {
     pub min_display_mastering_luminance: u32,
 }
 
+/// The DOVIDecoderConfigurationRecord of a 'dvcC', 'dvvC' or 'dvwC' box.
+/// See Dolby Vision Streams Within the ISO Base Media File Format v2.5 § 3.2
+#[derive(Debug, Clone)]
+pub struct DolbyVisionConfigBox {
+    pub dv_version_major: u8,
+    pub dv_version_minor: u8,
+    pub dv_profile: u8,
+    pub dv_level: u8,
+    /// Whether the stream has reference processing unit (RPU) metadata
+    pub rpu_present: bool,
+    /// Whether the stream has an enhancement layer
+    pub el_present: bool,
+    /// Whether the stream has a base layer
+    pub bl_present: bool,
+    /// Which non-Dolby Vision decoders can present the base layer, e.g. 1
+    /// for HDR10 or 4 for HLG; 0 if it isn't compatible
+    pub dv_bl_signal_compatibility_id: u8,
+}
+
 /// Content light level from a `clli` box (ISO 14496-12).
 #[derive(Debug, Clone)]
 pub struct ContentLightLevel {
@@ -1197,6 +1459,9 @@ pub struct ContentLightLevel {
 #[derive(Debug)]
 pub struct VideoSampleEntry {
     pub codec_type: CodecType,
+    /// The box type of the sample entry, e.g. 'avc3' or 'hev1', which
+    /// is 'encv' for encrypted entries
+    pub sample_entry_type: FourCC,
     #[allow(dead_code)] // See https://github.com/mozilla/mp4parse-rust/issues/340
     data_reference_index: u16,
     pub width: u16,
@@ -1204,6 +1469,7 @@ pub struct VideoSampleEntry {
     pub codec_specific: VideoCodecSpecific,
     pub protection_info: TryVec<ProtectionSchemeInfoBox>,
     pub pixel_aspect_ratio: Option<f32>,
+    pub clean_aperture: Option<CleanApertureBox>,
     /// Only `ColourInformation::Nclx` is currently surfaced through the C API;
     /// `ColourInformation::Icc` is stored but not exposed to C consumers.
     pub colour_info: Option<ColourInformation>,
@@ -1211,6 +1477,132 @@ pub struct VideoSampleEntry {
     pub hdr_mastering_display: Option<MasteringDisplayColourVolume>,
     /// Content light level from the `clli` box (ISO 14496-12).
     pub hdr_content_light_level: Option<ContentLightLevel>,
+    /// The Dolby Vision configuration from a 'dvcC', 'dvvC' or 'dvwC' box.
+    /// Dolby Vision sample entries ('dva1', 'dvh1', 'dvhe' and 'dav1') have
+    /// the `codec_type` of their base layer codec only if the base layer is
+    /// compatible with other decoders, and `CodecType::DolbyVision`
+    /// otherwise, while other entries with this configuration have a
+    /// backward compatible base layer.
+    pub dolby_vision_config: Option<DolbyVisionConfigBox>,
+}
+
+impl VideoSampleEntry {
+    /// The region of the video to display, or None if there's no 'clap' box
+    /// or it isn't valid for the sample entry's size.
+    pub fn crop_rectangle(&self) -> Option<CropRectangle> {
+        self.clean_aperture?
+            .crop_rectangle(self.width.into(), self.height.into())
+    }
+
+    /// Whether this is a Dolby Vision sample entry whose base layer, if any,
+    /// can't be presented by a decoder of its codec, as for profile 5.
+    pub fn requires_dolby_vision(&self) -> bool {
+        self.is_dolby_vision_entry()
+            && self
+                .dolby_vision_config
+                .as_ref()
+                .is_none_or(|dovi| dovi.dv_bl_signal_compatibility_id == 0)
+    }
+
+    /// The type of the sample entry, or its original format if encrypted.
+    fn original_format(&self) -> &FourCC {
+        match self.protection_info.first() {
+            Some(sinf) if self.codec_type == CodecType::EncryptedVideo => &sinf.original_format,
+            _ => &self.sample_entry_type,
+        }
+    }
+
+    fn is_dolby_vision_entry(&self) -> bool {
+        matches!(
+            &self.original_format().value,
+            b"dva1" | b"dvh1" | b"dvhe" | b"dav1"
+        )
+    }
+
+    /// See [`SampleEntry::codec_string`]
+    pub fn codec_string(&self) -> Result<TryString> {
+        // Encrypted entries are described by their original format
+        let sample_entry_type = std::str::from_utf8(&self.original_format().value)?;
+        let mut codec_string = TryString::new();
+        // See Dolby Vision Profiles and Levels v1.3.3 § 5.1
+        if self.is_dolby_vision_entry() {
+            let dovi = self.dolby_vision_config.as_ref().ok_or(Error::Unsupported(
+                "no codec string without Dolby Vision config",
+            ))?;
+            write_codec_string(
+                &mut codec_string,
+                format_args!(
+                    "{sample_entry_type}.{:02}.{:02}",
+                    dovi.dv_profile, dovi.dv_level
+                ),
+            )?;
+            return Ok(codec_string);
+        }
+        match &self.codec_specific {
+            // See RFC 6381 § 3.3
+            VideoCodecSpecific::AVCConfig(avcc) => {
+                if !avcc.parsed {
+                    return Err(Error::Unsupported("no codec string for unparsed avcC"));
+                }
+                write_codec_string(
+                    &mut codec_string,
+                    format_args!(
+                        "{sample_entry_type}.{:02x}{:02x}{:02x}",
+                        avcc.profile_indication, avcc.profile_compatibility, avcc.level_indication
+                    ),
+                )?;
+            }
+            // See ISO 14496-15:2019 § E.3
+            VideoCodecSpecific::HEVCConfig(hvcc) => {
+                if !hvcc.parsed {
+                    return Err(Error::Unsupported("no codec string for unparsed hvcC"));
+                }
+                let profile_space = ["", "A", "B", "C"][usize::from(hvcc.general_profile_space)];
+                let tier = if hvcc.general_tier_flag { 'H' } else { 'L' };
+                write_codec_string(
+                    &mut codec_string,
+                    format_args!(
+                        "{sample_entry_type}.{profile_space}{}.{:X}.{tier}{}",
+                        hvcc.general_profile_idc,
+                        hvcc.general_profile_compatibility_flags.reverse_bits(),
+                        hvcc.general_level_idc
+                    ),
+                )?;
+                // Trailing zero bytes of the constraint flags are omitted
+                let constraint_flags = &hvcc.general_constraint_indicator_flags.to_be_bytes()[2..];
+                let len = constraint_flags
+                    .iter()
+                    .rposition(|&byte| byte != 0)
+                    .map_or(0, |index| index + 1);
+                for byte in &constraint_flags[..len] {
+                    write_codec_string(&mut codec_string, format_args!(".{byte:02X}"))?;
+                }
+            }
+            // See VP Codec ISO Media File Format Binding § Codecs Parameter String
+            VideoCodecSpecific::VPxConfig(vpcc) => write_codec_string(
+                &mut codec_string,
+                format_args!(
+                    "{sample_entry_type}.{:02}.{:02}.{:02}",
+                    vpcc.profile, vpcc.level, vpcc.bit_depth
+                ),
+            )?,
+            // See AV1-ISOBMFF § 5
+            VideoCodecSpecific::AV1Config(av1c) => {
+                let tier = if av1c.tier == 0 { 'M' } else { 'H' };
+                write_codec_string(
+                    &mut codec_string,
+                    format_args!(
+                        "{sample_entry_type}.{}.{:02}{tier}.{:02}",
+                        av1c.profile, av1c.level, av1c.bit_depth
+                    ),
+                )?;
+            }
+            VideoCodecSpecific::ESDSConfig(_) | VideoCodecSpecific::H263Config(_) => {
+                codec_string.extend_from_slice(sample_entry_type.as_bytes())?
+            }
+        }
+        Ok(codec_string)
+    }
 }
 
 /// Represent a Video Partition Codec Configuration 'vpcC' box (aka vp9). The meaning of each
@@ -1218,11 +1610,9 @@ pub struct VideoSampleEntry {
 #[derive(Debug)]
 pub struct VPxConfigBox {
     /// An integer that specifies the VP codec profile.
-    #[allow(dead_code)] // See https://github.com/mozilla/mp4parse-rust/issues/340
     profile: u8,
     /// An integer that specifies a VP codec level all samples conform to the following table.
     /// For a description of the various levels, please refer to the VP9 Bitstream Specification.
-    #[allow(dead_code)] // See https://github.com/mozilla/mp4parse-rust/issues/340
     level: u8,
     /// An integer that specifies the bit depth of the luma and color components. Valid values
     /// are 8, 10, and 12.
@@ -1259,6 +1649,8 @@ pub struct AV1ConfigBox {
     pub chroma_sample_position: u8,
     pub initial_presentation_delay_present: bool,
     pub initial_presentation_delay_minus_one: u8,
+    /// The sequence header OBU of the configOBUs, if present and parsable
+    pub sequence_header: Option<AV1SequenceHeader>,
     // The raw config contained in the av1c box. Because some decoders accept this data as a binary
     // blob, rather than as structured data, we store the blob here for convenience.
     pub raw_config: TryVec<u8>,
@@ -1272,6 +1664,214 @@ impl AV1ConfigBox {
     }
 }
 
+/// The fields of an AV1 sequence header OBU describing the coded video.
+/// See [AV1 § 5.5.1](https://aomediacodec.github.io/av1-spec/#general-sequence-header-obu-syntax)
+#[derive(Debug)]
+pub struct AV1SequenceHeader {
+    pub seq_profile: u8,
+    pub still_picture: bool,
+    pub reduced_still_picture_header: bool,
+    pub timing_info: Option<AV1TimingInfo>,
+    /// At least one operating point, the first being the one decoders select
+    /// by default
+    pub operating_points: TryVec<AV1OperatingPoint>,
+    pub max_frame_width: u32,
+    pub max_frame_height: u32,
+    pub color_config: AV1ColorConfig,
+    pub film_grain_params_present: bool,
+}
+
+/// See [AV1 § 5.5.3](https://aomediacodec.github.io/av1-spec/#timing-info-syntax)
+#[derive(Debug)]
+pub struct AV1TimingInfo {
+    pub num_units_in_display_tick: u32,
+    pub time_scale: u32,
+    /// The number of ticks per picture if pictures are equally spaced
+    pub num_ticks_per_picture: Option<u64>,
+}
+
+#[derive(Debug)]
+pub struct AV1OperatingPoint {
+    /// The spatial and temporal layers decoded for the operating point, or
+    /// 0 if all of them are
+    pub idc: u16,
+    pub seq_level_idx: u8,
+    pub seq_tier: u8,
+}
+
+/// See [AV1 § 5.5.2](https://aomediacodec.github.io/av1-spec/#color-config-syntax)
+#[derive(Debug)]
+pub struct AV1ColorConfig {
+    pub bit_depth: u8,
+    pub mono_chrome: bool,
+    pub color_description_present: bool,
+    /// Really an enum defined by ISO 23091-2:2019 § 8.1, 2 (unspecified)
+    /// unless `color_description_present`
+    pub color_primaries: u8,
+    /// Really an enum defined by ISO 23091-2:2019 § 8.2, 2 (unspecified)
+    /// unless `color_description_present`
+    pub transfer_characteristics: u8,
+    /// Really an enum defined by ISO 23091-2:2019 § 8.3, 2 (unspecified)
+    /// unless `color_description_present`
+    pub matrix_coefficients: u8,
+    pub full_range: bool,
+    pub subsampling_x: bool,
+    pub subsampling_y: bool,
+    pub chroma_sample_position: u8,
+    pub separate_uv_delta_q: bool,
+}
+
+impl AV1ColorConfig {
+    /// The colour description as an 'nclx' 'colr' box would give it, for
+    /// images and tracks without one.
+    pub fn colour_information(&self) -> Option<NclxColourInformation> {
+        if !self.color_description_present {
+            return None;
+        }
+        Some(NclxColourInformation {
+            colour_primaries: self.color_primaries,
+            transfer_characteristics: self.transfer_characteristics,
+            matrix_coefficients: self.matrix_coefficients,
+            full_range_flag: self.full_range,
+        })
+    }
+}
+
+/// See ISO 14496-15:2019 § 5.3.2.1
+///
+/// Unless parsing strictly, an avcC box which can't be parsed results in
+/// only `raw_config` being set.
+#[derive(Debug, Default)]
+pub struct AVCConfigBox {
+    pub profile_indication: u8,
+    pub profile_compatibility: u8,
+    pub level_indication: u8,
+    /// The size in bytes of the length field preceding each NAL unit in the
+    /// samples; 1, 2 or 4.
+    pub nal_unit_length_size: u8,
+    /// The parameter set NAL units, including their NAL unit headers
+    pub sequence_parameter_sets: TryVec<TryVec<u8>>,
+    pub picture_parameter_sets: TryVec<TryVec<u8>>,
+    /// Only present for the profiles with chroma format and bit depth
+    /// extensions, and even then omitted by some muxers.
+    pub sequence_parameter_set_extensions: TryVec<TryVec<u8>>,
+    /// The first of `sequence_parameter_sets`, if it could be parsed
+    pub sps: Option<AVCSequenceParameterSet>,
+    /// Whether the record could be parsed. When false, only `raw_config` is
+    /// set.
+    pub parsed: bool,
+    // The raw config contained in the avcC box, which decoders generally
+    // accept as a binary blob.
+    pub raw_config: TryVec<u8>,
+}
+
+/// The fields of an H.264 sequence parameter set describing the decoded
+/// pictures.
+/// See ITU-T H.264 (08/2021) § 7.3.2.1.1 and Annex E.1.1
+#[derive(Debug)]
+pub struct AVCSequenceParameterSet {
+    pub profile_idc: u8,
+    /// The `constraint_set0_flag` to `constraint_set5_flag` bits, from the most
+    /// significant bit
+    pub constraint_set_flags: u8,
+    pub level_idc: u8,
+    /// 0 for monochrome, 1 for 4:2:0, 2 for 4:2:2 and 3 for 4:4:4
+    pub chroma_format_idc: u8,
+    pub bit_depth_luma: u8,
+    pub bit_depth_chroma: u8,
+    /// The size of the decoded pictures, which is a whole number of macroblocks
+    pub coded_width: u32,
+    pub coded_height: u32,
+    /// The region of the decoded pictures to output, from the frame cropping
+    /// offsets
+    pub crop: CropRectangle,
+    /// The sample aspect ratio as (width, height), if the VUI specifies it
+    pub sample_aspect_ratio: Option<(u16, u16)>,
+    /// The colour description of the VUI, if present
+    pub colour_description: Option<NclxColourInformation>,
+}
+
+/// See ISO 14496-15:2019 § 8.3.3.1
+///
+/// As for [`AVCConfigBox`], an hvcC box which can't be parsed results in only
+/// `raw_config` being set unless parsing strictly.
+#[derive(Debug, Default)]
+pub struct HEVCConfigBox {
+    pub general_profile_space: u8,
+    pub general_tier_flag: bool,
+    pub general_profile_idc: u8,
+    pub general_profile_compatibility_flags: u32,
+    /// The 48 bits of the general constraint indicator flags
+    pub general_constraint_indicator_flags: u64,
+    pub general_level_idc: u8,
+    /// 0 for monochrome, 1 for 4:2:0, 2 for 4:2:2 and 3 for 4:4:4
+    pub chroma_format_idc: u8,
+    pub bit_depth_luma: u8,
+    pub bit_depth_chroma: u8,
+    /// The size in bytes of the length field preceding each NAL unit in the
+    /// samples; 1, 2 or 4.
+    pub nal_unit_length_size: u8,
+    /// The arrays of parameter set and SEI NAL units, typically one array
+    /// each of VPS, SPS and PPS NAL units
+    pub nal_unit_arrays: TryVec<HEVCNalUnitArray>,
+    /// The first SPS of `nal_unit_arrays`, if it could be parsed
+    pub sps: Option<HEVCSequenceParameterSet>,
+    /// Whether the record could be parsed. When false, only `raw_config` is
+    /// set.
+    pub parsed: bool,
+    // The raw config contained in the hvcC box, which decoders generally
+    // accept as a binary blob.
+    pub raw_config: TryVec<u8>,
+}
+
+impl HEVCConfigBox {
+    pub const NAL_UNIT_TYPE_VPS: u8 = 32;
+    pub const NAL_UNIT_TYPE_SPS: u8 = 33;
+    pub const NAL_UNIT_TYPE_PPS: u8 = 34;
+
+    /// The NAL units of type `nal_unit_type`, including their NAL unit
+    /// headers, in the order of `nal_unit_arrays`.
+    pub fn nal_units(&self, nal_unit_type: u8) -> impl Iterator<Item = &[u8]> {
+        self.nal_unit_arrays
+            .iter()
+            .filter(move |array| array.nal_unit_type == nal_unit_type)
+            .flat_map(|array| array.nal_units.iter())
+            .map(|nal_unit| nal_unit.as_slice())
+    }
+}
+
+/// See ISO 14496-15:2019 § 8.3.3.1
+#[derive(Debug)]
+pub struct HEVCNalUnitArray {
+    /// True if all NAL units of `nal_unit_type` are in this array rather
+    /// than in the samples
+    pub array_completeness: bool,
+    pub nal_unit_type: u8,
+    /// The NAL units, including their NAL unit headers
+    pub nal_units: TryVec<TryVec<u8>>,
+}
+
+/// The fields of an H.265 sequence parameter set describing the decoded
+/// pictures.
+/// See ITU-T H.265 (08/2021) § 7.3.2.2.1 and Annex E.2.1
+#[derive(Debug)]
+pub struct HEVCSequenceParameterSet {
+    /// 0 for monochrome, 1 for 4:2:0, 2 for 4:2:2 and 3 for 4:4:4
+    pub chroma_format_idc: u8,
+    pub bit_depth_luma: u8,
+    pub bit_depth_chroma: u8,
+    /// The size of the decoded pictures in luma samples
+    pub width: u32,
+    pub height: u32,
+    /// The region of the decoded pictures to output, from the conformance
+    /// window offsets
+    pub conformance_window: CropRectangle,
+    /// The sample aspect ratio as (width, height), if the VUI specifies it
+    pub sample_aspect_ratio: Option<(u16, u16)>,
+    /// The colour description of the VUI, if present
+    pub colour_description: Option<NclxColourInformation>,
+}
+
 #[derive(Debug)]
 pub struct FLACMetadataBlock {
     pub block_type: u8,
@@ -1316,12 +1916,208 @@ pub struct ALACSpecificBox {
 #[derive(Debug)]
 pub struct MovieExtendsBox {
     pub fragment_duration: Option<MediaScaledTime>,
+    pub trex: TryVec<TrackExtendsBox>,
+}
+
+impl MovieExtendsBox {
+    /// Return the 'trex' defaults for the given track, if any.
+    pub fn track_extends(&self, track_id: u32) -> Option<&TrackExtendsBox> {
+        self.trex.iter().find(|trex| trex.track_id == track_id)
+    }
+}
+
+/// Track extends box 'trex'
+/// See ISOBMFF (ISO 14496-12:2020) § 8.8.3
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TrackExtendsBox {
+    pub track_id: u32,
+    pub default_sample_description_index: u32,
+    pub default_sample_duration: u32,
+    pub default_sample_size: u32,
+    pub default_sample_flags: u32,
+}
+
+/// Movie fragment box 'moof'
+/// See ISOBMFF (ISO 14496-12:2020) § 8.8.4
+#[derive(Debug, Default)]
+pub struct MovieFragmentBox {
+    /// File offset of the first byte of the 'moof' box, which is the
+    /// default base for sample data offsets of its track fragments.
+    pub offset: u64,
+    /// The 'mfhd' sequence number.
+    pub sequence_number: u32,
+    pub trafs: TryVec<TrackFragmentBox>,
+    /// 'pssh' boxes delivered with the fragment, e.g. for key rotation.
+    pub psshs: TryVec<ProtectionSystemSpecificHeaderBox>,
+}
+
+/// Track fragment box 'traf'
+/// See ISOBMFF (ISO 14496-12:2020) § 8.8.6
+#[derive(Debug, Default)]
+pub struct TrackFragmentBox {
+    pub tfhd: TrackFragmentHeaderBox,
+    /// The 'tfdt' decode time of the first sample in the fragment, in the
+    /// media timescale.
+    pub base_media_decode_time: Option<u64>,
+    pub truns: TryVec<TrackRunBox>,
+    pub senc: Option<SampleEncryptionBox>,
+    pub saiz: Option<SampleAuxiliaryInformationSizesBox>,
+    pub saio: Option<SampleAuxiliaryInformationOffsetsBox>,
+    /// The auxiliary information located by `saiz` and `saio`, in sample
+    /// order, if the fragment has no `senc` and it was read from the file.
+    pub aux_info: Option<TryVec<u8>>,
+    pub sgpds: TryVec<SampleGroupDescriptionBox>,
+    pub sbgps: TryVec<SampleToGroupBox>,
+}
+
+/// Track fragment header box 'tfhd'
+/// See ISOBMFF (ISO 14496-12:2020) § 8.8.7
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TrackFragmentHeaderBox {
+    pub track_id: u32,
+    pub base_data_offset: Option<u64>,
+    pub sample_description_index: Option<u32>,
+    pub default_sample_duration: Option<u32>,
+    pub default_sample_size: Option<u32>,
+    pub default_sample_flags: Option<u32>,
+    pub duration_is_empty: bool,
+    pub default_base_is_moof: bool,
+}
+
+/// Track fragment run box 'trun'
+/// See ISOBMFF (ISO 14496-12:2020) § 8.8.8
+#[derive(Debug, Default)]
+pub struct TrackRunBox {
+    /// Offset of the run's data relative to the track fragment's base data
+    /// offset. When absent, the data immediately follows the previous run.
+    pub data_offset: Option<i32>,
+    pub first_sample_flags: Option<u32>,
+    pub sample_count: u32,
+    /// Per-sample fields, empty when the run carries none of them.
+    pub samples: TryVec<TrackRunSample>,
+}
+
+impl TrackRunBox {
+    /// Return the fields recorded for sample `index` of the run.
+    pub fn sample(&self, index: usize) -> TrackRunSample {
+        self.samples.get(index).copied().unwrap_or_default()
+    }
+}
+
+/// Per-sample fields of a 'trun'. Fields absent from the run take their
+/// values from 'tfhd' or 'trex'.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TrackRunSample {
+    pub duration: Option<u32>,
+    pub size: Option<u32>,
+    pub flags: Option<u32>,
+    pub composition_time_offset: Option<i64>,
+}
+
+/// Segment index box 'sidx'
+/// See ISOBMFF (ISO 14496-12:2020) § 8.16.3
+#[derive(Debug, Default)]
+pub struct SegmentIndexBox {
+    /// File offset of the first byte of the 'sidx' box, by which it's
+    /// found when referenced from another 'sidx'.
+    pub offset: u64,
+    /// File offset of the first byte after the 'sidx' box, from which
+    /// `first_offset` is measured.
+    pub anchor_point: u64,
+    pub reference_id: u32,
+    pub timescale: u32,
+    pub earliest_presentation_time: u64,
+    pub first_offset: u64,
+    pub references: TryVec<SegmentReference>,
+}
+
+/// A reference from a 'sidx' to a subsegment or to another 'sidx'.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SegmentReference {
+    /// True if the reference is to another 'sidx' rather than to media.
+    pub references_index: bool,
+    pub referenced_size: u32,
+    pub subsegment_duration: u32,
+    pub starts_with_sap: bool,
+    pub sap_type: u8,
+    pub sap_delta_time: u32,
+}
+
+/// A media subsegment described by the segment indexes of a track, as
+/// returned by [`MediaContext::segment_index`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Segment {
+    /// File offset of the first byte of the subsegment.
+    pub offset: u64,
+    pub size: u64,
+    /// Earliest presentation time of the subsegment, in the timescale of
+    /// the 'sidx'.
+    pub start_time: u64,
+    pub duration: u64,
+    pub starts_with_sap: bool,
+}
+
+/// Movie fragment random access box 'mfra'
+/// See ISOBMFF (ISO 14496-12:2020) § 8.8.9
+#[derive(Debug, Default)]
+pub struct MovieFragmentRandomAccessBox {
+    pub tfras: TryVec<TrackFragmentRandomAccessBox>,
+}
+
+impl MovieFragmentRandomAccessBox {
+    /// Find the last random access point of `track_id` at or before `time`,
+    /// in the media timescale of the track.
+    ///
+    /// Returns None if the track has no random access point that early.
+    pub fn find_random_access_point(
+        &self,
+        track_id: u32,
+        time: u64,
+    ) -> Option<&TrackFragmentRandomAccessEntry> {
+        self.tfras
+            .iter()
+            .filter(|tfra| tfra.track_id == track_id)
+            .flat_map(|tfra| tfra.entries.iter())
+            .filter(|entry| entry.time <= time)
+            .max_by_key(|entry| entry.time)
+    }
+}
+
+/// Track fragment random access box 'tfra'
+/// See ISOBMFF (ISO 14496-12:2020) § 8.8.10
+#[derive(Debug, Default)]
+pub struct TrackFragmentRandomAccessBox {
+    pub track_id: u32,
+    pub entries: TryVec<TrackFragmentRandomAccessEntry>,
+}
+
+/// A random access sample within a movie fragment.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TrackFragmentRandomAccessEntry {
+    /// Presentation time of the sample, in the media timescale.
+    pub time: u64,
+    /// File offset of the first byte of the 'moof' containing the sample.
+    pub moof_offset: u64,
+    /// 1-based index of the 'traf' within the 'moof'.
+    pub traf_number: u32,
+    /// 1-based index of the 'trun' within the 'traf'.
+    pub trun_number: u32,
+    /// 1-based index of the sample within the 'trun'.
+    pub sample_number: u32,
+}
+
+/// The subsegments of a track, in file order.
+#[derive(Debug, Default)]
+pub struct SegmentIndex {
+    pub timescale: u32,
+    pub segments: TryVec<Segment>,
 }
 
 pub type ByteData = TryVec<u8>;
 
 #[derive(Debug, Default)]
 pub struct ProtectionSystemSpecificHeaderBox {
+    pub version: u8,
     pub system_id: ByteData,
     pub kid: TryVec<ByteData>,
     pub data: ByteData,
@@ -1348,6 +2144,215 @@ pub struct TrackEncryptionBox {
     // End pattern encryption scheme members
 }
 
+/// Sample encryption box 'senc'
+/// See Common Encryption (ISO 23001-7:2016) § 7.2
+#[derive(Debug, Default)]
+pub struct SampleEncryptionBox {
+    /// Whether each sample has a subsample map.
+    pub uses_subsamples: bool,
+    pub sample_count: u32,
+    /// The per-sample entries, still encoded since their layout depends on
+    /// the IV size from 'tenc'; see [`SampleEncryptionBox::entries`].
+    pub data: TryVec<u8>,
+}
+
+impl SampleEncryptionBox {
+    /// Decode the per-sample entries, given the `Per_Sample_IV_Size` of the
+    /// track's 'tenc'.
+    pub fn entries(&self, iv_size: u8) -> Result<TryVec<SampleEncryptionEntry>> {
+        let min_entry_size = u64::from(iv_size) + if self.uses_subsamples { 2 } else { 0 };
+        if u64::from(self.sample_count) * min_entry_size > self.data.len().to_u64() {
+            return Status::SencBadSize.into();
+        }
+        self.entries_with_iv_sizes(|_| iv_size)
+    }
+
+    /// Like [`SampleEncryptionBox::entries`], for when the IV size varies by
+    /// sample, as with 'seig' sample groups. `iv_size` is called with the
+    /// index of each sample.
+    pub fn entries_with_iv_sizes<F: Fn(usize) -> u8>(
+        &self,
+        iv_size: F,
+    ) -> Result<TryVec<SampleEncryptionEntry>> {
+        let min_entry_size = if self.uses_subsamples { 2 } else { 0 };
+        if u64::from(self.sample_count) * min_entry_size > self.data.len().to_u64() {
+            return Status::SencBadSize.into();
+        }
+
+        let src = &mut Cursor::new(self.data.as_slice());
+        let mut entries = TryVec::with_capacity(self.sample_count.to_usize())?;
+        for i in 0..self.sample_count.to_usize() {
+            let iv = read_buf(src, iv_size(i).into()).map_err(|_| Status::SencBadSize)?;
+            let mut subsamples = TryVec::new();
+            if self.uses_subsamples {
+                let subsample_count = be_u16(src)?;
+                let bytes_left = self.data.len().to_u64() - src.position();
+                if u64::from(subsample_count) * 6 > bytes_left {
+                    return Status::SencBadSize.into();
+                }
+                subsamples.reserve(subsample_count.into())?;
+                for _ in 0..subsample_count {
+                    subsamples.push(SubsampleEntry {
+                        bytes_of_clear_data: be_u16(src)?,
+                        bytes_of_protected_data: be_u32(src)?,
+                    })?;
+                }
+            }
+            entries.push(SampleEncryptionEntry { iv, subsamples })?;
+        }
+        Ok(entries)
+    }
+}
+
+/// The encryption parameters of a single sample.
+#[derive(Debug, Default)]
+pub struct SampleEncryptionEntry {
+    /// The initialization vector, empty when the track uses a constant IV.
+    pub iv: TryVec<u8>,
+    /// The clear and protected ranges of the sample, empty when the whole
+    /// sample is protected.
+    pub subsamples: TryVec<SubsampleEntry>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SubsampleEntry {
+    pub bytes_of_clear_data: u16,
+    pub bytes_of_protected_data: u32,
+}
+
+/// Sample auxiliary information sizes box 'saiz'
+/// See ISOBMFF (ISO 14496-12:2020) § 8.7.8
+#[derive(Debug, Default)]
+pub struct SampleAuxiliaryInformationSizesBox {
+    pub aux_info_type: Option<FourCC>,
+    pub default_sample_info_size: u8,
+    pub sample_count: u32,
+    /// Per-sample sizes, empty when `default_sample_info_size` is nonzero.
+    pub sample_info_sizes: TryVec<u8>,
+}
+
+impl SampleAuxiliaryInformationSizesBox {
+    /// Return the size of the auxiliary information of sample `index`.
+    pub fn sample_info_size(&self, index: usize) -> Option<u8> {
+        if self.default_sample_info_size != 0 {
+            return (index < self.sample_count.to_usize()).then_some(self.default_sample_info_size);
+        }
+        self.sample_info_sizes.get(index).copied()
+    }
+
+    /// The total size of the auxiliary information of all samples.
+    pub fn total_size(&self) -> u64 {
+        if self.default_sample_info_size != 0 {
+            return u64::from(self.sample_count) * u64::from(self.default_sample_info_size);
+        }
+        self.sample_info_sizes
+            .iter()
+            .map(|&size| u64::from(size))
+            .sum()
+    }
+
+    /// Decode the Common Encryption parameters of each sample from their
+    /// auxiliary information `data`, as read from the file. `iv_size` is
+    /// called with the index of each sample, as for
+    /// [`SampleEncryptionBox::entries_with_iv_sizes`]. A sample has a
+    /// subsample map if its information is larger than its IV.
+    /// See Common Encryption (ISO 23001-7:2016) § 7.1
+    pub fn encryption_entries<F: Fn(usize) -> u8>(
+        &self,
+        data: &[u8],
+        iv_size: F,
+    ) -> Result<TryVec<SampleEncryptionEntry>> {
+        if self.total_size() != data.len().to_u64() {
+            return Status::SaizBadInfo.into();
+        }
+
+        let mut entries = TryVec::with_capacity(self.sample_count.to_usize())?;
+        let mut rest = data;
+        for i in 0..self.sample_count.to_usize() {
+            let size = self.sample_info_size(i).ok_or(Status::SaizBadInfo)?;
+            let (info, next) = rest.split_at(size.into());
+            rest = next;
+            let src = &mut Cursor::new(info);
+            let iv = read_buf(src, iv_size(i).into()).map_err(|_| Status::SaizBadInfo)?;
+            let mut subsamples = TryVec::new();
+            if src.position() < info.len().to_u64() {
+                let subsample_count = be_u16(src).map_err(|_| Status::SaizBadInfo)?;
+                let bytes_left = info.len().to_u64() - src.position();
+                if u64::from(subsample_count) * 6 != bytes_left {
+                    return Status::SaizBadInfo.into();
+                }
+                subsamples.reserve(subsample_count.into())?;
+                for _ in 0..subsample_count {
+                    subsamples.push(SubsampleEntry {
+                        bytes_of_clear_data: be_u16(src)?,
+                        bytes_of_protected_data: be_u32(src)?,
+                    })?;
+                }
+            }
+            entries.push(SampleEncryptionEntry { iv, subsamples })?;
+        }
+        Ok(entries)
+    }
+}
+
+/// Sample auxiliary information offsets box 'saio'
+/// See ISOBMFF (ISO 14496-12:2020) § 8.7.9
+#[derive(Debug, Default)]
+pub struct SampleAuxiliaryInformationOffsetsBox {
+    pub aux_info_type: Option<FourCC>,
+    /// Offsets of the auxiliary information, which are file offsets in a
+    /// 'stbl' and relative to the base data offset in a 'traf'.
+    pub offsets: TryVec<u64>,
+}
+
+/// Sample group description box 'sgpd'
+/// See ISOBMFF (ISO 14496-12:2020) § 8.9.3
+#[derive(Debug, Default)]
+pub struct SampleGroupDescriptionBox {
+    pub grouping_type: FourCC,
+    /// The 1-based index of the entry of samples not mapped by a 'sbgp',
+    /// from version 2.
+    pub default_group_description_index: Option<u32>,
+    pub entries: TryVec<SampleGroupDescriptionEntry>,
+}
+
+impl SampleGroupDescriptionBox {
+    /// Return the 'seig' entry with the 1-based `index`.
+    pub fn seig_entry(&self, index: u32) -> Option<&TrackEncryptionBox> {
+        match self.entries.get(index.checked_sub(1)?.to_usize())? {
+            SampleGroupDescriptionEntry::CencSampleEncryptionInformation(seig) => Some(seig),
+            SampleGroupDescriptionEntry::Unknown(_) => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SampleGroupDescriptionEntry {
+    /// A 'seig' entry, which overrides the 'tenc' defaults for the samples
+    /// of the group, as used for key rotation.
+    /// See Common Encryption (ISO 23001-7:2016) § 6
+    CencSampleEncryptionInformation(TrackEncryptionBox),
+    /// The payload of an entry of another grouping type.
+    Unknown(TryVec<u8>),
+}
+
+/// Sample to group box 'sbgp'
+/// See ISOBMFF (ISO 14496-12:2020) § 8.9.2
+#[derive(Debug, Default)]
+pub struct SampleToGroupBox {
+    pub grouping_type: FourCC,
+    pub grouping_type_parameter: Option<u32>,
+    pub entries: TryVec<SampleToGroupEntry>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SampleToGroupEntry {
+    pub sample_count: u32,
+    /// The 1-based index of the 'sgpd' entry, or 0 for no group. In a
+    /// 'traf', indexes above 0x10000 refer to the 'sgpd' of the 'traf'.
+    pub group_description_index: u32,
+}
+
 #[derive(Debug, Default)]
 pub struct ProtectionSchemeInfoBox {
     pub original_format: FourCC,
@@ -1552,32 +2557,355 @@ pub struct MediaContext {
     pub userdata: Option<Result<UserdataBox>>,
     #[cfg(feature = "meta-xml")]
     pub metadata: Option<Result<MetadataBox>>,
+    /// Movie fragments found after the 'moov' box.
+    pub moofs: TryVec<MovieFragmentBox>,
+    /// The file ranges of the content of the 'mdat' boxes found after the
+    /// 'moov' box, as far as the file extends.
+    pub mdats: TryVec<(u64, u64)>,
+    /// Segment indexes found after the 'moov' box.
+    pub sidxs: TryVec<SegmentIndexBox>,
+    /// The movie fragment random access box, normally at the end of the
+    /// file. See also [`read_mfra`].
+    pub mfra: Option<MovieFragmentRandomAccessBox>,
+    /// Sample auxiliary information located by 'saiz' and 'saio' boxes
+    /// which hasn't been read yet.
+    aux_info_requests: TryVec<AuxInfoRequest>,
+}
+
+/// Where sample auxiliary information is stored once it's read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AuxInfoTarget {
+    /// The `aux_info` of the track at this index of `MediaContext::tracks`
+    Track(usize),
+    /// The `aux_info` of the 'traf' at the second index of the 'moof' at the
+    /// first index of `MediaContext::moofs`
+    Traf(usize, usize),
 }
 
-/// An ISOBMFF item as described by an iloc box. For the sake of avoiding copies,
-/// this can either be represented by the `Location` variant, which indicates
-/// where the data exists within a `DataBox` stored separately, or the `Data`
-/// variant which owns the data. Unfortunately, it's not simple to represent
-/// this as a [`std::borrow::Cow`], or other reference-based type, because
-/// multiple instances may references different parts of the same [`DataBox`]
-/// and we want to avoid the copy that splitting the storage would entail.
-enum IsobmffItem {
-    MdatLocation(Extent),
-    IdatLocation(Extent),
-    Data(TryVec<u8>),
+/// The sample auxiliary information of a track or track fragment, as it's
+/// read from the file.
+#[derive(Debug)]
+struct AuxInfoRequest {
+    target: AuxInfoTarget,
+    /// The file offset and length of each contiguous range of the
+    /// information, in sample order
+    ranges: TryVec<(u64, u64)>,
+    /// The information read so far, from the start of the first range
+    data: TryVec<u8>,
 }
 
-impl fmt::Debug for IsobmffItem {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match &self {
-            IsobmffItem::MdatLocation(extent) | IsobmffItem::IdatLocation(extent) => f
-                .debug_struct("IsobmffItem::Location")
-                .field("0", &format_args!("{extent:?}"))
-                .finish(),
-            IsobmffItem::Data(data) => f
-                .debug_struct("IsobmffItem::Data")
-                .field("0", &format_args!("{} bytes", data.len()))
-                .finish(),
+impl AuxInfoRequest {
+    /// The file offset of the next byte to read, and the number of bytes
+    /// left in its range, or None once all the information has been read.
+    fn next_range(&self) -> Option<(u64, u64)> {
+        let mut read = self.data.len().to_u64();
+        for &(offset, len) in self.ranges.iter() {
+            if read < len {
+                return Some((offset + read, len - read));
+            }
+            read -= len;
+        }
+        None
+    }
+}
+
+impl MediaContext {
+    /// Record the sample auxiliary information of the tracks from
+    /// `first_track` onwards which have a 'saiz' and 'saio' but no 'senc',
+    /// to be read from the file. The 'saio' has either one offset, or one
+    /// per chunk.
+    fn request_track_aux_info(&mut self, first_track: usize) -> Result<()> {
+        for (index, track) in self.tracks.iter().enumerate().skip(first_track) {
+            let (saiz, saio) = match (&track.saiz, &track.saio, &track.senc) {
+                (Some(saiz), Some(saio), None) => (saiz, saio),
+                _ => continue,
+            };
+            let mut ranges = TryVec::new();
+            if let [offset] = saio.offsets[..] {
+                ranges.push((offset, saiz.total_size()))?;
+            } else {
+                let chunk_count = track.stco.as_ref().map_or(0, |stco| stco.offsets.len());
+                let stsc = match &track.stsc {
+                    Some(stsc) if saio.offsets.len() == chunk_count => stsc,
+                    _ => {
+                        warn!("saio entry_count doesn't match the chunks of track {index}");
+                        continue;
+                    }
+                };
+                let mut sample = 0;
+                for (chunk, &offset) in saio.offsets.iter().enumerate() {
+                    let samples_per_chunk = stsc
+                        .samples
+                        .iter()
+                        .take_while(|entry| entry.first_chunk.to_usize() <= chunk + 1)
+                        .last()
+                        .map_or(0, |entry| entry.samples_per_chunk.to_usize());
+                    let mut len = 0;
+                    for i in sample..sample + samples_per_chunk {
+                        len += u64::from(saiz.sample_info_size(i).unwrap_or(0));
+                    }
+                    sample += samples_per_chunk;
+                    ranges.push((offset, len))?;
+                }
+            }
+            self.aux_info_requests.push(AuxInfoRequest {
+                target: AuxInfoTarget::Track(index),
+                ranges,
+                data: TryVec::new(),
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Like [`MediaContext::request_track_aux_info`], for the track fragments
+    /// of the last 'moof', whose 'saio' has either one offset or one per
+    /// 'trun'.
+    fn request_traf_aux_info(&mut self) -> Result<()> {
+        let moof_index = match self.moofs.len().checked_sub(1) {
+            Some(index) => index,
+            None => return Ok(()),
+        };
+        let moof = &self.moofs[moof_index];
+        for (traf_index, traf) in moof.trafs.iter().enumerate() {
+            let (saiz, saio) = match (&traf.saiz, &traf.saio, &traf.senc) {
+                (Some(saiz), Some(saio), None) => (saiz, saio),
+                _ => continue,
+            };
+            // The offsets are relative to the base data offset, which is
+            // only known without the sample sizes of the preceding 'traf' if
+            // it's given or is the 'moof'.
+            let base = match traf.tfhd.base_data_offset {
+                Some(offset) => offset,
+                None if traf_index == 0 || traf.tfhd.default_base_is_moof => moof.offset,
+                None => {
+                    warn!("Not reading the auxiliary information of traf {traf_index}");
+                    continue;
+                }
+            };
+            let mut ranges = TryVec::new();
+            if let [offset] = saio.offsets[..] {
+                let offset = base.checked_add(offset).ok_or(Status::SaioBadSize)?;
+                ranges.push((offset, saiz.total_size()))?;
+            } else if saio.offsets.len() == traf.truns.len() {
+                let mut sample = 0;
+                for (trun, &offset) in traf.truns.iter().zip(saio.offsets.iter()) {
+                    let offset = base.checked_add(offset).ok_or(Status::SaioBadSize)?;
+                    let sample_count = trun.sample_count.to_usize();
+                    let mut len = 0;
+                    for i in sample..sample + sample_count {
+                        len += u64::from(saiz.sample_info_size(i).unwrap_or(0));
+                    }
+                    sample += sample_count;
+                    ranges.push((offset, len))?;
+                }
+            } else {
+                warn!("saio entry_count doesn't match the truns of traf {traf_index}");
+                continue;
+            }
+            self.aux_info_requests.push(AuxInfoRequest {
+                target: AuxInfoTarget::Traf(moof_index, traf_index),
+                ranges,
+                data: TryVec::new(),
+            })?;
+        }
+        Ok(())
+    }
+
+    /// The file offset and length of the first range of sample auxiliary
+    /// information still to be read within the `len` bytes at `offset`.
+    fn next_aux_info_range(&self, offset: u64, len: u64) -> Option<(u64, u64)> {
+        let end = offset.saturating_add(len);
+        self.aux_info_requests
+            .iter()
+            .filter_map(AuxInfoRequest::next_range)
+            .filter(|&(start, _)| start >= offset && start < end)
+            .map(|(start, len)| (start, len.min(end - start)))
+            .min()
+    }
+
+    /// Take the sample auxiliary information within `data`, which was read
+    /// from the file at `offset`. Information is read in order, so this must
+    /// be called with the data of the file in order too.
+    fn add_aux_info(&mut self, offset: u64, data: &[u8]) -> Result<()> {
+        let end = offset.saturating_add(data.len().to_u64());
+        let mut i = 0;
+        while let Some(request) = self.aux_info_requests.get_mut(i) {
+            while let Some((start, len)) = request.next_range() {
+                if start < offset || start >= end {
+                    break;
+                }
+                let from: usize = (start - offset).try_into()?;
+                let to: usize = (start.saturating_add(len).min(end) - offset).try_into()?;
+                request.data.extend_from_slice(&data[from..to])?;
+            }
+            if request.next_range().is_some() {
+                i += 1;
+                continue;
+            }
+            // The order of the requests doesn't matter.
+            let last = self.aux_info_requests.len() - 1;
+            self.aux_info_requests.swap(i, last);
+            let request = match self.aux_info_requests.pop() {
+                Some(request) => request,
+                None => break,
+            };
+            let aux_info = match request.target {
+                AuxInfoTarget::Track(track) => self.tracks.get_mut(track).map(|t| &mut t.aux_info),
+                AuxInfoTarget::Traf(moof, traf) => self
+                    .moofs
+                    .get_mut(moof)
+                    .and_then(|m| m.trafs.get_mut(traf))
+                    .map(|t| &mut t.aux_info),
+            };
+            if let Some(aux_info) = aux_info {
+                *aux_info = Some(request.data);
+            }
+        }
+        Ok(())
+    }
+
+    /// Record the `len` bytes of 'mdat' content at `offset`, which extend
+    /// the last range if they follow on from it.
+    fn add_mdat(&mut self, offset: u64, len: u64) -> Result<()> {
+        let end = offset
+            .checked_add(len)
+            .ok_or(Error::from(Status::BoxBadSize))?;
+        match self.mdats.last_mut() {
+            Some((_, last_end)) if *last_end == offset => *last_end = end,
+            _ => self.mdats.push((offset, end))?,
+        }
+        Ok(())
+    }
+
+    /// Build the list of media subsegments of `track_id` from the 'sidx'
+    /// boxes, following references from one 'sidx' to another.
+    ///
+    /// Returns None if no 'sidx' describes the track.
+    pub fn segment_index(&self, track_id: u32) -> Option<Result<SegmentIndex>> {
+        let mut roots = self
+            .sidxs
+            .iter()
+            .filter(|sidx| sidx.reference_id == track_id)
+            .peekable();
+        let timescale = roots.peek()?.timescale;
+        let mut index = SegmentIndex {
+            timescale,
+            segments: TryVec::new(),
+        };
+        // File offsets of the 'sidx' boxes already visited as a child of
+        // another, so they aren't visited again as a root.
+        let mut visited = TryVec::new();
+        for root in roots {
+            if visited.contains(&root.offset) {
+                continue;
+            }
+            if let Err(e) = self.add_segments(root, &mut index, &mut visited) {
+                return Some(Err(e));
+            }
+        }
+        Some(Ok(index))
+    }
+
+    fn add_segments(
+        &self,
+        root: &SegmentIndexBox,
+        index: &mut SegmentIndex,
+        visited: &mut TryVec<u64>,
+    ) -> Result<()> {
+        // Walk the hierarchy depth first with an explicit stack of
+        // (sidx, next reference, file offset and start time of that
+        // reference), so deeply nested indexes can't exhaust the call stack.
+        let mut stack = TryVec::new();
+        let start = root
+            .anchor_point
+            .checked_add(root.first_offset)
+            .ok_or(Error::InvalidData(Status::SidxBadReference))?;
+        stack.push((root, 0, start, root.earliest_presentation_time))?;
+        while let Some(&mut (sidx, ref mut i, ref mut offset, ref mut time)) = stack.last_mut() {
+            if sidx.timescale != index.timescale {
+                return Status::SidxBadTimescale.into();
+            }
+            let reference = match sidx.references.get(*i) {
+                Some(reference) => *reference,
+                None => {
+                    stack.pop();
+                    continue;
+                }
+            };
+            let reference_offset = *offset;
+            let reference_time = *time;
+            *i += 1;
+            *offset = offset
+                .checked_add(reference.referenced_size.into())
+                .ok_or(Error::InvalidData(Status::SidxBadReference))?;
+            *time = time
+                .checked_add(reference.subsegment_duration.into())
+                .ok_or(Error::InvalidData(Status::SidxBadReference))?;
+
+            if reference.references_index {
+                // A referenced 'sidx' always follows the one referencing it,
+                // so the hierarchy can't contain a cycle. Each 'sidx' may
+                // still only be expanded once: an empty reference, or several
+                // references to the same child, would otherwise multiply
+                // the segments at every level of the hierarchy.
+                if reference.referenced_size == 0 || visited.contains(&reference_offset) {
+                    return Status::SidxBadReference.into();
+                }
+                let child = self
+                    .sidxs
+                    .iter()
+                    .find(|child| child.offset == reference_offset)
+                    .ok_or(Error::InvalidData(Status::SidxBadReference))?;
+                visited.push(child.offset)?;
+                let start = child
+                    .anchor_point
+                    .checked_add(child.first_offset)
+                    .ok_or(Error::InvalidData(Status::SidxBadReference))?;
+                stack.push((child, 0, start, child.earliest_presentation_time))?;
+            } else {
+                index.segments.push(Segment {
+                    offset: reference_offset,
+                    size: reference.referenced_size.into(),
+                    start_time: reference_time,
+                    duration: reference.subsegment_duration.into(),
+                    starts_with_sap: reference.starts_with_sap,
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An ISOBMFF item as described by an iloc box. For the sake of avoiding copies,
+/// this can either be represented by the `Location` variant, which indicates
+/// where the data exists within a `DataBox` stored separately, or the `Data`
+/// variant which owns the data. Unfortunately, it's not simple to represent
+/// this as a [`std::borrow::Cow`], or other reference-based type, because
+/// multiple instances may references different parts of the same [`DataBox`]
+/// and we want to avoid the copy that splitting the storage would entail.
+enum IsobmffItem {
+    MdatLocation(Extent),
+    IdatLocation(Extent),
+    Data(TryVec<u8>),
+    /// Data within the file which hasn't been read; see [`read_avif_lazy`].
+    FileExtents(TryVec<Extent>),
+}
+
+impl fmt::Debug for IsobmffItem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self {
+            IsobmffItem::MdatLocation(extent) | IsobmffItem::IdatLocation(extent) => f
+                .debug_struct("IsobmffItem::Location")
+                .field("0", &format_args!("{extent:?}"))
+                .finish(),
+            IsobmffItem::Data(data) => f
+                .debug_struct("IsobmffItem::Data")
+                .field("0", &format_args!("{} bytes", data.len()))
+                .finish(),
+            IsobmffItem::FileExtents(extents) => f
+                .debug_struct("IsobmffItem::FileExtents")
+                .field("0", &format_args!("{extents:?}"))
+                .finish(),
         }
     }
 }
@@ -1600,6 +2928,256 @@ impl AvifItem {
             image_data: IsobmffItem::Data(TryVec::new()),
         }
     }
+
+    fn file_extents(&self) -> Option<&[Extent]> {
+        match &self.image_data {
+            IsobmffItem::FileExtents(extents) => Some(extents.as_slice()),
+            _ => None,
+        }
+    }
+}
+
+/// A 'grid' derived image item, which is reconstructed from its tiles
+#[derive(Debug)]
+struct AvifGrid {
+    id: ItemId,
+    grid: ImageGrid,
+    /// The tiles in raster order, per the order of the 'dimg' references.
+    /// A tile may be referenced more than once.
+    tile_ids: TryVec<ItemId>,
+}
+
+/// A 'tmap' derived item whose first input is the primary item, and its
+/// second the gain map image, stored in `other_items`
+#[derive(Debug)]
+struct AvifGainMap {
+    tmap_id: ItemId,
+    metadata: GainMapMetadata,
+    gain_map_id: ItemId,
+}
+
+/// A read-only view of an item listed in the 'iinf' box of an [`AvifContext`]
+/// See ISOBMFF (ISO 14496-12:2020) § 8.11.6
+#[derive(Clone, Copy)]
+pub struct HeifItem<'a> {
+    context: &'a AvifContext,
+    info: &'a ItemInfoEntry,
+}
+
+impl<'a> HeifItem<'a> {
+    pub fn id(&self) -> u32 {
+        self.info.item_id.0
+    }
+
+    pub fn item_type(&self) -> FourCC {
+        self.info.item_type.into()
+    }
+
+    /// The `item_name`, which may be empty.
+    pub fn name(&self) -> &'a [u8] {
+        self.info.item_name.as_slice()
+    }
+
+    /// The MIME type of 'mime' items.
+    pub fn content_type(&self) -> Option<&'a [u8]> {
+        self.info.content_type.as_ref().map(|s| s.as_slice())
+    }
+
+    /// Whether the item is hidden; see HEIF (ISO 23008-12:2017) § 6.4.2
+    pub fn is_hidden(&self) -> bool {
+        self.info.hidden
+    }
+
+    /// Whether the item is protected by a scheme in the 'ipro' box, in which
+    /// case its data is encrypted and it's otherwise ignored.
+    pub fn is_protected(&self) -> bool {
+        self.info.protected
+    }
+
+    /// The references from this item to other items.
+    pub fn references(&self) -> impl Iterator<Item = ItemReference> + 'a {
+        let item_id = self.info.item_id;
+        self.context
+            .item_references
+            .iter()
+            .filter(move |iref| iref.from_item_id == item_id)
+            .map(ItemReference::from)
+    }
+
+    /// The references from other items to this item.
+    pub fn referenced_by(&self) -> impl Iterator<Item = ItemReference> + 'a {
+        let item_id = self.info.item_id;
+        self.context
+            .item_references
+            .iter()
+            .filter(move |iref| iref.to_item_id == item_id)
+            .map(ItemReference::from)
+    }
+
+    /// The properties associated with this item, in the order of its 'ipma'
+    /// entry.
+    pub fn properties(&self) -> impl Iterator<Item = ItemPropertyAssociation<'a>> + 'a {
+        self.context.item_properties.associations(self.info.item_id)
+    }
+
+    /// Returns None if the item has no data, such as when its location
+    /// couldn't be found, or if its data wasn't read because the context was
+    /// created by [`read_avif_lazy`]. The data of an item which isn't
+    /// otherwise processed is also only available here if it's contiguous;
+    /// use [`HeifItem::read`] to copy it from several extents.
+    pub fn data(&self) -> Option<&'a [u8]> {
+        let context = self.context;
+        if let Some(item) = context.item_with_id(self.info.item_id) {
+            return context.item_as_slice(item);
+        }
+        let loc = context.item_locations.get(&self.info.item_id)?;
+        match (loc.construction_method, loc.extents.as_slice()) {
+            (ConstructionMethod::File, [extent]) => context
+                .media_storage
+                .iter()
+                .find_map(|mdat| mdat.get(extent)),
+            (ConstructionMethod::Idat, [extent]) => context.item_data_box.as_ref()?.get(extent),
+            _ => None,
+        }
+    }
+
+    /// Like [`AvifContext::primary_item_extents`], for this item.
+    pub fn extents(&self) -> Option<Result<TryVec<Extent>>> {
+        let item = match self.locate()? {
+            Ok(item) => item,
+            Err(e) => return Some(Err(e)),
+        };
+        let mut copy = TryVec::new();
+        for extent in item.get().file_extents()? {
+            if let Err(e) = copy.push(extent.clone()) {
+                return Some(Err(e.into()));
+            }
+        }
+        Some(Ok(copy))
+    }
+
+    /// Like [`AvifContext::read_primary_item`], for this item.
+    pub fn read<R: Read + Seek>(&self, src: &mut R) -> Option<Result<TryVec<u8>>> {
+        Some(
+            self.locate()?
+                .and_then(|item| self.context.read_item(item.get(), src)),
+        )
+    }
+
+    /// The 'auxC' property of an auxiliary image item, which identifies what
+    /// it represents.
+    pub fn auxiliary_type(&self) -> Result<Option<&'a AuxiliaryTypeProperty>> {
+        match self
+            .context
+            .item_properties
+            .get(self.info.item_id, BoxType::AuxiliaryTypeProperty)?
+        {
+            Some(ItemProperty::AuxiliaryType(auxc)) => Ok(Some(auxc)),
+            Some(other_property) => panic!("property key mismatch: {:?}", other_property),
+            None => Ok(None),
+        }
+    }
+
+    /// The 'ispe' property of an image item.
+    pub fn spatial_extents(&self) -> Result<Option<&'a ImageSpatialExtentsProperty>> {
+        self.context.image_spatial_extents(self.info.item_id)
+    }
+
+    /// The bits per channel of an image item from its 'pixi' property, or
+    /// empty if it has none.
+    pub fn bits_per_channel(&self) -> Result<&'a [u8]> {
+        self.context.image_bits_per_channel(self.info.item_id)
+    }
+
+    /// The 'av1C' property of an AV1 image item.
+    pub fn av1_config(&self) -> Result<Option<&'a AV1ConfigBox>> {
+        self.context.image_av1_config(self.info.item_id)
+    }
+
+    /// The 'hvcC' property of an HEVC image item.
+    pub fn hevc_config(&self) -> Result<Option<&'a HEVCConfigBox>> {
+        self.context.image_hevc_config(self.info.item_id)
+    }
+
+    /// Like [`AvifContext::nclx_colour_information_ptr`], for this item.
+    pub fn nclx_colour_information(&self) -> Option<Result<&'a NclxColourInformation>> {
+        self.context
+            .image_nclx_colour_information(self.info.item_id)
+    }
+
+    /// Like [`AvifContext::icc_colour_information`], for this item.
+    pub fn icc_colour_information(&self) -> Option<Result<&'a [u8]>> {
+        self.context.image_icc_colour_information(self.info.item_id)
+    }
+
+    /// Find the data of the item, locating it from its 'iloc' entry if it
+    /// wasn't located while reading the file.
+    fn locate(&self) -> Option<Result<ItemRef<'a>>> {
+        let context = self.context;
+        if let Some(item) = context.item_with_id(self.info.item_id) {
+            return Some(Ok(ItemRef::Located(item)));
+        }
+        let loc = context.item_locations.get(&self.info.item_id)?;
+        locate_item(
+            self.info.item_id,
+            loc,
+            &context.media_storage,
+            context.item_data_box.as_ref(),
+            context.lazy_mdats.as_ref(),
+            context.strictness,
+        )
+        .map(|item| item.map(ItemRef::Owned))
+        .transpose()
+    }
+}
+
+/// An item either located while reading the file, or on demand by
+/// [`HeifItem`]
+enum ItemRef<'a> {
+    Located(&'a AvifItem),
+    Owned(AvifItem),
+}
+
+impl ItemRef<'_> {
+    fn get(&self) -> &AvifItem {
+        match self {
+            ItemRef::Located(item) => item,
+            ItemRef::Owned(item) => item,
+        }
+    }
+}
+
+impl fmt::Debug for HeifItem<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("HeifItem").field("info", self.info).finish()
+    }
+}
+
+/// A reference of type `reference_type` from one item to another
+/// See ISOBMFF (ISO 14496-12:2020) § 8.11.12
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ItemReference {
+    pub reference_type: FourCC,
+    pub from_item_id: u32,
+    pub to_item_id: u32,
+}
+
+impl From<&SingleItemTypeReferenceBox> for ItemReference {
+    fn from(iref: &SingleItemTypeReferenceBox) -> Self {
+        Self {
+            reference_type: iref.item_type.clone(),
+            from_item_id: iref.from_item_id.0,
+            to_item_id: iref.to_item_id.0,
+        }
+    }
+}
+
+/// A property associated with an item by an 'ipma' box
+/// See ISOBMFF (ISO 14496-12:2020) § 8.11.14
+#[derive(Debug)]
+pub struct ItemPropertyAssociation<'a> {
+    pub property: &'a ItemProperty,
+    pub essential: bool,
 }
 
 #[derive(Default, Debug)]
@@ -1618,11 +3196,43 @@ pub struct AvifContext {
     primary_item: Option<AvifItem>,
     /// Associated alpha channel for the primary item, if any
     alpha_item: Option<AvifItem>,
+    /// The primary item if it's a 'grid' rather than a coded image, in which
+    /// case `primary_item` is `None`
+    primary_grid: Option<AvifGrid>,
+    /// Items other than `primary_item` and `alpha_item` whose data is needed,
+    /// such as the tiles of `primary_grid`
+    other_items: TryHashMap<ItemId, AvifItem>,
+    /// The 'iloc' entries of the items which aren't otherwise processed, so
+    /// their data is only located when asked for through [`HeifItem`]
+    item_locations: TryHashMap<ItemId, ItemLocationBoxItem>,
+    /// The file ranges of the unread 'mdat' boxes if the context was created
+    /// by [`read_avif_lazy`], as used to locate `item_locations`
+    lazy_mdats: Option<TryVec<(u64, Option<u64>)>>,
     /// If true, divide RGB values by the alpha value.
     /// See `prem` in MIAF (ISO 23000-22:2019) § 7.3.5.2
     pub premultiplied_alpha: bool,
-    /// All properties associated with `primary_item` or `alpha_item`
+    /// The clean aperture of the primary item, if it has a valid 'clap'
+    primary_item_crop: Option<CropRectangle>,
+    /// The layers of the primary item from its 'a1lx' property, if any
+    primary_item_layers: TryVec<LayerRange>,
+    /// The metadata items describing the primary item, stored in `other_items`
+    exif_item_id: Option<ItemId>,
+    xmp_item_id: Option<ItemId>,
+    /// The coded thumbnails of the primary item, stored in `other_items`
+    thumbnail_ids: TryVec<ItemId>,
+    /// The coded auxiliary images of the primary item, stored in
+    /// `other_items` apart from `alpha_item`
+    auxiliary_image_ids: TryVec<ItemId>,
+    /// The gain map to render the primary item as HDR, if any
+    gain_map: Option<AvifGainMap>,
+    /// All items from the 'iinf' box, as viewed by [`AvifContext::items`]
+    item_infos: TryVec<ItemInfoEntry>,
+    /// All references from the 'iref' box
+    item_references: TryVec<SingleItemTypeReferenceBox>,
+    /// All properties associated with items by the 'ipma' boxes
     item_properties: ItemPropertiesBox,
+    /// The coding format of the image items
+    pub codec: ImageCodec,
     /// Should probably only ever be [`AVIF_BRAND`] or [`AVIS_BRAND`], but other values
     /// are legal as long as one of the two is the `compatible_brand` list.
     pub major_brand: FourCC,
@@ -1637,132 +3247,407 @@ impl AvifContext {
         self.primary_item.is_some()
     }
 
+    /// Returns None if there is no primary item, or if its data wasn't read
+    /// because the context was created by [`read_avif_lazy`].
     pub fn primary_item_coded_data(&self) -> Option<&[u8]> {
         self.primary_item
             .as_ref()
-            .map(|item| self.item_as_slice(item))
+            .and_then(|item| self.item_as_slice(item))
     }
 
-    pub fn primary_item_bits_per_channel(&self) -> Option<Result<&[u8]>> {
+    /// The extents within the file of the primary item's coded data, if the
+    /// context was created by [`read_avif_lazy`] and the data is stored in
+    /// 'mdat' boxes.
+    pub fn primary_item_extents(&self) -> Option<&[Extent]> {
+        self.primary_item.as_ref().and_then(AvifItem::file_extents)
+    }
+
+    /// Read the primary item's coded data from `src`, which must be the
+    /// source the context was read from.
+    pub fn read_primary_item<R: Read + Seek>(&self, src: &mut R) -> Option<Result<TryVec<u8>>> {
         self.primary_item
             .as_ref()
-            .map(|item| self.image_bits_per_channel(item.id))
+            .map(|item| self.read_item(item, src))
+    }
+
+    /// The byte ranges of each layer within the primary item's coded data,
+    /// so it can be decoded progressively as the data arrives. Empty unless
+    /// the primary item has an 'a1lx' property.
+    pub fn primary_item_layer_ranges(&self) -> &[LayerRange] {
+        &self.primary_item_layers
+    }
+
+    /// The Exif metadata of the primary image, starting at its TIFF header.
+    /// Returns None if there is none, or if its data wasn't read because the
+    /// context was created by [`read_avif_lazy`].
+    pub fn exif_data(&self) -> Option<&[u8]> {
+        self.metadata_item(self.exif_item_id)
+            .and_then(|item| self.item_as_slice(item))
+            .and_then(exif_tiff_header)
+    }
+
+    /// Like [`AvifContext::exif_data`], reading the data from `src` if
+    /// necessary, which must be the source the context was read from.
+    pub fn read_exif<R: Read + Seek>(&self, src: &mut R) -> Option<Result<TryVec<u8>>> {
+        self.metadata_item(self.exif_item_id).map(|item| {
+            let data = self.read_item(item, src)?;
+            let tiff_header = exif_tiff_header(&data)
+                .ok_or_else(|| Error::from(Status::ExifBadTiffHeaderOffset))?;
+            let mut exif = TryVec::with_capacity(tiff_header.len())?;
+            exif.extend_from_slice(tiff_header)?;
+            Ok(exif)
+        })
     }
 
-    pub fn alpha_item_is_present(&self) -> bool {
-        self.alpha_item.is_some()
+    /// The XMP metadata of the primary image. Returns None if there is none,
+    /// or if its data wasn't read because the context was created by
+    /// [`read_avif_lazy`].
+    pub fn xmp_data(&self) -> Option<&[u8]> {
+        self.metadata_item(self.xmp_item_id)
+            .and_then(|item| self.item_as_slice(item))
     }
 
-    pub fn alpha_item_coded_data(&self) -> Option<&[u8]> {
-        self.alpha_item
-            .as_ref()
-            .map(|item| self.item_as_slice(item))
+    /// Like [`AvifContext::xmp_data`], reading the data from `src` if
+    /// necessary, which must be the source the context was read from.
+    pub fn read_xmp<R: Read + Seek>(&self, src: &mut R) -> Option<Result<TryVec<u8>>> {
+        self.metadata_item(self.xmp_item_id)
+            .map(|item| self.read_item(item, src))
     }
 
-    pub fn alpha_item_bits_per_channel(&self) -> Option<Result<&[u8]>> {
-        self.alpha_item
-            .as_ref()
-            .map(|item| self.image_bits_per_channel(item.id))
+    fn metadata_item(&self, item_id: Option<ItemId>) -> Option<&AvifItem> {
+        self.other_items.get(&item_id?)
     }
 
-    fn image_bits_per_channel(&self, item_id: ItemId) -> Result<&[u8]> {
-        match self
-            .item_properties
-            .get(item_id, BoxType::PixelInformationBox)?
-        {
-            Some(ItemProperty::Channels(pixi)) => Ok(pixi.bits_per_channel.as_slice()),
+    /// The AV1 operating point to decode the primary image with, from its
+    /// 'a1op' property.
+    pub fn primary_item_operating_point(&self) -> Result<Option<u8>> {
+        match self.primary_item_property(BoxType::OperatingPointSelectorProperty)? {
+            Some(ItemProperty::OperatingPointSelector(op_index)) => Ok(Some(*op_index)),
             Some(other_property) => panic!("property key mismatch: {:?}", other_property),
-            None => Ok(&[]),
+            None => Ok(None),
         }
     }
 
-    pub fn spatial_extents_ptr(&self) -> Result<*const ImageSpatialExtentsProperty> {
-        if let Some(primary_item) = &self.primary_item {
-            match self
-                .item_properties
-                .get(primary_item.id, BoxType::ImageSpatialExtentsProperty)?
-            {
-                Some(ItemProperty::ImageSpatialExtents(ispe)) => Ok(ispe),
-                Some(other_property) => panic!("property key mismatch: {:?}", other_property),
-                None => {
-                    fail_with_status_if(
-                        self.strictness != ParseStrictness::Permissive,
-                        Status::IspeMissing,
-                    )?;
-                    Ok(std::ptr::null())
-                }
-            }
-        } else {
-            Ok(std::ptr::null())
+    /// The spatial layer of the primary image to display, from its 'lsel'
+    /// property. None if all layers may be displayed.
+    pub fn primary_item_layer_selection(&self) -> Result<Option<u16>> {
+        match self.primary_item_property(BoxType::LayerSelectorProperty)? {
+            Some(ItemProperty::LayerSelection(LSEL_LAYER_ID_NO_SELECTION)) | None => Ok(None),
+            Some(ItemProperty::LayerSelection(layer_id)) => Ok(Some(*layer_id)),
+            Some(other_property) => panic!("property key mismatch: {:?}", other_property),
         }
     }
 
-    /// Returns None if there is no primary item or it has no associated NCLX colour boxes.
-    pub fn nclx_colour_information_ptr(&self) -> Option<Result<*const NclxColourInformation>> {
-        if let Some(primary_item) = &self.primary_item {
-            match self.item_properties.get_multiple(primary_item.id, |prop| {
-                matches!(prop, ItemProperty::Colour(ColourInformation::Nclx(_)))
-            }) {
-                Ok(nclx_colr_boxes) => match *nclx_colr_boxes.as_slice() {
-                    [] => None,
-                    [ItemProperty::Colour(ColourInformation::Nclx(nclx)), ..] => {
-                        if nclx_colr_boxes.len() > 1 {
-                            warn!("Multiple nclx colr boxes, using first");
-                        }
-                        Some(Ok(nclx))
-                    }
-                    _ => unreachable!("Expect only ColourInformation::Nclx(_) matches"),
-                },
-                Err(e) => Some(Err(e)),
-            }
-        } else {
-            None
+    fn primary_item_property(&self, property_type: BoxType) -> Result<Option<&ItemProperty>> {
+        match self.primary_item_id() {
+            Some(primary_item_id) => self.item_properties.get(primary_item_id, property_type),
+            None => Ok(None),
         }
     }
 
-    /// Returns None if there is no primary item or it has no associated ICC colour boxes.
-    pub fn icc_colour_information(&self) -> Option<Result<&[u8]>> {
-        if let Some(primary_item) = &self.primary_item {
-            match self.item_properties.get_multiple(primary_item.id, |prop| {
-                matches!(prop, ItemProperty::Colour(ColourInformation::Icc(_, _)))
-            }) {
-                Ok(icc_colr_boxes) => match *icc_colr_boxes.as_slice() {
-                    [] => None,
-                    [ItemProperty::Colour(ColourInformation::Icc(icc, _)), ..] => {
-                        if icc_colr_boxes.len() > 1 {
-                            warn!("Multiple ICC profiles in colr boxes, using first");
-                        }
-                        Some(Ok(icc.bytes.as_slice()))
-                    }
-                    _ => unreachable!("Expect only ColourInformation::Icc(_) matches"),
-                },
-                Err(e) => Some(Err(e)),
-            }
-        } else {
-            None
+    pub fn primary_item_bits_per_channel(&self) -> Option<Result<&[u8]>> {
+        self.primary_item
+            .as_ref()
+            .map(|item| self.image_bits_per_channel(item.id))
+    }
+
+    /// The 'av1C' property of the primary item, including its sequence
+    /// header, if the context was created by [`read_avif`].
+    pub fn primary_item_av1_config(&self) -> Result<Option<&AV1ConfigBox>> {
+        match &self.primary_item {
+            Some(item) => self.image_av1_config(item.id),
+            None => Ok(None),
         }
     }
 
-    pub fn image_rotation(&self) -> Result<ImageRotation> {
-        if let Some(primary_item) = &self.primary_item {
-            match self
-                .item_properties
-                .get(primary_item.id, BoxType::ImageRotation)?
-            {
-                Some(ItemProperty::Rotation(irot)) => Ok(*irot),
-                Some(other_property) => panic!("property key mismatch: {:?}", other_property),
-                None => Ok(ImageRotation::D0),
-            }
-        } else {
-            Ok(ImageRotation::D0)
+    /// The 'hvcC' property of the primary item, if the context was created
+    /// by [`read_heif`].
+    pub fn primary_item_hevc_config(&self) -> Result<Option<&HEVCConfigBox>> {
+        match &self.primary_item {
+            Some(item) => self.image_hevc_config(item.id),
+            None => Ok(None),
         }
     }
 
-    pub fn image_mirror_ptr(&self) -> Result<*const ImageMirror> {
-        if let Some(primary_item) = &self.primary_item {
+    /// The layout of the primary item if it's a 'grid' of tiles, which are
+    /// available from [`AvifContext::grid_tiles`].
+    pub fn primary_grid(&self) -> Option<&ImageGrid> {
+        self.primary_grid.as_ref().map(|grid| &grid.grid)
+    }
+
+    /// The tiles of the primary 'grid' item in raster order, or none if there
+    /// is no grid. A tile may be listed more than once.
+    pub fn grid_tiles(&self) -> impl Iterator<Item = HeifItem<'_>> {
+        self.primary_grid
+            .iter()
+            .flat_map(|grid| grid.tile_ids.iter())
+            .filter_map(move |tile_id| self.item(tile_id.0))
+    }
+
+    /// The thumbnails of the primary item, from their 'thmb' references to it.
+    pub fn thumbnails(&self) -> impl Iterator<Item = HeifItem<'_>> {
+        self.thumbnail_ids
+            .iter()
+            .filter_map(move |item_id| self.item(item_id.0))
+    }
+
+    /// The auxiliary images of the primary item, from their 'auxl'
+    /// references to it. These include the alpha item, if any.
+    pub fn auxiliary_images(&self) -> impl Iterator<Item = HeifItem<'_>> {
+        self.auxiliary_image_ids
+            .iter()
+            .filter_map(move |item_id| self.item(item_id.0))
+    }
+
+    /// The metadata to apply the gain map to the primary item with, from its
+    /// 'tmap' derived item.
+    pub fn gain_map_metadata(&self) -> Option<&GainMapMetadata> {
+        self.gain_map.as_ref().map(|gain_map| &gain_map.metadata)
+    }
+
+    /// The image of the gain map, the second input of the 'tmap' item.
+    pub fn gain_map_image(&self) -> Option<HeifItem<'_>> {
+        self.item(self.gain_map.as_ref()?.gain_map_id.0)
+    }
+
+    /// The 'tmap' derived item, whose colour properties describe the
+    /// alternate (HDR) rendition of the primary item.
+    pub fn tone_map_item(&self) -> Option<HeifItem<'_>> {
+        self.item(self.gain_map.as_ref()?.tmap_id.0)
+    }
+
+    fn image_av1_config(&self, item_id: ItemId) -> Result<Option<&AV1ConfigBox>> {
+        match self
+            .item_properties
+            .get(item_id, BoxType::AV1CodecConfigurationBox)?
+        {
+            Some(ItemProperty::AV1Config(av1c)) => Ok(Some(av1c)),
+            Some(other_property) => panic!("property key mismatch: {:?}", other_property),
+            None => Ok(None),
+        }
+    }
+
+    fn image_hevc_config(&self, item_id: ItemId) -> Result<Option<&HEVCConfigBox>> {
+        match self
+            .item_properties
+            .get(item_id, BoxType::HEVCConfigurationBox)?
+        {
+            Some(ItemProperty::HEVCConfig(hvcc)) => Ok(Some(hvcc)),
+            Some(other_property) => panic!("property key mismatch: {:?}", other_property),
+            None => Ok(None),
+        }
+    }
+
+    /// The id of the primary item, whether it's a coded image or a 'grid'.
+    fn primary_item_id(&self) -> Option<ItemId> {
+        match (&self.primary_item, &self.primary_grid) {
+            (Some(item), _) => Some(item.id),
+            (None, Some(grid)) => Some(grid.id),
+            (None, None) => None,
+        }
+    }
+
+    /// All items of the file in the order of the 'iinf' box, including those
+    /// which aren't otherwise processed, such as the items of an image
+    /// collection.
+    pub fn items(&self) -> impl Iterator<Item = HeifItem<'_>> {
+        self.item_infos.iter().map(move |info| HeifItem {
+            context: self,
+            info,
+        })
+    }
+
+    /// The item with the `item_ID` `id`, if it's listed in the 'iinf' box.
+    pub fn item(&self, id: u32) -> Option<HeifItem<'_>> {
+        self.items().find(|item| item.id() == id)
+    }
+
+    /// All references between items from the 'iref' box.
+    pub fn item_references(&self) -> impl Iterator<Item = ItemReference> + '_ {
+        self.item_references.iter().map(ItemReference::from)
+    }
+
+    fn item_with_id(&self, item_id: ItemId) -> Option<&AvifItem> {
+        self.primary_item
+            .iter()
+            .chain(&self.alpha_item)
+            .find(|item| item.id == item_id)
+            .or_else(|| self.other_items.get(&item_id))
+    }
+
+    pub fn alpha_item_is_present(&self) -> bool {
+        self.alpha_item.is_some()
+    }
+
+    /// Returns None if there is no alpha item, or if its data wasn't read
+    /// because the context was created by [`read_avif_lazy`].
+    pub fn alpha_item_coded_data(&self) -> Option<&[u8]> {
+        self.alpha_item
+            .as_ref()
+            .and_then(|item| self.item_as_slice(item))
+    }
+
+    /// Like [`AvifContext::primary_item_extents`], for the alpha item.
+    pub fn alpha_item_extents(&self) -> Option<&[Extent]> {
+        self.alpha_item.as_ref().and_then(AvifItem::file_extents)
+    }
+
+    /// Like [`AvifContext::read_primary_item`], for the alpha item.
+    pub fn read_alpha_item<R: Read + Seek>(&self, src: &mut R) -> Option<Result<TryVec<u8>>> {
+        self.alpha_item
+            .as_ref()
+            .map(|item| self.read_item(item, src))
+    }
+
+    pub fn alpha_item_bits_per_channel(&self) -> Option<Result<&[u8]>> {
+        self.alpha_item
+            .as_ref()
+            .map(|item| self.image_bits_per_channel(item.id))
+    }
+
+    /// Like [`AvifContext::primary_item_av1_config`], for the alpha item.
+    pub fn alpha_item_av1_config(&self) -> Result<Option<&AV1ConfigBox>> {
+        match &self.alpha_item {
+            Some(item) => self.image_av1_config(item.id),
+            None => Ok(None),
+        }
+    }
+
+    /// Like [`AvifContext::primary_item_hevc_config`], for the alpha item.
+    pub fn alpha_item_hevc_config(&self) -> Result<Option<&HEVCConfigBox>> {
+        match &self.alpha_item {
+            Some(item) => self.image_hevc_config(item.id),
+            None => Ok(None),
+        }
+    }
+
+    fn image_bits_per_channel(&self, item_id: ItemId) -> Result<&[u8]> {
+        match self
+            .item_properties
+            .get(item_id, BoxType::PixelInformationBox)?
+        {
+            Some(ItemProperty::Channels(pixi)) => Ok(pixi.bits_per_channel.as_slice()),
+            Some(other_property) => panic!("property key mismatch: {:?}", other_property),
+            None => Ok(&[]),
+        }
+    }
+
+    pub fn spatial_extents_ptr(&self) -> Result<*const ImageSpatialExtentsProperty> {
+        if let Some(primary_item_id) = self.primary_item_id() {
+            self.image_spatial_extents_ptr(primary_item_id)
+        } else {
+            Ok(std::ptr::null())
+        }
+    }
+
+    fn image_spatial_extents_ptr(
+        &self,
+        item_id: ItemId,
+    ) -> Result<*const ImageSpatialExtentsProperty> {
+        Ok(self
+            .image_spatial_extents(item_id)?
+            .map_or(std::ptr::null(), |ispe| ispe))
+    }
+
+    fn image_spatial_extents(
+        &self,
+        item_id: ItemId,
+    ) -> Result<Option<&ImageSpatialExtentsProperty>> {
+        match self
+            .item_properties
+            .get(item_id, BoxType::ImageSpatialExtentsProperty)?
+        {
+            Some(ItemProperty::ImageSpatialExtents(ispe)) => Ok(Some(ispe)),
+            Some(other_property) => panic!("property key mismatch: {:?}", other_property),
+            None => {
+                fail_with_status_if(
+                    self.strictness != ParseStrictness::Permissive,
+                    Status::IspeMissing,
+                )?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Returns None if there is no primary item or it has no associated NCLX colour boxes.
+    pub fn nclx_colour_information_ptr(&self) -> Option<Result<*const NclxColourInformation>> {
+        self.primary_item_id()
+            .and_then(|primary_item_id| self.image_nclx_colour_information_ptr(primary_item_id))
+    }
+
+    fn image_nclx_colour_information_ptr(
+        &self,
+        item_id: ItemId,
+    ) -> Option<Result<*const NclxColourInformation>> {
+        self.image_nclx_colour_information(item_id)
+            .map(|nclx| nclx.map(|nclx| nclx as *const _))
+    }
+
+    fn image_nclx_colour_information(
+        &self,
+        item_id: ItemId,
+    ) -> Option<Result<&NclxColourInformation>> {
+        match self.item_properties.get_multiple(item_id, |prop| {
+            matches!(prop, ItemProperty::Colour(ColourInformation::Nclx(_)))
+        }) {
+            Ok(nclx_colr_boxes) => match *nclx_colr_boxes.as_slice() {
+                [] => None,
+                [ItemProperty::Colour(ColourInformation::Nclx(nclx)), ..] => {
+                    if nclx_colr_boxes.len() > 1 {
+                        warn!("Multiple nclx colr boxes, using first");
+                    }
+                    Some(Ok(nclx))
+                }
+                _ => unreachable!("Expect only ColourInformation::Nclx(_) matches"),
+            },
+            Err(e) => Some(Err(e)),
+        }
+    }
+
+    /// Returns None if there is no primary item or it has no associated ICC colour boxes.
+    pub fn icc_colour_information(&self) -> Option<Result<&[u8]>> {
+        self.primary_item_id()
+            .and_then(|primary_item_id| self.image_icc_colour_information(primary_item_id))
+    }
+
+    fn image_icc_colour_information(&self, item_id: ItemId) -> Option<Result<&[u8]>> {
+        match self.item_properties.get_multiple(item_id, |prop| {
+            matches!(prop, ItemProperty::Colour(ColourInformation::Icc(_, _)))
+        }) {
+            Ok(icc_colr_boxes) => match *icc_colr_boxes.as_slice() {
+                [] => None,
+                [ItemProperty::Colour(ColourInformation::Icc(icc, _)), ..] => {
+                    if icc_colr_boxes.len() > 1 {
+                        warn!("Multiple ICC profiles in colr boxes, using first");
+                    }
+                    Some(Ok(icc.bytes.as_slice()))
+                }
+                _ => unreachable!("Expect only ColourInformation::Icc(_) matches"),
+            },
+            Err(e) => Some(Err(e)),
+        }
+    }
+
+    pub fn image_rotation(&self) -> Result<ImageRotation> {
+        if let Some(primary_item_id) = self.primary_item_id() {
+            match self
+                .item_properties
+                .get(primary_item_id, BoxType::ImageRotation)?
+            {
+                Some(ItemProperty::Rotation(irot)) => Ok(*irot),
+                Some(other_property) => panic!("property key mismatch: {:?}", other_property),
+                None => Ok(ImageRotation::D0),
+            }
+        } else {
+            Ok(ImageRotation::D0)
+        }
+    }
+
+    pub fn image_mirror_ptr(&self) -> Result<*const ImageMirror> {
+        if let Some(primary_item_id) = self.primary_item_id() {
             match self
                 .item_properties
-                .get(primary_item.id, BoxType::ImageMirror)?
+                .get(primary_item_id, BoxType::ImageMirror)?
             {
                 Some(ItemProperty::Mirroring(imir)) => Ok(imir),
                 Some(other_property) => panic!("property key mismatch: {:?}", other_property),
@@ -1773,11 +3658,47 @@ impl AvifContext {
         }
     }
 
+    /// Returns null unless the primary item has a 'clap' property.
+    pub fn clean_aperture_ptr(&self) -> *const CropRectangle {
+        match &self.primary_item_crop {
+            Some(crop) => crop,
+            None => std::ptr::null(),
+        }
+    }
+
+    /// The displayed size and orientation of the primary image, or None if
+    /// there is no primary item or it has no 'ispe' property.
+    pub fn display_geometry(&self) -> Result<Option<DisplayGeometry>> {
+        let primary_item_id = match self.primary_item_id() {
+            Some(item_id) => item_id,
+            None => return Ok(None),
+        };
+        let ispe = match self.image_spatial_extents(primary_item_id)? {
+            Some(ispe) => ispe,
+            None => return Ok(None),
+        };
+        let mirror = match self
+            .item_properties
+            .get(primary_item_id, BoxType::ImageMirror)?
+        {
+            Some(ItemProperty::Mirroring(imir)) => Some(imir),
+            Some(other_property) => panic!("property key mismatch: {:?}", other_property),
+            None => None,
+        };
+        Ok(Some(DisplayGeometry::new(
+            ispe.image_width,
+            ispe.image_height,
+            self.primary_item_crop,
+            self.image_rotation()?,
+            mirror,
+        )))
+    }
+
     pub fn pixel_aspect_ratio_ptr(&self) -> Result<*const PixelAspectRatio> {
-        if let Some(primary_item) = &self.primary_item {
+        if let Some(primary_item_id) = self.primary_item_id() {
             match self
                 .item_properties
-                .get(primary_item.id, BoxType::PixelAspectRatioBox)?
+                .get(primary_item_id, BoxType::PixelAspectRatioBox)?
             {
                 Some(ItemProperty::PixelAspectRatio(pasp)) => Ok(pasp),
                 Some(other_property) => panic!("property key mismatch: {:?}", other_property),
@@ -1789,27 +3710,105 @@ impl AvifContext {
     }
 
     /// A helper for the various `AvifItem`s to expose a reference to the
-    /// underlying data while avoiding copies.
-    fn item_as_slice<'a>(&'a self, item: &'a AvifItem) -> &'a [u8] {
-        match &item.image_data {
-            IsobmffItem::MdatLocation(extent) => {
-                for mdat in &self.media_storage {
-                    if let Some(slice) = mdat.get(extent) {
-                        return slice;
+    /// underlying data while avoiding copies. Returns None for items which
+    /// haven't been read from the file.
+    fn item_as_slice<'a>(&'a self, item: &'a AvifItem) -> Option<&'a [u8]> {
+        item_data(
+            &item.image_data,
+            &self.media_storage,
+            self.item_data_box.as_ref(),
+        )
+    }
+
+    /// Copy the data of `item`, reading it from `src` if necessary.
+    fn read_item<R: Read + Seek>(&self, item: &AvifItem, src: &mut R) -> Result<TryVec<u8>> {
+        let extents = match &item.image_data {
+            IsobmffItem::FileExtents(extents) => extents,
+            _ => {
+                let mut data = TryVec::new();
+                data.extend_from_slice(self.item_as_slice(item).unwrap_or_default())?;
+                return Ok(data);
+            }
+        };
+
+        let mut data = TryVec::new();
+        for extent in extents.iter() {
+            let (offset, len) = match *extent {
+                Extent::WithLength { offset, len } => (offset, Some(len.to_u64())),
+                Extent::ToEnd { offset } => (offset, None),
+            };
+            src.seek(SeekFrom::Start(offset))?;
+            match len {
+                Some(len) => {
+                    let got = fallible_collections::try_read_up_to(src, len, &mut data)?;
+                    if got.to_u64() != len {
+                        return Err(Error::UnexpectedEOF);
                     }
                 }
-                unreachable!(
-                    "IsobmffItem::MdatLocation requires the location exists in AvifContext::media_storage"
-                );
+                None => {
+                    // Read in chunks until EOF, as for an unknown sized `mdat`.
+                    const BUF_SIZE: u64 = 64 * 1024;
+                    while fallible_collections::try_read_up_to(src, BUF_SIZE, &mut data)? > 0 {}
+                }
             }
-            IsobmffItem::IdatLocation(extent) => {
-                self.item_data_box
-                    .as_ref()
-                    .and_then(|idat| idat.get(extent))
-                    .unwrap_or_else(|| unreachable!("IsobmffItem::IdatLocation equires the location exists in AvifContext::item_data_box"))
+        }
+        Ok(data)
+    }
+}
+
+/// Skip the exif_tiff_header_offset field of an Exif item's data, and the
+/// bytes it counts, to return the data from the TIFF header onwards.
+/// See HEIF (ISO 23008-12:2017) § A.2.1
+fn exif_tiff_header(data: &[u8]) -> Option<&[u8]> {
+    let (offset, payload) = (data.get(..4)?, data.get(4..)?);
+    let offset = u32::from_be_bytes(offset.try_into().ok()?);
+    payload.get(offset.to_usize()..)
+}
+
+/// The length of an item's data, if it's known without reading it.
+fn item_len(
+    image_data: &IsobmffItem,
+    media_storage: &[DataBox],
+    item_data_box: Option<&DataBox>,
+) -> Option<u64> {
+    match image_data {
+        IsobmffItem::FileExtents(extents) => {
+            extents.iter().try_fold(0u64, |len, extent| match *extent {
+                Extent::WithLength {
+                    len: extent_len, ..
+                } => len.checked_add(extent_len.to_u64()),
+                Extent::ToEnd { .. } => None,
+            })
+        }
+        _ => item_data(image_data, media_storage, item_data_box).map(|data| data.len().to_u64()),
+    }
+}
+
+/// The data of an item located by `image_data`, or None for items which
+/// haven't been read from the file.
+fn item_data<'a>(
+    image_data: &'a IsobmffItem,
+    media_storage: &'a [DataBox],
+    item_data_box: Option<&'a DataBox>,
+) -> Option<&'a [u8]> {
+    match image_data {
+        IsobmffItem::MdatLocation(extent) => {
+            for mdat in media_storage {
+                if let Some(slice) = mdat.get(extent) {
+                    return Some(slice);
+                }
             }
-            IsobmffItem::Data(data) => data.as_slice(),
+            unreachable!(
+                "IsobmffItem::MdatLocation requires the location exists in AvifContext::media_storage"
+            );
+        }
+        IsobmffItem::IdatLocation(extent) => {
+            Some(item_data_box
+                .and_then(|idat| idat.get(extent))
+                .unwrap_or_else(|| unreachable!("IsobmffItem::IdatLocation equires the location exists in AvifContext::item_data_box")))
         }
+        IsobmffItem::Data(data) => Some(data.as_slice()),
+        IsobmffItem::FileExtents(_) => None,
     }
 }
 
@@ -1949,6 +3948,15 @@ impl ItemId {
 struct ItemInfoEntry {
     item_id: ItemId,
     item_type: u32,
+    /// The `item_name`, without its nul terminator
+    item_name: TryString,
+    /// The MIME type of 'mime' items, without its nul terminator
+    content_type: Option<TryString>,
+    /// Whether the item is hidden; see HEIF (ISO 23008-12:2017) § 6.4.2
+    hidden: bool,
+    /// Whether the item has an `item_protection_index`, in which case it
+    /// isn't processed since protected items aren't supported
+    protected: bool,
 }
 
 /// See ISOBMFF (ISO 14496-12:2020) § 8.11.12
@@ -2043,8 +4051,8 @@ enum ConstructionMethod {
 /// `usize::MAX` can be used in a successful indexing operation in rust.
 /// `extent_index` is omitted since it's only used for ConstructionMethod::Item which
 /// is currently not implemented.
-#[derive(Clone, Debug)]
-enum Extent {
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Extent {
     WithLength { offset: u64, len: usize },
     ToEnd { offset: u64 },
 }
@@ -2101,6 +4109,8 @@ pub enum CodecType {
     ALAC,
     H263,
     HEVC, // 23008-2
+    /// Dolby Vision without a base layer other decoders can present
+    DolbyVision,
     #[cfg(feature = "3gpp")]
     AMRNB,
     #[cfg(feature = "3gpp")]
@@ -2156,6 +4166,14 @@ pub struct Track {
     pub stss: Option<SyncSampleBox>,
     pub ctts: Option<CompositionOffsetBox>,
     pub tref: Option<TrackReferenceBox>,
+    pub senc: Option<SampleEncryptionBox>,
+    pub saiz: Option<SampleAuxiliaryInformationSizesBox>,
+    pub saio: Option<SampleAuxiliaryInformationOffsetsBox>,
+    /// The auxiliary information located by `saiz` and `saio`, in sample
+    /// order, if the track has no `senc` and it was read from the file.
+    pub aux_info: Option<TryVec<u8>>,
+    pub sgpds: TryVec<SampleGroupDescriptionBox>,
+    pub sbgps: TryVec<SampleToGroupBox>,
 }
 
 impl Track {
@@ -2367,9 +4385,80 @@ impl AvifImageType {
     }
 }
 
+/// The coding format of the image items of an [`AvifContext`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ImageCodec {
+    /// 'av01' items with 'av1C' properties, as read by [`read_avif`]
+    #[default]
+    Av1,
+    /// 'hvc1' items with 'hvcC' properties, as read by [`read_heif`]
+    Hevc,
+}
+
+impl ImageCodec {
+    fn item_type(self) -> &'static [u8; 4] {
+        match self {
+            Self::Av1 => b"av01",
+            Self::Hevc => b"hvc1",
+        }
+    }
+
+    fn config_property(self) -> BoxType {
+        match self {
+            Self::Av1 => BoxType::AV1CodecConfigurationBox,
+            Self::Hevc => BoxType::HEVCConfigurationBox,
+        }
+    }
+
+    fn config_missing_status(self) -> Status {
+        match self {
+            Self::Av1 => Status::Av1cMissing,
+            Self::Hevc => Status::HvccMissing,
+        }
+    }
+}
+
 /// Read the contents of an AVIF file
 pub fn read_avif<T: Read>(f: &mut T, strictness: ParseStrictness) -> Result<AvifContext> {
-    debug!("read_avif(strictness: {strictness:?})");
+    read_avif_common(f, strictness, false, ImageCodec::Av1)
+}
+
+/// Read the contents of an AVIF file without keeping the contents of its
+/// 'mdat' boxes in memory.
+///
+/// Items stored in 'mdat' boxes are recorded by their extents instead, which
+/// are available from [`AvifContext::primary_item_extents`], and their data
+/// can be read from the source with [`AvifContext::read_primary_item`].
+pub fn read_avif_lazy<T: Read>(f: &mut T, strictness: ParseStrictness) -> Result<AvifContext> {
+    read_avif_common(f, strictness, true, ImageCodec::Av1)
+}
+
+/// Read the contents of a HEIF file whose images are HEVC coded, such as a
+/// HEIC file.
+///
+/// The resulting context is the same as for AVIF, with 'hvc1' items and
+/// their 'hvcC' properties in place of 'av01' items and 'av1C' properties.
+pub fn read_heif<T: Read>(f: &mut T, strictness: ParseStrictness) -> Result<AvifContext> {
+    read_avif_common(f, strictness, false, ImageCodec::Hevc)
+}
+
+/// Like [`read_heif`], without keeping the contents of 'mdat' boxes in
+/// memory as for [`read_avif_lazy`].
+pub fn read_heif_lazy<T: Read>(f: &mut T, strictness: ParseStrictness) -> Result<AvifContext> {
+    read_avif_common(f, strictness, true, ImageCodec::Hevc)
+}
+
+/// The content_type of 'mime' items containing XMP metadata
+/// See HEIF (ISO 23008-12:2017) § A.2.2
+const XMP_CONTENT_TYPE: &[u8] = b"application/rdf+xml";
+
+fn read_avif_common<T: Read>(
+    f: &mut T,
+    strictness: ParseStrictness,
+    lazy: bool,
+    codec: ImageCodec,
+) -> Result<AvifContext> {
+    debug!("read_avif(strictness: {strictness:?}, lazy: {lazy}, codec: {codec:?})");
 
     let mut f = OffsetReader::new(f);
     let mut iter = BoxIter::new(&mut f);
@@ -2381,13 +4470,19 @@ pub fn read_avif<T: Read>(f: &mut T, strictness: ParseStrictness) -> Result<Avif
         if b.head.name == BoxType::FileTypeBox {
             let ftyp = read_ftyp(&mut b)?;
 
-            let has_avif_brand = ftyp.contains(&AVIF_BRAND);
-            let has_avis_brand = ftyp.contains(&AVIS_BRAND);
+            // The brands of the codec for images and image sequences
+            let (has_image_brand, has_sequence_brand) = match codec {
+                ImageCodec::Av1 => (ftyp.contains(&AVIF_BRAND), ftyp.contains(&AVIS_BRAND)),
+                ImageCodec::Hevc => (
+                    ftyp.contains(&HEIC_BRAND) || ftyp.contains(&HEIX_BRAND),
+                    ftyp.contains(&HEVC_BRAND) || ftyp.contains(&HEVX_BRAND),
+                ),
+            };
             let has_mif1_brand = ftyp.contains(&MIF1_BRAND);
             let has_msf1_brand = ftyp.contains(&MSF1_BRAND);
 
-            let primary_image_expected = has_mif1_brand || has_avif_brand;
-            let image_sequence_expected = has_msf1_brand || has_avis_brand;
+            let primary_image_expected = has_mif1_brand || has_image_brand;
+            let image_sequence_expected = has_msf1_brand || has_sequence_brand;
 
             expected_image_type = if primary_image_expected && image_sequence_expected {
                 AvifImageType::Both
@@ -2407,10 +4502,13 @@ pub fn read_avif<T: Read>(f: &mut T, strictness: ParseStrictness) -> Result<Avif
                 )?;
             }
 
-            if !has_avif_brand && !has_avis_brand {
+            if !has_image_brand && !has_sequence_brand {
                 fail_with_status_if(
                     strictness != ParseStrictness::Permissive,
-                    Status::MissingAvifOrAvisBrand,
+                    match codec {
+                        ImageCodec::Av1 => Status::MissingAvifOrAvisBrand,
+                        ImageCodec::Hevc => Status::MissingHeicOrHevcBrand,
+                    },
                 )?;
             }
 
@@ -2425,6 +4523,9 @@ pub fn read_avif<T: Read>(f: &mut T, strictness: ParseStrictness) -> Result<Avif
     let mut meta = None;
     let mut image_sequence = None;
     let mut media_storage = TryVec::new();
+    // The file ranges of the mdat boxes not stored in `media_storage`, with
+    // `None` for an mdat extending to the end of the file.
+    let mut lazy_mdats: TryVec<(u64, Option<u64>)> = TryVec::new();
 
     loop {
         let mut b = match iter.next_box() {
@@ -2457,6 +4558,26 @@ pub fn read_avif<T: Read>(f: &mut T, strictness: ParseStrictness) -> Result<Avif
                 }
                 image_sequence = Some(read_moov(&mut b, None, strictness)?);
             }
+            BoxType::MediaDataBox if lazy => {
+                let file_offset = b.offset();
+                let end = if b.head.size == 0 {
+                    // Unknown sized `mdat` extends to EOF.
+                    None
+                } else {
+                    file_offset.checked_add(b.bytes_left())
+                };
+                lazy_mdats.push((file_offset, end))?;
+                // The few bytes of the items needed to process the file are
+                // kept while skipping the rest.
+                if let Some(meta) = &meta {
+                    let locations = lazy_item_locations(meta)?;
+                    read_lazy_item_extents(&mut b, &locations, &mut media_storage)?;
+                }
+                if b.head.size == 0 {
+                    break;
+                }
+                skip_box_remain(&mut b)?;
+            }
             BoxType::MediaDataBox => {
                 let file_offset = b.offset();
                 let data = if b.head.size == 0 {
@@ -2503,6 +4624,9 @@ pub fn read_avif<T: Read>(f: &mut T, strictness: ParseStrictness) -> Result<Avif
         item_data_box,
     } = meta.ok_or_else(|| Error::from(Status::MetaBadQuantity))?;
 
+    // Protected items are listed by AvifContext::items, but not processed
+    let unprotected_infos = || item_infos.iter().filter(|item_info| !item_info.protected);
+
     let (alpha_item_id, premultiplied_alpha) = if let Some(primary_item_id) = primary_item_id {
         let mut alpha_item_ids = item_references
             .iter()
@@ -2533,103 +4657,158 @@ pub fn read_avif<T: Read>(f: &mut T, strictness: ParseStrictness) -> Result<Avif
         (None, false)
     };
 
-    debug!("primary_item_id: {primary_item_id:?}");
-    debug!("alpha_item_id: {alpha_item_id:?}");
-    let mut primary_item = None;
-    let mut alpha_item = None;
+    let item_type = |item_id: ItemId| {
+        unprotected_infos()
+            .find(|item_info| item_id == item_info.item_id)
+            .map(|item_info| item_info.item_type.to_be_bytes())
+    };
 
-    // store data or record location of relevant items
-    for (item_id, loc) in iloc_items {
-        let item = if Some(item_id) == primary_item_id {
-            &mut primary_item
-        } else if Some(item_id) == alpha_item_id {
-            &mut alpha_item
-        } else {
-            continue;
-        };
+    // The tiles of the primary item in the order of its 'dimg' references,
+    // if it's a 'grid' derived image.
+    let grid_tile_ids = match primary_item_id {
+        Some(grid_id) if item_type(grid_id).as_ref() == Some(b"grid") => {
+            let mut tile_ids = TryVec::new();
+            for iref in item_references
+                .iter()
+                .filter(|iref| iref.from_item_id == grid_id && iref.item_type == b"dimg")
+            {
+                tile_ids.push(iref.to_item_id)?;
+            }
+            Some(tile_ids)
+        }
+        _ => None,
+    };
 
-        assert!(item.is_none());
+    // The Exif and XMP metadata items with 'cdsc' references to the primary item
+    let mut exif_item_id = None;
+    let mut xmp_item_id = None;
+    if let Some(primary_item_id) = primary_item_id {
+        for iref in item_references
+            .iter()
+            .filter(|iref| iref.to_item_id == primary_item_id && iref.item_type == b"cdsc")
+        {
+            let item_info =
+                unprotected_infos().find(|item_info| item_info.item_id == iref.from_item_id);
+            let metadata_item_id = match item_info {
+                Some(item_info) if item_info.item_type.to_be_bytes() == *b"Exif" => {
+                    &mut exif_item_id
+                }
+                Some(ItemInfoEntry {
+                    content_type: Some(content_type),
+                    ..
+                }) if content_type.as_slice() == XMP_CONTENT_TYPE => &mut xmp_item_id,
+                _ => continue,
+            };
+            if metadata_item_id.is_some() {
+                warn!("Ignoring additional metadata item {:?}", iref.from_item_id);
+            } else {
+                *metadata_item_id = Some(iref.from_item_id);
+            }
+        }
+    }
 
-        // If our item is spread over multiple extents, we'll need to copy it
-        // into a contiguous buffer. Otherwise, we can just store the extent
-        // and return a pointer into the mdat/idat later to avoid the copy.
-        if loc.extents.len() > 1 {
-            *item = Some(AvifItem::with_inline_data(item_id))
+    // The thumbnails of the primary item, in the order of their 'thmb' references
+    let mut thumbnail_ids = TryVec::new();
+    if let Some(primary_item_id) = primary_item_id {
+        for iref in item_references
+            .iter()
+            .filter(|iref| iref.to_item_id == primary_item_id && iref.item_type == b"thmb")
+        {
+            thumbnail_ids.push(iref.from_item_id)?;
         }
+    }
 
-        trace!(
-            "{:?} construction_method: {:?}",
-            item_id,
-            loc.construction_method
-        );
+    // The auxiliary images of the primary item, in the order of their 'auxl'
+    // references, which include the alpha item
+    let mut auxiliary_image_ids = TryVec::new();
+    if let Some(primary_item_id) = primary_item_id {
+        for iref in item_references
+            .iter()
+            .filter(|iref| iref.to_item_id == primary_item_id && iref.item_type == b"auxl")
+        {
+            auxiliary_image_ids.push(iref.from_item_id)?;
+        }
+    }
 
-        // Generalize the process of connecting items to their data; returns
-        // true if the extent is successfully added to the AvifItem
-        let mut find_and_add_to_item = |extent: &Extent, dat: &DataBox| -> Result<bool> {
-            if let Some(extent_slice) = dat.get(extent) {
-                match item {
-                    None => {
-                        trace!("Using IsobmffItem::Location");
-                        *item = Some(AvifItem {
-                            id: item_id,
-                            image_data: dat.location(extent),
-                        });
-                    }
-                    Some(AvifItem {
-                        image_data: IsobmffItem::Data(bytes),
-                        ..
-                    }) => {
-                        trace!("Using IsobmffItem::Data");
-                        // We could potentially optimize memory usage by trying to avoid reading
-                        // or storing dat boxes which aren't used by our API, but for now it seems
-                        // like unnecessary complexity
-                        bytes.extend_from_slice(extent_slice)?;
-                    }
-                    _ => unreachable!(),
+    // The first 'tmap' derived item whose base image input is the primary
+    // item, with the ids of it and its gain map image input
+    let mut gain_map_item_ids = None;
+    if let Some(primary_item_id) = primary_item_id {
+        for item_info in
+            unprotected_infos().filter(|item_info| item_info.item_type.to_be_bytes() == *b"tmap")
+        {
+            let mut inputs = item_references
+                .iter()
+                .filter(|iref| iref.from_item_id == item_info.item_id && iref.item_type == b"dimg")
+                .map(|iref| iref.to_item_id);
+            if let (Some(base_id), Some(gain_map_id), None) =
+                (inputs.next(), inputs.next(), inputs.next())
+            {
+                if base_id == primary_item_id {
+                    gain_map_item_ids = Some((item_info.item_id, gain_map_id));
+                    break;
                 }
-                return Ok(true);
             }
-            Ok(false)
-        };
+        }
+    }
 
-        match loc.construction_method {
-            ConstructionMethod::File => {
-                for extent in loc.extents {
-                    let mut found = false;
-                    // try to find an mdat which contains the extent
-                    for mdat in media_storage.iter() {
-                        if find_and_add_to_item(&extent, mdat)? {
-                            found = true;
-                            break;
-                        }
-                    }
+    debug!("primary_item_id: {primary_item_id:?}");
+    debug!("alpha_item_id: {alpha_item_id:?}");
+    debug!("grid_tile_ids: {grid_tile_ids:?}");
+    debug!("exif_item_id: {exif_item_id:?}");
+    debug!("xmp_item_id: {xmp_item_id:?}");
+    debug!("thumbnail_ids: {thumbnail_ids:?}");
+    debug!("auxiliary_image_ids: {auxiliary_image_ids:?}");
+    debug!("gain_map_item_ids: {gain_map_item_ids:?}");
+    let mut primary_item = None;
+    let mut alpha_item = None;
+    let mut other_items = TryHashMap::with_capacity(
+        grid_tile_ids.as_ref().map_or(0, |ids| ids.len())
+            + usize::from(exif_item_id.is_some())
+            + usize::from(xmp_item_id.is_some())
+            + thumbnail_ids.len()
+            + auxiliary_image_ids.len()
+            + if gain_map_item_ids.is_some() { 2 } else { 0 },
+    )?;
+    let mut item_locations = TryHashMap::with_capacity(iloc_items.len())?;
+    let lazy_mdats = if lazy { Some(lazy_mdats) } else { None };
 
-                    if !found {
-                        return Status::IlocNotFound.into();
-                    }
-                }
-            }
-            ConstructionMethod::Idat => {
-                if let Some(idat) = &item_data_box {
-                    for extent in loc.extents {
-                        let found = find_and_add_to_item(&extent, idat)?;
-                        if !found {
-                            return Status::IlocNotFound.into();
-                        }
-                    }
-                } else {
-                    return Status::IdatMissing.into();
-                }
-            }
-            ConstructionMethod::Item => {
-                fail_with_status_if(
-                    strictness != ParseStrictness::Permissive,
-                    Status::ConstructionMethod,
-                )?;
-            }
+    // Store data or record location of relevant items. The locations of the
+    // others are kept to find their data on demand through AvifContext::items.
+    for (item_id, loc) in iloc_items {
+        let is_processed = Some(item_id) == primary_item_id
+            || Some(item_id) == alpha_item_id
+            || grid_tile_ids.iter().flatten().any(|&id| id == item_id)
+            || Some(item_id) == exif_item_id
+            || Some(item_id) == xmp_item_id
+            || thumbnail_ids.contains(&item_id)
+            || auxiliary_image_ids.contains(&item_id)
+            || gain_map_item_ids
+                .is_some_and(|(tmap_id, gain_map_id)| item_id == tmap_id || item_id == gain_map_id);
+        if !is_processed {
+            item_locations.insert(item_id, loc)?;
+            continue;
         }
 
-        assert!(item.is_some());
+        let item = match locate_item(
+            item_id,
+            &loc,
+            &media_storage,
+            item_data_box.as_ref(),
+            lazy_mdats.as_ref(),
+            strictness,
+        )? {
+            Some(item) => item,
+            None => continue,
+        };
+
+        if Some(item_id) == primary_item_id {
+            primary_item = Some(item);
+        } else if Some(item_id) == alpha_item_id {
+            alpha_item = Some(item);
+        } else {
+            other_items.insert(item_id, item)?;
+        }
     }
 
     if (primary_item_id.is_some() && primary_item.is_none())
@@ -2666,49 +4845,47 @@ pub fn read_avif<T: Read>(f: &mut T, strictness: ParseStrictness) -> Result<Avif
         })
     };
 
+    // The property checks for coded image items, which apply to grid tiles too
+    let check_coded_item = |item_id: Option<ItemId>| -> Result<()> {
+        if missing_property_for(item_id, codec.config_property()) {
+            fail_with_status_if(
+                strictness != ParseStrictness::Permissive,
+                codec.config_missing_status(),
+            )?;
+        }
+
+        if missing_property_for(item_id, BoxType::PixelInformationBox) {
+            // The requirement to include pixi is in the process of being changed
+            // to allowing its omission to imply a default value. In anticipation
+            // of that, only give an error in strict mode
+            // See https://github.com/MPEGGroup/MIAF/issues/9
+            fail_with_status_if(
+                if cfg!(feature = "missing-pixi-permitted") {
+                    strictness == ParseStrictness::Strict
+                } else {
+                    strictness != ParseStrictness::Permissive
+                },
+                Status::PixiMissing,
+            )?;
+        }
+
+        if missing_property_for(item_id, BoxType::ImageSpatialExtentsProperty) {
+            fail_with_status_if(
+                strictness != ParseStrictness::Permissive,
+                Status::IspeMissing,
+            )?;
+        }
+        Ok(())
+    };
+
     // Generalize the property checks so we can apply them to primary and alpha items
     let mut check_image_item = |item: &mut Option<AvifItem>| -> Result<()> {
         let item_id = item.as_ref().map(|item| item.id);
-        let item_type = item_id.and_then(|item_id| {
-            item_infos
-                .iter()
-                .find(|item_info| item_id == item_info.item_id)
-                .map(|item_info| item_info.item_type)
-        });
-
-        match item_type.map(u32::to_be_bytes).as_ref() {
-            Some(b"av01") => {
-                if missing_property_for(item_id, BoxType::AV1CodecConfigurationBox) {
-                    fail_with_status_if(
-                        strictness != ParseStrictness::Permissive,
-                        Status::Av1cMissing,
-                    )?;
-                }
-
-                if missing_property_for(item_id, BoxType::PixelInformationBox) {
-                    // The requirement to include pixi is in the process of being changed
-                    // to allowing its omission to imply a default value. In anticipation
-                    // of that, only give an error in strict mode
-                    // See https://github.com/MPEGGroup/MIAF/issues/9
-                    fail_with_status_if(
-                        if cfg!(feature = "missing-pixi-permitted") {
-                            strictness == ParseStrictness::Strict
-                        } else {
-                            strictness != ParseStrictness::Permissive
-                        },
-                        Status::PixiMissing,
-                    )?;
-                }
 
-                if missing_property_for(item_id, BoxType::ImageSpatialExtentsProperty) {
-                    fail_with_status_if(
-                        strictness != ParseStrictness::Permissive,
-                        Status::IspeMissing,
-                    )?;
-                }
-            }
+        match item_id.and_then(item_type).as_ref() {
+            Some(coded_type) if coded_type == codec.item_type() => check_coded_item(item_id)?,
             Some(b"grid") => {
-                // TODO: https://github.com/mozilla/mp4parse-rust/issues/198
+                // Only a primary 'grid' item is supported
                 unsupported_features.insert(Feature::Grid);
                 *item = None;
             }
@@ -2730,23 +4907,452 @@ pub fn read_avif<T: Read>(f: &mut T, strictness: ParseStrictness) -> Result<Avif
         Ok(())
     };
 
+    let grid_item = match grid_tile_ids {
+        Some(_) => primary_item.take(),
+        None => None,
+    };
+
     check_image_item(&mut primary_item)?;
     check_image_item(&mut alpha_item)?;
 
+    // A 'grid' primary item is only processed if it and all its tiles can be
+    let mut primary_grid = None;
+    if let (Some(grid_item), Some(tile_ids)) = (grid_item, grid_tile_ids) {
+        let lazy_data;
+        let data = match &grid_item.image_data {
+            IsobmffItem::FileExtents(extents) => {
+                lazy_data = lazy_item_data(extents, &media_storage)?.ok_or(Error::Unsupported(
+                    "lazily read 'grid' item data before 'meta'",
+                ))?;
+                lazy_data.as_slice()
+            }
+            image_data => item_data(image_data, &media_storage, item_data_box.as_ref())
+                .ok_or(Error::Unsupported("'grid' item data"))?,
+        };
+        let grid = read_image_grid(&mut Cursor::new(data))?;
+        let mut usable = !item_properties.forbidden_items.contains(&grid_item.id);
+
+        if tile_ids.len() != usize::from(grid.rows) * usize::from(grid.columns) {
+            fail_with_status_if(
+                strictness != ParseStrictness::Permissive,
+                Status::GridBadTileCount,
+            )?;
+            usable = false;
+        }
+
+        for &tile_id in tile_ids.iter() {
+            if other_items.get(&tile_id).is_none() {
+                fail_with_status_if(
+                    strictness != ParseStrictness::Permissive,
+                    Status::GridTileMissing,
+                )?;
+                usable = false;
+            }
+            if item_type(tile_id).as_ref() != Some(codec.item_type()) {
+                fail_with_status_if(
+                    strictness != ParseStrictness::Permissive,
+                    Status::ImageItemType,
+                )?;
+                usable = false;
+                continue;
+            }
+            check_coded_item(Some(tile_id))?;
+            if item_properties.forbidden_items.contains(&tile_id) {
+                usable = false;
+            }
+        }
+
+        if usable {
+            primary_grid = Some(AvifGrid {
+                id: grid_item.id,
+                grid,
+                tile_ids,
+            });
+        } else {
+            error!(
+                "Not processing grid item id {:?} since it or its tiles can't be processed",
+                grid_item.id
+            );
+            unsupported_features.insert(Feature::Grid);
+        }
+        // Keep the data available from AvifContext::items
+        other_items.insert(grid_item.id, grid_item)?;
+    }
+
+    // Thumbnails, auxiliary images and gain maps are optional, so any which
+    // can't be processed are ignored
+    let is_usable_item = |item_id: ItemId| -> bool {
+        let usable = (other_items.get(&item_id).is_some()
+            || alpha_item.as_ref().is_some_and(|item| item.id == item_id))
+            && item_type(item_id).as_ref() == Some(codec.item_type())
+            && !item_properties.forbidden_items.contains(&item_id)
+            && !missing_property_for(Some(item_id), codec.config_property())
+            && !missing_property_for(Some(item_id), BoxType::ImageSpatialExtentsProperty);
+        if !usable {
+            warn!("Ignoring item {item_id:?} since it can't be processed");
+        }
+        usable
+    };
+    let usable_items = |item_ids: TryVec<ItemId>| -> Result<TryVec<ItemId>> {
+        let mut usable_item_ids = TryVec::with_capacity(item_ids.len())?;
+        for item_id in item_ids {
+            if is_usable_item(item_id) {
+                usable_item_ids.push(item_id)?;
+            }
+        }
+        Ok(usable_item_ids)
+    };
+    let thumbnail_ids = usable_items(thumbnail_ids)?;
+    let auxiliary_image_ids = usable_items(auxiliary_image_ids)?;
+
+    let mut gain_map = None;
+    if let Some((tmap_id, gain_map_id)) = gain_map_item_ids {
+        let tmap_image_data = other_items.get(&tmap_id).map(|item| &item.image_data);
+        let lazy_data = match tmap_image_data {
+            Some(IsobmffItem::FileExtents(extents)) => lazy_item_data(extents, &media_storage)?,
+            _ => None,
+        };
+        let tmap_data = match tmap_image_data {
+            Some(IsobmffItem::FileExtents(_)) => lazy_data.as_deref(),
+            Some(image_data) => item_data(image_data, &media_storage, item_data_box.as_ref()),
+            None => None,
+        };
+        match tmap_data {
+            Some(_) if item_properties.forbidden_items.contains(&tmap_id) => {
+                warn!("Ignoring tmap item {tmap_id:?} with unsupported essential properties");
+            }
+            Some(data) => match read_tone_map_image(&mut Cursor::new(data)) {
+                Ok(Some(metadata)) if is_usable_item(gain_map_id) => {
+                    gain_map = Some(AvifGainMap {
+                        tmap_id,
+                        metadata,
+                        gain_map_id,
+                    });
+                }
+                Ok(Some(_)) => {}
+                Ok(None) => warn!("Ignoring tmap item {tmap_id:?} of an unsupported version"),
+                // As for thumbnails, a gain map which can't be read is
+                // dropped unless parsing strictly.
+                Err(e) => {
+                    error!("Invalid tmap item {tmap_id:?}: {e:?}");
+                    fail_with_status_if(
+                        strictness == ParseStrictness::Strict,
+                        Status::TmapBadMetadata,
+                    )?;
+                }
+            },
+            None => warn!("Ignoring tmap item {tmap_id:?} whose data wasn't read"),
+        }
+    }
+
+    // Validate the clean aperture of the primary image against its size
+    let mut primary_item_crop = None;
+    let final_primary_item_id = match (&primary_item, &primary_grid) {
+        (Some(item), _) => Some(item.id),
+        (None, Some(grid)) => Some(grid.id),
+        (None, None) => None,
+    };
+    if let Some(item_id) = final_primary_item_id {
+        if let (
+            Some(ItemProperty::CleanAperture(clap)),
+            Some(ItemProperty::ImageSpatialExtents(ispe)),
+        ) = (
+            item_properties.get(item_id, BoxType::CleanApertureBox)?,
+            item_properties.get(item_id, BoxType::ImageSpatialExtentsProperty)?,
+        ) {
+            primary_item_crop = clap.crop_rectangle(ispe.image_width, ispe.image_height);
+            if primary_item_crop.is_none() {
+                fail_with_status_if(
+                    strictness != ParseStrictness::Permissive,
+                    Status::ClapBadCrop,
+                )?;
+            }
+        }
+    }
+
+    // Metadata items are only retained if they have a location
+    let exif_item_id = exif_item_id.filter(|id| other_items.get(id).is_some());
+    let xmp_item_id = xmp_item_id.filter(|id| other_items.get(id).is_some());
+    if let Some(exif_item) = exif_item_id.and_then(|id| other_items.get(&id)) {
+        if let Some(data) = item_data(
+            &exif_item.image_data,
+            &media_storage,
+            item_data_box.as_ref(),
+        ) {
+            if exif_tiff_header(data).is_none() {
+                fail_with_status_if(
+                    strictness != ParseStrictness::Permissive,
+                    Status::ExifBadTiffHeaderOffset,
+                )?;
+            }
+        }
+    }
+
+    // Locate the layers of a progressive primary image within its data
+    let mut primary_item_layers = TryVec::new();
+    if let Some(item) = &primary_item {
+        if let Some(ItemProperty::LayeredImageIndexing(a1lx)) =
+            item_properties.get(item.id, BoxType::AV1LayeredImageIndexingProperty)?
+        {
+            match item_len(&item.image_data, &media_storage, item_data_box.as_ref()) {
+                Some(len) => match a1lx.layer_ranges(len)? {
+                    Some(ranges) => primary_item_layers = ranges,
+                    None => fail_with_status_if(
+                        strictness != ParseStrictness::Permissive,
+                        Status::A1lxBadLayerSize,
+                    )?,
+                },
+                None => warn!("Not locating the layers of an item of unknown length"),
+            }
+        }
+    }
+
     Ok(AvifContext {
         strictness,
         media_storage,
         item_data_box,
         primary_item,
         alpha_item,
+        primary_grid,
+        other_items,
         premultiplied_alpha,
+        primary_item_crop,
+        primary_item_layers,
+        exif_item_id,
+        xmp_item_id,
+        thumbnail_ids,
+        auxiliary_image_ids,
+        gain_map,
+        item_locations,
+        lazy_mdats,
+        item_infos,
+        item_references,
         item_properties,
+        codec,
         major_brand,
         sequence: image_sequence,
         unsupported_features,
     })
 }
 
+/// Locate the data of the item `item_id` described by `loc`, copying it into
+/// a contiguous buffer if it's spread over multiple extents. The extents are
+/// only recorded if `lazy_mdats` are given; see [`read_avif_lazy`].
+fn locate_item(
+    item_id: ItemId,
+    loc: &ItemLocationBoxItem,
+    media_storage: &TryVec<DataBox>,
+    item_data_box: Option<&DataBox>,
+    lazy_mdats: Option<&TryVec<(u64, Option<u64>)>>,
+    strictness: ParseStrictness,
+) -> Result<Option<AvifItem>> {
+    // If our item is spread over multiple extents, we'll need to copy it
+    // into a contiguous buffer. Otherwise, we can just store the extent
+    // and return a pointer into the mdat/idat later to avoid the copy.
+    let mut item = if loc.extents.len() > 1 {
+        Some(AvifItem::with_inline_data(item_id))
+    } else {
+        None
+    };
+
+    trace!(
+        "{:?} construction_method: {:?}",
+        item_id,
+        loc.construction_method
+    );
+
+    // Generalize the process of connecting items to their data; returns
+    // true if the extent is successfully added to the AvifItem
+    let mut find_and_add_to_item = |extent: &Extent, dat: &DataBox| -> Result<bool> {
+        if let Some(extent_slice) = dat.get(extent) {
+            match &mut item {
+                None => {
+                    trace!("Using IsobmffItem::Location");
+                    item = Some(AvifItem {
+                        id: item_id,
+                        image_data: dat.location(extent),
+                    });
+                }
+                Some(AvifItem {
+                    image_data: IsobmffItem::Data(bytes),
+                    ..
+                }) => {
+                    trace!("Using IsobmffItem::Data");
+                    // We could potentially optimize memory usage by trying to avoid reading
+                    // or storing dat boxes which aren't used by our API, but for now it seems
+                    // like unnecessary complexity
+                    bytes.extend_from_slice(extent_slice)?;
+                }
+                _ => unreachable!(),
+            }
+            return Ok(true);
+        }
+        Ok(false)
+    };
+
+    match (loc.construction_method, lazy_mdats) {
+        (ConstructionMethod::File, Some(lazy_mdats)) => {
+            let mut extents = TryVec::with_capacity(loc.extents.len())?;
+            for extent in loc.extents.iter() {
+                extents.push(resolve_lazy_extent(extent.clone(), lazy_mdats)?)?;
+            }
+            item = Some(AvifItem {
+                id: item_id,
+                image_data: IsobmffItem::FileExtents(extents),
+            });
+        }
+        (ConstructionMethod::File, None) => {
+            for extent in loc.extents.iter() {
+                let mut found = false;
+                // try to find an mdat which contains the extent
+                for mdat in media_storage.iter() {
+                    if find_and_add_to_item(extent, mdat)? {
+                        found = true;
+                        break;
+                    }
+                }
+
+                if !found {
+                    return Status::IlocNotFound.into();
+                }
+            }
+        }
+        (ConstructionMethod::Idat, _) => {
+            if let Some(idat) = item_data_box {
+                for extent in loc.extents.iter() {
+                    let found = find_and_add_to_item(extent, idat)?;
+                    if !found {
+                        return Status::IlocNotFound.into();
+                    }
+                }
+            } else {
+                return Status::IdatMissing.into();
+            }
+        }
+        (ConstructionMethod::Item, _) => {
+            fail_with_status_if(
+                strictness != ParseStrictness::Permissive,
+                Status::ConstructionMethod,
+            )?;
+            return Ok(None);
+        }
+    }
+
+    Ok(item)
+}
+
+/// The largest payload of a 'grid' item, with 32-bit output dimensions
+/// See HEIF (ISO 23008-12:2017) § 6.6.2.3.2
+const MAX_IMAGE_GRID_SIZE: u64 = 12;
+
+/// A bound on the size of a 'tmap' item payload, well beyond the 142 bytes
+/// of a version 0 one with three channels, allowing for extensions by later
+/// writer versions.
+/// See ISO 21496-1:2025 § C.2.2
+const MAX_TONE_MAP_IMAGE_SIZE: u64 = 1024;
+
+/// The 'iloc' entries of the items stored in 'mdat' which are read even when
+/// the mdat isn't, with the largest size of their data: a 'grid' primary
+/// item, which is needed to process the file at all, and 'tmap' items.
+fn lazy_item_locations(meta: &AvifMeta) -> Result<TryVec<(&ItemLocationBoxItem, u64)>> {
+    let mut locations = TryVec::new();
+    for info in meta.item_infos.iter().filter(|info| !info.protected) {
+        let max_size = match &info.item_type.to_be_bytes() {
+            b"grid" if meta.primary_item_id == Some(info.item_id) => MAX_IMAGE_GRID_SIZE,
+            b"tmap" => MAX_TONE_MAP_IMAGE_SIZE,
+            _ => continue,
+        };
+        if let Some(loc) = meta
+            .iloc_items
+            .get(&info.item_id)
+            .filter(|loc| loc.construction_method == ConstructionMethod::File)
+        {
+            locations.push((loc, max_size))?;
+        }
+    }
+    Ok(locations)
+}
+
+/// Read the extents of the items at `locations` within the lazily read mdat
+/// `src`, so their data is available without keeping the whole mdat. Items
+/// larger than the size given with their location are left unread. Each
+/// extent is stored in `media_storage` as if it were an mdat of its own.
+fn read_lazy_item_extents<T: Read + Offset>(
+    src: &mut BMFFBox<T>,
+    locations: &[(&ItemLocationBoxItem, u64)],
+    media_storage: &mut TryVec<DataBox>,
+) -> Result<()> {
+    let mut extents = TryVec::new();
+    for &(loc, max_size) in locations {
+        let mut size = 0u64;
+        for extent in loc.extents.iter() {
+            size = match *extent {
+                Extent::WithLength { len, .. } => size.saturating_add(len.to_u64()),
+                Extent::ToEnd { .. } => u64::MAX,
+            };
+        }
+        if size > max_size {
+            continue;
+        }
+        for extent in loc.extents.iter() {
+            if let Extent::WithLength { offset, len } = *extent {
+                extents.push((offset, len.to_u64()))?;
+            }
+        }
+    }
+    extents.sort_unstable();
+    for (offset, len) in extents {
+        let position = src.offset();
+        if offset < position || offset - position >= src.bytes_left() {
+            continue;
+        }
+        skip(src, offset - position)?;
+        let data = read_buf(src, len)?;
+        media_storage.push(DataBox::from_mdat(offset, data))?;
+    }
+    Ok(())
+}
+
+/// The data of an item whose `extents` were read by `read_lazy_item_extents`,
+/// or None if they weren't.
+fn lazy_item_data(extents: &[Extent], media_storage: &[DataBox]) -> Result<Option<TryVec<u8>>> {
+    let mut data = TryVec::new();
+    for extent in extents {
+        match media_storage.iter().find_map(|mdat| mdat.get(extent)) {
+            Some(extent_data) => data.extend_from_slice(extent_data)?,
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(data))
+}
+
+/// Check `extent` lies within one of the unread mdat boxes, bounding an
+/// extent which runs to the end of its mdat by the mdat's size.
+fn resolve_lazy_extent(extent: Extent, mdats: &TryVec<(u64, Option<u64>)>) -> Result<Extent> {
+    let (offset, end) = match extent {
+        Extent::WithLength { offset, len } => (offset, Some(offset.checked_add(len.to_u64()))),
+        Extent::ToEnd { offset } => (offset, None),
+    };
+    for &(mdat_start, mdat_end) in mdats.iter() {
+        if offset < mdat_start || mdat_end.is_some_and(|mdat_end| offset > mdat_end) {
+            continue;
+        }
+        match (end, mdat_end) {
+            (Some(Some(end)), Some(mdat_end)) if end <= mdat_end => return Ok(extent),
+            (Some(Some(_)), None) => return Ok(extent),
+            (None, Some(mdat_end)) => {
+                return Ok(Extent::WithLength {
+                    offset,
+                    len: (mdat_end - offset).try_into()?,
+                })
+            }
+            (None, None) => return Ok(extent),
+            _ => {}
+        }
+    }
+    Status::IlocNotFound.into()
+}
+
 /// Parse a metadata box in the context of an AVIF
 /// Currently requires the primary item to be an av01 item type and generates
 /// an error otherwise.
@@ -2894,9 +5500,7 @@ fn read_iinf<T: Read>(
             return Status::IinfBadChild.into();
         }
 
-        if let Some(infe) = read_infe(&mut b, strictness, unsupported_features)? {
-            item_infos.push(infe)?;
-        }
+        item_infos.push(read_infe(&mut b, strictness, unsupported_features)?)?;
 
         check_parser_state!(b.content);
     }
@@ -2923,13 +5527,14 @@ fn read_infe<T: Read>(
     src: &mut BMFFBox<T>,
     strictness: ParseStrictness,
     unsupported_features: &mut UnsupportedFeatures,
-) -> Result<Option<ItemInfoEntry>> {
+) -> Result<ItemInfoEntry> {
     let (version, flags) = read_fullbox_extra(src)?;
 
-    // According to the standard, it seems the flags field shall be 0, but at
-    // least one sample AVIF image has a nonzero value.
+    // According to the standard, it seems the flags field shall be 0, except
+    // for the hidden flag HEIF defines, but at least one sample AVIF image
+    // has other nonzero values.
     // See https://github.com/AOMediaCodec/av1-avif/issues/146
-    if flags != 0 {
+    if flags & !1 != 0 {
         fail_with_status_if(
             strictness == ParseStrictness::Strict,
             Status::InfeFlagsNonzero,
@@ -2948,15 +5553,33 @@ fn read_infe<T: Read>(
     let item_type = be_u32(src)?;
     debug!("infe {:?} item_type: {}", item_id, U32BE(item_type));
 
-    // There are some additional fields here, but they're not of interest to us
-    skip_box_remain(src)?;
+    // The remaining fields are nul-terminated strings, of which we're only
+    // interested in the item_name and the content_type of 'mime' items
+    let strings = src.read_into_try_vec()?;
+    let mut strings = strings.split(|&b| b == b'\0');
+    let mut item_name = TryString::new();
+    item_name.extend_from_slice(strings.next().unwrap_or_default())?;
+    let content_type = match strings.next() {
+        Some(content_type) if item_type.to_be_bytes() == *b"mime" => {
+            let mut s = TryString::new();
+            s.extend_from_slice(content_type)?;
+            Some(s)
+        }
+        _ => None,
+    };
 
-    if item_protection_index != 0 {
+    let protected = item_protection_index != 0;
+    if protected {
         unsupported_features.insert(Feature::Ipro);
-        Ok(None)
-    } else {
-        Ok(Some(ItemInfoEntry { item_id, item_type }))
     }
+    Ok(ItemInfoEntry {
+        item_id,
+        item_type,
+        item_name,
+        content_type,
+        hidden: flags & 1 != 0,
+        protected,
+    })
 }
 
 /// Parse an Item Reference Box
@@ -3024,14 +5647,6 @@ fn read_iprp<T: Read>(
     let mut association_entries = TryVec::<ItemPropertyAssociationEntry>::new();
     let mut forbidden_items = TryVec::new();
 
-    // A LayerSelectorProperty with this layer_id enables, but does not require,
-    // progressive rendering: a client may render progressively or just show the
-    // final image (which is also what ignoring the property does), so no client
-    // can get this wrong. It therefore does not need to be treated as an
-    // unsupported essential property, and its item is accepted and processed.
-    // See <https://aomediacodec.github.io/av1-avif/#layer-selector-property>
-    const LSEL_LAYER_ID_NO_SELECTION: u16 = 0xffff;
-
     while let Some(mut b) = iter.next_box()? {
         if b.head.name != BoxType::ItemPropertyAssociationBox {
             return Status::IprpBadChild.into();
@@ -3097,25 +5712,16 @@ fn read_iprp<T: Read>(
                     assert!(brand == MIF1_BRAND);
 
                     let feature = Feature::try_from(property);
-                    let property_supported = if matches!(
-                        property,
-                        ItemProperty::LayerSelection(layer_id)
-                            if *layer_id == LSEL_LAYER_ID_NO_SELECTION
-                    ) {
-                        // Not an unsupported feature; see LSEL_LAYER_ID_NO_SELECTION.
-                        true
-                    } else {
-                        match feature {
-                            Ok(feature) => {
-                                if feature.supported() {
-                                    true
-                                } else {
-                                    unsupported_features.insert(feature);
-                                    false
-                                }
+                    let property_supported = match feature {
+                        Ok(feature) => {
+                            if feature.supported() {
+                                true
+                            } else {
+                                unsupported_features.insert(feature);
+                                false
                             }
-                            Err(_) => false,
                         }
+                        Err(_) => false,
                     };
 
                     if !property_supported {
@@ -3138,7 +5744,8 @@ fn read_iprp<T: Read>(
                     // Check additional requirements on specific properties
                     match property {
                         ItemProperty::AV1Config(_)
-                        | ItemProperty::CleanAperture
+                        | ItemProperty::HEVCConfig(_)
+                        | ItemProperty::CleanAperture(_)
                         | ItemProperty::Mirroring(_)
                         | ItemProperty::Rotation(_) => {
                             if !a.essential {
@@ -3174,10 +5781,7 @@ fn read_iprp<T: Read>(
                             }
                         }
 
-                        // The following properties are unsupported, but we still enforce that
-                        // they've been correctly marked as essential or not.
-                        ItemProperty::LayeredImageIndexing => {
-                            assert!(feature.is_ok() && unsupported_features.contains(feature?));
+                        ItemProperty::LayeredImageIndexing(_) => {
                             if a.essential {
                                 fail_with_status_if(
                                     strictness != ParseStrictness::Permissive,
@@ -3186,33 +5790,18 @@ fn read_iprp<T: Read>(
                             }
                         }
 
-                        ItemProperty::LayerSelection(layer_id) => {
+                        ItemProperty::LayerSelection(_) => {
+                            // lsel shall be marked as essential regardless of its layer_id
                             if !a.essential {
-                                // lsel shall be marked as essential regardless of its
-                                // layer_id.
                                 fail_with_status_if(
                                     strictness != ParseStrictness::Permissive,
                                     Status::LselNoEssential,
                                 )?;
-                            } else if *layer_id != LSEL_LAYER_ID_NO_SELECTION {
-                                // A specific layer was requested; selecting a layer is
-                                // unsupported, so the item shall not be processed.
-                                assert!(feature.is_ok() && unsupported_features.contains(feature?));
-                                assert!(
-                                    forbidden_items.contains(&association_entry.item_id)
-                                        || strictness == ParseStrictness::Permissive
-                                );
                             }
                         }
 
-                        ItemProperty::OperatingPointSelector => {
-                            assert!(feature.is_ok() && unsupported_features.contains(feature?));
-                            if a.essential {
-                                assert!(
-                                    forbidden_items.contains(&association_entry.item_id)
-                                        || strictness == ParseStrictness::Permissive
-                                );
-                            } else {
+                        ItemProperty::OperatingPointSelector(_) => {
+                            if !a.essential {
                                 fail_with_status_if(
                                     strictness != ParseStrictness::Permissive,
                                     Status::A1opNoEssential,
@@ -3276,20 +5865,20 @@ fn read_iprp<T: Read>(
 }
 
 /// See ISOBMFF (ISO 14496-12:2020) § 8.11.14.1
-/// Variants with no associated data are recognized but not necessarily supported.
 /// See [`Feature`] to determine support.
 #[derive(Debug)]
 pub enum ItemProperty {
     AuxiliaryType(AuxiliaryTypeProperty),
     AV1Config(AV1ConfigBox),
     Channels(PixelInformation),
-    CleanAperture,
+    CleanAperture(CleanApertureBox),
     Colour(ColourInformation),
+    HEVCConfig(HEVCConfigBox),
     ImageSpatialExtents(ImageSpatialExtentsProperty),
-    LayeredImageIndexing,
+    LayeredImageIndexing(LayeredImageIndexingProperty),
     LayerSelection(u16),
     Mirroring(ImageMirror),
-    OperatingPointSelector,
+    OperatingPointSelector(u8),
     PixelAspectRatio(PixelAspectRatio),
     Rotation(ImageRotation),
     /// Necessary to validate property indices in read_iprp
@@ -3301,12 +5890,13 @@ impl From<&ItemProperty> for BoxType {
         match item_property {
             ItemProperty::AuxiliaryType(_) => BoxType::AuxiliaryTypeProperty,
             ItemProperty::AV1Config(_) => BoxType::AV1CodecConfigurationBox,
-            ItemProperty::CleanAperture => BoxType::CleanApertureBox,
+            ItemProperty::CleanAperture(_) => BoxType::CleanApertureBox,
             ItemProperty::Colour(_) => BoxType::ColourInformationBox,
-            ItemProperty::LayeredImageIndexing => BoxType::AV1LayeredImageIndexingProperty,
+            ItemProperty::HEVCConfig(_) => BoxType::HEVCConfigurationBox,
+            ItemProperty::LayeredImageIndexing(_) => BoxType::AV1LayeredImageIndexingProperty,
             ItemProperty::LayerSelection(_) => BoxType::LayerSelectorProperty,
             ItemProperty::Mirroring(_) => BoxType::ImageMirror,
-            ItemProperty::OperatingPointSelector => BoxType::OperatingPointSelectorProperty,
+            ItemProperty::OperatingPointSelector(_) => BoxType::OperatingPointSelectorProperty,
             ItemProperty::PixelAspectRatio(_) => BoxType::PixelAspectRatioBox,
             ItemProperty::Rotation(_) => BoxType::ImageRotation,
             ItemProperty::ImageSpatialExtents(_) => BoxType::ImageSpatialExtentsProperty,
@@ -3383,6 +5973,19 @@ impl ItemPropertiesBox {
         }
     }
 
+    fn associations(&self, item_id: ItemId) -> impl Iterator<Item = ItemPropertyAssociation<'_>> {
+        self.association_entries
+            .iter()
+            .filter(move |entry| entry.item_id == item_id)
+            .flat_map(|entry| entry.associations.iter())
+            .filter_map(move |a| {
+                Some(ItemPropertyAssociation {
+                    property: self.properties.get(&a.property_index)?,
+                    essential: a.essential,
+                })
+            })
+    }
+
     fn get_multiple(
         &self,
         item_id: ItemId,
@@ -3657,6 +6260,10 @@ fn read_ipco<T: Read>(
         let property = match b.head.name {
             BoxType::AuxiliaryTypeProperty => ItemProperty::AuxiliaryType(read_auxc(&mut b)?),
             BoxType::AV1CodecConfigurationBox => ItemProperty::AV1Config(read_av1c(&mut b)?),
+            BoxType::AV1LayeredImageIndexingProperty => {
+                ItemProperty::LayeredImageIndexing(read_a1lx(&mut b)?)
+            }
+            BoxType::CleanApertureBox => ItemProperty::CleanAperture(read_clap(&mut b)?),
             BoxType::ColourInformationBox => {
                 let colour_type = be_u32(&mut b)?.to_be_bytes();
                 match read_colr(&mut b, colour_type, strictness)? {
@@ -3667,6 +6274,9 @@ fn read_ipco<T: Read>(
                     }
                 }
             }
+            BoxType::HEVCConfigurationBox => {
+                ItemProperty::HEVCConfig(read_hvcc(&mut b, strictness)?)
+            }
             BoxType::ImageMirror => ItemProperty::Mirroring(read_imir(&mut b)?),
             BoxType::ImageRotation => ItemProperty::Rotation(read_irot(&mut b)?),
             BoxType::ImageSpatialExtentsProperty => {
@@ -3675,20 +6285,16 @@ fn read_ipco<T: Read>(
             BoxType::PixelAspectRatioBox => ItemProperty::PixelAspectRatio(read_pasp(&mut b)?),
             BoxType::PixelInformationBox => ItemProperty::Channels(read_pixi(&mut b)?),
             BoxType::LayerSelectorProperty => ItemProperty::LayerSelection(read_lsel(&mut b)?),
+            BoxType::OperatingPointSelectorProperty => {
+                ItemProperty::OperatingPointSelector(read_a1op(&mut b)?)
+            }
 
             other_box_type => {
                 // Even if we didn't do anything with other property types, we still store
                 // a record at the index to identify invalid indices in ipma boxes
                 skip_box_remain(&mut b)?;
-                let item_property = match other_box_type {
-                    BoxType::AV1LayeredImageIndexingProperty => ItemProperty::LayeredImageIndexing,
-                    BoxType::CleanApertureBox => ItemProperty::CleanAperture,
-                    BoxType::OperatingPointSelectorProperty => ItemProperty::OperatingPointSelector,
-                    _ => {
-                        warn!("No ItemProperty variant for {other_box_type:?}");
-                        ItemProperty::Unsupported(other_box_type)
-                    }
-                };
+                warn!("No ItemProperty variant for {other_box_type:?}");
+                let item_property = ItemProperty::Unsupported(other_box_type);
                 debug!("Storing empty record {item_property:?}");
                 item_property
             }
@@ -3716,19 +6322,90 @@ fn read_lsel<T: Read>(src: &mut BMFFBox<T>) -> Result<u16> {
     Ok(layer_id)
 }
 
-#[repr(C)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub struct ImageSpatialExtentsProperty {
-    image_width: u32,
-    image_height: u32,
+/// A LayerSelectorProperty with this layer_id enables, but does not require,
+/// progressive rendering: a client may render progressively or just show the
+/// final image.
+/// See <https://aomediacodec.github.io/av1-avif/#layer-selector-property>
+const LSEL_LAYER_ID_NO_SELECTION: u16 = 0xffff;
+
+/// Parse an OperatingPointSelectorProperty, returning its op_index.
+///
+/// See <https://aomediacodec.github.io/av1-avif/#operating-point-selector-property>
+fn read_a1op<T: Read>(src: &mut BMFFBox<T>) -> Result<u8> {
+    let op_index = src.read_u8()?;
+    Ok(op_index)
 }
 
-/// Parse image spatial extents property
+/// The sizes of the layers of a layered AV1 image item, except the last.
 ///
-/// See HEIF (ISO 23008-12:2017) § 6.5.3.1
-fn read_ispe<T: Read>(src: &mut BMFFBox<T>) -> Result<ImageSpatialExtentsProperty> {
-    if read_fullbox_version_no_flags(src)? != 0 {
-        return Err(Error::Unsupported("ispe version"));
+/// See <https://aomediacodec.github.io/av1-avif/#layered-image-indexing-property>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LayeredImageIndexingProperty {
+    pub layer_sizes: [u32; 3],
+}
+
+impl LayeredImageIndexingProperty {
+    /// The byte ranges of each layer within coded data of `item_len` bytes,
+    /// or None if the layers don't fit. A size of 0 marks the end of the
+    /// sized layers, and the last layer runs to the end of the item.
+    pub fn layer_ranges(&self, item_len: u64) -> Result<Option<TryVec<LayerRange>>> {
+        let mut ranges = TryVec::with_capacity(self.layer_sizes.len() + 1)?;
+        let mut offset = 0u64;
+        for &size in self.layer_sizes.iter().take_while(|&&size| size != 0) {
+            ranges.push(LayerRange {
+                offset,
+                length: size.into(),
+            })?;
+            offset += u64::from(size);
+        }
+        if offset >= item_len {
+            return Ok(None);
+        }
+        ranges.push(LayerRange {
+            offset,
+            length: item_len - offset,
+        })?;
+        Ok(Some(ranges))
+    }
+}
+
+/// The location of a layer within an item's coded data
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LayerRange {
+    pub offset: u64,
+    pub length: u64,
+}
+
+/// Parse an AV1LayeredImageIndexingProperty
+///
+/// See <https://aomediacodec.github.io/av1-avif/#layered-image-indexing-property>
+fn read_a1lx<T: Read>(src: &mut BMFFBox<T>) -> Result<LayeredImageIndexingProperty> {
+    let large_size = src.read_u8()? & 0b1 == 1;
+    let mut layer_sizes = [0; 3];
+    for layer_size in layer_sizes.iter_mut() {
+        *layer_size = if large_size {
+            be_u32(src)?
+        } else {
+            be_u16(src)?.into()
+        };
+    }
+    Ok(LayeredImageIndexingProperty { layer_sizes })
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ImageSpatialExtentsProperty {
+    image_width: u32,
+    image_height: u32,
+}
+
+/// Parse image spatial extents property
+///
+/// See HEIF (ISO 23008-12:2017) § 6.5.3.1
+fn read_ispe<T: Read>(src: &mut BMFFBox<T>) -> Result<ImageSpatialExtentsProperty> {
+    if read_fullbox_version_no_flags(src)? != 0 {
+        return Err(Error::Unsupported("ispe version"));
     }
 
     let image_width = be_u32(src)?;
@@ -3740,6 +6417,330 @@ fn read_ispe<T: Read>(src: &mut BMFFBox<T>) -> Result<ImageSpatialExtentsPropert
     })
 }
 
+/// Clean aperture box 'clap', which gives the region of an image or video
+/// to display as fractional width, height and offsets of its centre from
+/// the centre of the image.
+/// See ISOBMFF (ISO 14496-12:2020) § 12.1.4
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CleanApertureBox {
+    pub width_n: u32,
+    pub width_d: u32,
+    pub height_n: u32,
+    pub height_d: u32,
+    pub horiz_off_n: i32,
+    pub horiz_off_d: u32,
+    pub vert_off_n: i32,
+    pub vert_off_d: u32,
+}
+
+/// The region of an image to display, in pixels from its top left corner.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CropRectangle {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl CleanApertureBox {
+    /// The crop rectangle of the clean aperture of an image of the given
+    /// size, or None unless it's a whole number of pixels within the image,
+    /// as MIAF (ISO 23000-22:2019) § 7.3.6.7 requires.
+    pub fn crop_rectangle(&self, image_width: u32, image_height: u32) -> Option<CropRectangle> {
+        // Returns the offset and size of the aperture along one dimension.
+        fn crop(
+            size_n: u32,
+            size_d: u32,
+            off_n: i32,
+            off_d: u32,
+            image_size: u32,
+        ) -> Option<(u32, u32)> {
+            #[allow(clippy::manual_is_multiple_of)] // Allow until Gecko's MSRV is 1.87.
+            if size_d == 0 || off_d == 0 || size_n % size_d != 0 {
+                return None;
+            }
+            let size = size_n / size_d;
+            if size == 0 || size > image_size {
+                return None;
+            }
+            // offset = off_n / off_d + (image_size - size) / 2
+            let numerator =
+                2 * i128::from(off_n) + i128::from(image_size - size) * i128::from(off_d);
+            let denominator = 2 * i128::from(off_d);
+            if numerator % denominator != 0 {
+                return None;
+            }
+            let offset = u32::try_from(numerator / denominator).ok()?;
+            if offset.checked_add(size)? > image_size {
+                return None;
+            }
+            Some((offset, size))
+        }
+
+        let (x, width) = crop(
+            self.width_n,
+            self.width_d,
+            self.horiz_off_n,
+            self.horiz_off_d,
+            image_width,
+        )?;
+        let (y, height) = crop(
+            self.height_n,
+            self.height_d,
+            self.vert_off_n,
+            self.vert_off_d,
+            image_height,
+        )?;
+        Some(CropRectangle {
+            x,
+            y,
+            width,
+            height,
+        })
+    }
+}
+
+/// The size and orientation in which an image is displayed, from its 'ispe',
+/// 'clap', 'irot' and 'imir' properties, which are applied in that order
+/// per MIAF (ISO 23000-22:2019) § 7.3.6.7.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DisplayGeometry {
+    /// The region of the decoded image which is displayed
+    pub crop: CropRectangle,
+    /// The size of the displayed image
+    pub width: u32,
+    pub height: u32,
+    /// The affine transform `[a, b, c, d, e, f]` which maps a point `(x, y)`
+    /// of the decoded image to `(a * x + b * y + c, d * x + e * y + f)` in the
+    /// displayed image. Both have their origin at the top left corner.
+    pub matrix: [i64; 6],
+}
+
+impl DisplayGeometry {
+    /// Combine the transforms of an image of the given size, which is
+    /// displayed whole when there's no `crop`.
+    pub fn new(
+        image_width: u32,
+        image_height: u32,
+        crop: Option<CropRectangle>,
+        rotation: ImageRotation,
+        mirror: Option<&ImageMirror>,
+    ) -> Self {
+        let crop = crop.unwrap_or(CropRectangle {
+            x: 0,
+            y: 0,
+            width: image_width,
+            height: image_height,
+        });
+        let mut geometry = Self {
+            crop,
+            width: crop.width,
+            height: crop.height,
+            matrix: [1, 0, -i64::from(crop.x), 0, 1, -i64::from(crop.y)],
+        };
+
+        let (w, h) = (i64::from(geometry.width), i64::from(geometry.height));
+        match rotation {
+            ImageRotation::D0 => {}
+            ImageRotation::D90 => geometry.then(true, [0, 1, 0, -1, 0, w]),
+            ImageRotation::D180 => geometry.then(false, [-1, 0, w, 0, -1, h]),
+            ImageRotation::D270 => geometry.then(true, [0, -1, h, 1, 0, 0]),
+        }
+
+        let (w, h) = (i64::from(geometry.width), i64::from(geometry.height));
+        match mirror {
+            None => {}
+            Some(ImageMirror::TopBottom) => geometry.then(false, [1, 0, 0, 0, -1, h]),
+            Some(ImageMirror::LeftRight) => geometry.then(false, [-1, 0, w, 0, 1, 0]),
+        }
+
+        geometry
+    }
+
+    /// Apply the transform `next` after the current one, swapping the
+    /// displayed width and height if `transpose` is set.
+    fn then(&mut self, transpose: bool, next: [i64; 6]) {
+        let [a, b, c, d, e, f] = self.matrix;
+        self.matrix = [
+            next[0] * a + next[1] * d,
+            next[0] * b + next[1] * e,
+            next[0] * c + next[1] * f + next[2],
+            next[3] * a + next[4] * d,
+            next[3] * b + next[4] * e,
+            next[3] * c + next[4] * f + next[5],
+        ];
+        if transpose {
+            std::mem::swap(&mut self.width, &mut self.height);
+        }
+    }
+}
+
+/// Parse a clean aperture box
+///
+/// See ISOBMFF (ISO 14496-12:2020) § 12.1.4.2
+fn read_clap<T: Read>(src: &mut BMFFBox<T>) -> Result<CleanApertureBox> {
+    Ok(CleanApertureBox {
+        width_n: be_u32(src)?,
+        width_d: be_u32(src)?,
+        height_n: be_u32(src)?,
+        height_d: be_u32(src)?,
+        horiz_off_n: be_i32(src)?,
+        horiz_off_d: be_u32(src)?,
+        vert_off_n: be_i32(src)?,
+        vert_off_d: be_u32(src)?,
+    })
+}
+
+/// The layout of a 'grid' derived image, whose tiles are placed on a canvas
+/// in raster order, which is then cropped to the output size.
+/// See HEIF (ISO 23008-12:2017) § 6.6.2.3
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ImageGrid {
+    pub rows: u16,
+    pub columns: u16,
+    pub output_width: u32,
+    pub output_height: u32,
+}
+
+/// Parse the ImageGrid structure, which is the data of a 'grid' item
+///
+/// See HEIF (ISO 23008-12:2017) § 6.6.2.3.2
+fn read_image_grid<T: Read>(src: &mut T) -> Result<ImageGrid> {
+    let version = src.read_u8()?;
+    if version != 0 {
+        return Status::GridBadVersion.into();
+    }
+    let flags = src.read_u8()?;
+    let rows = u16::from(src.read_u8()?) + 1;
+    let columns = u16::from(src.read_u8()?) + 1;
+    let (output_width, output_height) = if flags & 1 == 0 {
+        (be_u16(src)?.into(), be_u16(src)?.into())
+    } else {
+        (be_u32(src)?, be_u32(src)?)
+    };
+
+    Ok(ImageGrid {
+        rows,
+        columns,
+        output_width,
+        output_height,
+    })
+}
+
+/// A signed rational number
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SignedFraction {
+    pub numerator: i32,
+    pub denominator: u32,
+}
+
+/// An unsigned rational number
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UnsignedFraction {
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+/// The gain map parameters of one colour channel, as log2 values apart from
+/// the offsets.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GainMapChannel {
+    pub gain_map_min: SignedFraction,
+    pub gain_map_max: SignedFraction,
+    pub gamma: UnsignedFraction,
+    pub base_offset: SignedFraction,
+    pub alternate_offset: SignedFraction,
+}
+
+/// The metadata of a 'tmap' derived item, describing how to apply its gain
+/// map image to its base image to produce the alternate (HDR) image.
+///
+/// See ISO 21496-1:2025 § C.2.2
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GainMapMetadata {
+    pub writer_version: u16,
+    /// Whether the gain map is applied in the colour space of the base image,
+    /// rather than that of the alternate image
+    pub use_base_colour_space: bool,
+    pub base_hdr_headroom: UnsignedFraction,
+    pub alternate_hdr_headroom: UnsignedFraction,
+    /// 1, if `channels[0]` applies to all channels, or 3
+    pub channel_count: u8,
+    pub channels: [GainMapChannel; 3],
+}
+
+/// Parse the data of a 'tmap' item, returning None if its version is
+/// unsupported.
+///
+/// See <https://aomediacodec.github.io/av1-avif/#gain-map-image-item> and
+/// ISO 21496-1:2025 § C.2.2
+fn read_tone_map_image<T: Read>(src: &mut T) -> Result<Option<GainMapMetadata>> {
+    let version = src.read_u8()?;
+    if version != 0 {
+        return Ok(None);
+    }
+    let minimum_version = be_u16(src)?;
+    if minimum_version != 0 {
+        return Ok(None);
+    }
+
+    let writer_version = be_u16(src)?;
+    let flags = src.read_u8()?;
+    let is_multichannel = flags & 0x80 != 0;
+    let use_base_colour_space = flags & 0x40 != 0;
+
+    let read_signed = |src: &mut T| -> Result<SignedFraction> {
+        let numerator = be_i32(src)?;
+        match be_u32(src)? {
+            0 => Status::TmapBadMetadata.into(),
+            denominator => Ok(SignedFraction {
+                numerator,
+                denominator,
+            }),
+        }
+    };
+    let read_unsigned = |src: &mut T| -> Result<UnsignedFraction> {
+        let numerator = be_u32(src)?;
+        match be_u32(src)? {
+            0 => Status::TmapBadMetadata.into(),
+            denominator => Ok(UnsignedFraction {
+                numerator,
+                denominator,
+            }),
+        }
+    };
+
+    let mut metadata = GainMapMetadata {
+        writer_version,
+        use_base_colour_space,
+        base_hdr_headroom: read_unsigned(src)?,
+        alternate_hdr_headroom: read_unsigned(src)?,
+        channel_count: if is_multichannel { 3 } else { 1 },
+        ..Default::default()
+    };
+    for channel in metadata
+        .channels
+        .iter_mut()
+        .take(metadata.channel_count.into())
+    {
+        *channel = GainMapChannel {
+            gain_map_min: read_signed(src)?,
+            gain_map_max: read_signed(src)?,
+            gamma: read_unsigned(src)?,
+            base_offset: read_signed(src)?,
+            alternate_offset: read_signed(src)?,
+        };
+    }
+
+    Ok(Some(metadata))
+}
+
 #[repr(C)]
 #[derive(Debug)]
 pub struct PixelAspectRatio {
@@ -3783,6 +6784,27 @@ fn read_mdcv<T: Read>(src: &mut BMFFBox<T>) -> Result<MasteringDisplayColourVolu
     })
 }
 
+/// Parse a Dolby Vision configuration box.
+/// See Dolby Vision Streams Within the ISO Base Media File Format v2.5 § 3.2
+fn read_dovi<T: Read>(src: &mut BMFFBox<T>) -> Result<DolbyVisionConfigBox> {
+    let dv_version_major = src.read_u8()?;
+    let dv_version_minor = src.read_u8()?;
+    let profile_level = be_u16(src)?;
+    let dv_bl_signal_compatibility_id = src.read_u8()? >> 4;
+    // Skip the reserved fields.
+    skip_box_remain(src)?;
+    Ok(DolbyVisionConfigBox {
+        dv_version_major,
+        dv_version_minor,
+        dv_profile: (profile_level >> 9).try_into()?,
+        dv_level: ((profile_level >> 3) & 0x3f).try_into()?,
+        rpu_present: profile_level & 0x04 != 0,
+        el_present: profile_level & 0x02 != 0,
+        bl_present: profile_level & 0x01 != 0,
+        dv_bl_signal_compatibility_id,
+    })
+}
+
 /// Parse content light level box (ISO 14496-12).
 fn read_clli<T: Read>(src: &mut BMFFBox<T>) -> Result<ContentLightLevel> {
     Ok(ContentLightLevel {
@@ -4021,6 +7043,19 @@ pub struct AuxiliaryTypeProperty {
     aux_subtype: TryString,
 }
 
+impl AuxiliaryTypeProperty {
+    /// The URN identifying the type of the auxiliary image, without its nul
+    /// terminator, e.g. `urn:mpeg:hevc:2015:auxid:2` for a depth map
+    pub fn aux_type(&self) -> &[u8] {
+        &self.aux_type
+    }
+
+    /// Data whose semantics depend on `aux_type`
+    pub fn aux_subtype(&self) -> &[u8] {
+        &self.aux_subtype
+    }
+}
+
 /// Parse image properties for auxiliary images
 /// See HEIF (ISO 23008-12:2017) § 6.5.8
 fn read_auxc<T: Read>(src: &mut BMFFBox<T>) -> Result<AuxiliaryTypeProperty> {
@@ -4177,42 +7212,12 @@ fn read_iloc<T: Read>(src: &mut BMFFBox<T>) -> Result<TryHashMap<ItemId, ItemLoc
 pub fn read_mp4<T: Read>(f: &mut T, strictness: ParseStrictness) -> Result<MediaContext> {
     let mut context = None;
     let mut found_ftyp = false;
+    let mut f = OffsetReader::new(f);
     // TODO(kinetik): Top-level parsing should handle zero-sized boxes
     // rather than throwing an error.
-    let mut iter = BoxIter::new(f);
+    let mut iter = BoxIter::new(&mut f);
     while let Some(mut b) = iter.next_box()? {
-        // box ordering: ftyp before any variable length box (inc. moov),
-        // but may not be first box in file if file signatures etc. present
-        // fragmented mp4 order: ftyp, moov, pairs of moof/mdat (1-multiple), mfra
-
-        // "special": uuid, wide (= 8 bytes)
-        // isom: moov, mdat, free, skip, udta, ftyp, moof, mfra
-        // iso2: pdin, meta
-        // iso3: meco
-        // iso5: styp, sidx, ssix, prft
-        // unknown, maybe: id32
-
-        // qt: pnot
-
-        // possibly allow anything where all printable and/or all lowercase printable
-        // "four printable characters from the ISO 8859-1 character set"
-        match b.head.name {
-            BoxType::FileTypeBox => {
-                let ftyp = read_ftyp(&mut b)?;
-                found_ftyp = true;
-                debug!("{ftyp:?}");
-            }
-            BoxType::MovieBox => {
-                context = Some(read_moov(&mut b, context, strictness)?);
-            }
-            #[cfg(feature = "meta-xml")]
-            BoxType::MetadataBox => {
-                if let Some(ctx) = &mut context {
-                    ctx.metadata = Some(read_meta(&mut b));
-                }
-            }
-            _ => skip_box_content(&mut b)?,
-        };
+        read_mp4_box(&mut b, &mut context, &mut found_ftyp, strictness)?;
         check_parser_state!(b.content);
         if context.is_some() {
             debug!(
@@ -4232,6 +7237,381 @@ pub fn read_mp4<T: Read>(f: &mut T, strictness: ParseStrictness) -> Result<Media
     context.ok_or(Error::MoovMissing)
 }
 
+/// Read the contents of an MP4 file from a seekable source.
+///
+/// This behaves like [`read_mp4`], except that top-level boxes which aren't
+/// parsed, such as 'mdat', are seeked over rather than read, so a 'moov' at
+/// the end of the file is found without reading the media data. Offsets are
+/// relative to the start of the source, not its position on entry.
+pub fn read_mp4_seekable<T: Read + Seek>(
+    f: &mut T,
+    strictness: ParseStrictness,
+) -> Result<MediaContext> {
+    let mut context = None;
+    let mut found_ftyp = false;
+    loop {
+        let box_start = f.stream_position()?;
+        let (box_end, mdat_offset) = {
+            let mut reader = OffsetReader {
+                reader: f,
+                offset: box_start,
+            };
+            let mut iter = BoxIter::new(&mut reader);
+            let mut b = match iter.next_box()? {
+                Some(b) => b,
+                None => break,
+            };
+            let parsed = is_parsed_top_level_box(b.head.name);
+            if parsed {
+                read_mp4_box(&mut b, &mut context, &mut found_ftyp, strictness)?;
+                check_parser_state!(b.content);
+                continue;
+            }
+
+            trace!("seeking past {:?}", b.head.name);
+            let mdat_offset = (b.head.name == BoxType::MediaDataBox).then_some(b.offset());
+            // An unknown size box extends to the end of the file.
+            let box_end = match b.head.size {
+                0 => None,
+                size => Some(
+                    box_start
+                        .checked_add(size)
+                        .ok_or(Error::from(Status::BoxBadSize))?,
+                ),
+            };
+            (box_end, mdat_offset)
+        };
+        if let (Some(ctx), Some(offset)) = (&mut context, mdat_offset) {
+            let file_end = f.seek(SeekFrom::End(0))?;
+            let end = box_end.map_or(file_end, |end| end.min(file_end));
+            ctx.add_mdat(offset, end.saturating_sub(offset))?;
+        }
+        match box_end {
+            Some(box_end) => f.seek(SeekFrom::Start(box_end))?,
+            None => break,
+        };
+    }
+
+    if let Some(ctx) = &mut context {
+        seek_aux_info(f, ctx)?;
+    }
+    context.ok_or(Error::MoovMissing)
+}
+
+/// Whether `read_mp4_box` parses top level boxes of type `name`, which the
+/// seekable and push parsers mustn't skip.
+fn is_parsed_top_level_box(name: BoxType) -> bool {
+    match name {
+        BoxType::FileTypeBox
+        | BoxType::MovieBox
+        | BoxType::MovieFragmentBox
+        | BoxType::MovieFragmentRandomAccessBox
+        | BoxType::SegmentIndexBox => true,
+        #[cfg(feature = "meta-xml")]
+        BoxType::MetadataBox => true,
+        _ => false,
+    }
+}
+
+/// Parse a single top-level box of an MP4 file into `context`.
+fn read_mp4_box<T: Read + Offset>(
+    b: &mut BMFFBox<T>,
+    context: &mut Option<MediaContext>,
+    found_ftyp: &mut bool,
+    strictness: ParseStrictness,
+) -> Result<()> {
+    // box ordering: ftyp before any variable length box (inc. moov),
+    // but may not be first box in file if file signatures etc. present
+    // fragmented mp4 order: ftyp, moov, pairs of moof/mdat (1-multiple), mfra
+
+    // "special": uuid, wide (= 8 bytes)
+    // isom: moov, mdat, free, skip, udta, ftyp, moof, mfra
+    // iso2: pdin, meta
+    // iso3: meco
+    // iso5: styp, sidx, ssix, prft
+    // unknown, maybe: id32
+
+    // qt: pnot
+
+    // possibly allow anything where all printable and/or all lowercase printable
+    // "four printable characters from the ISO 8859-1 character set"
+    match b.head.name {
+        BoxType::FileTypeBox => {
+            let ftyp = read_ftyp(b)?;
+            *found_ftyp = true;
+            debug!("{ftyp:?}");
+        }
+        BoxType::MovieBox => {
+            let first_track = context.as_ref().map_or(0, |ctx| ctx.tracks.len());
+            let moov = read_moov(b, context.take(), strictness)?;
+            context.insert(moov).request_track_aux_info(first_track)?;
+        }
+        #[cfg(feature = "meta-xml")]
+        BoxType::MetadataBox => {
+            if let Some(ctx) = context {
+                ctx.metadata = Some(read_meta(b));
+            }
+        }
+        BoxType::MovieFragmentBox => match context {
+            Some(ctx) => {
+                let moof_offset = b.offset() - b.head.offset;
+                // Keep the content, which may hold the sample auxiliary
+                // information of its track fragments.
+                let content_offset = b.offset();
+                let content = b.read_into_try_vec()?;
+                let mut reader = OffsetReader {
+                    reader: &mut Cursor::new(content.as_slice()),
+                    offset: content_offset,
+                };
+                let mut content_box = BMFFBox {
+                    head: b.head,
+                    content: (&mut reader).take(content.len().to_u64()),
+                };
+                // The content has been read, so a malformed moof can be
+                // skipped without losing track of the following boxes.
+                match read_moof(&mut content_box, moof_offset) {
+                    Ok(moof) => {
+                        debug!("{moof:?}");
+                        ctx.moofs.push(moof)?;
+                        ctx.request_traf_aux_info()?;
+                        ctx.add_aux_info(content_offset, &content)?;
+                    }
+                    Err(Error::OutOfMemory) => return Err(Error::OutOfMemory),
+                    Err(e) if strictness != ParseStrictness::Strict => {
+                        warn!("Skipping malformed moof: {e:?}");
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+            // A moof is meaningless without the preceding moov.
+            None => skip_box_content(b)?,
+        },
+        BoxType::MovieFragmentRandomAccessBox => match context {
+            Some(ctx) => {
+                let mfra = read_mfra_box(b)?;
+                debug!("{mfra:?}");
+                ctx.mfra = Some(mfra);
+            }
+            None => skip_box_content(b)?,
+        },
+        BoxType::SegmentIndexBox => match context {
+            Some(ctx) => {
+                let sidx_offset = b.offset() - b.head.offset;
+                let sidx = read_sidx(b, sidx_offset)?;
+                debug!("{sidx:?}");
+                ctx.sidxs.push(sidx)?;
+            }
+            None => skip_box_content(b)?,
+        },
+        _ => match context {
+            Some(ctx) => {
+                let content_offset = b.offset();
+                if ctx
+                    .next_aux_info_range(content_offset, b.bytes_left())
+                    .is_some()
+                {
+                    read_box_aux_info(b, ctx)?;
+                } else {
+                    skip_box_content(b)?;
+                }
+                if b.head.name == BoxType::MediaDataBox {
+                    ctx.add_mdat(content_offset, b.offset() - content_offset)?;
+                }
+            }
+            None => skip_box_content(b)?,
+        },
+    };
+    Ok(())
+}
+
+/// Read the sample auxiliary information `context` is waiting for from a
+/// box which isn't otherwise parsed, such as 'mdat', skipping the rest.
+fn read_box_aux_info<T: Read + Offset>(
+    src: &mut BMFFBox<T>,
+    context: &mut MediaContext,
+) -> Result<()> {
+    while let Some((offset, len)) = context.next_aux_info_range(src.offset(), src.bytes_left()) {
+        skip(src, offset - src.offset())?;
+        let data = read_buf(src, len)?;
+        context.add_aux_info(offset, &data)?;
+    }
+    skip_box_remain(src)
+}
+
+/// Read the sample auxiliary information `context` is still waiting for
+/// from the seekable source `src`. Information beyond the end of the source
+/// is left unread.
+fn seek_aux_info<T: Read + Seek>(src: &mut T, context: &mut MediaContext) -> Result<()> {
+    while let Some((offset, len)) = context.next_aux_info_range(0, u64::MAX) {
+        src.seek(SeekFrom::Start(offset))?;
+        let data = src.take(len).read_into_try_vec()?;
+        context.add_aux_info(offset, &data)?;
+        if data.len().to_u64() < len {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// The progress reported by [`Mp4PushParser::next_event`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushParseEvent {
+    /// All the data pushed so far has been consumed; push more to continue.
+    NeedMoreData,
+    /// A 'moov' box has been parsed and the tracks are available from
+    /// [`Mp4PushParser::context`].
+    MoovReady,
+    /// A 'moof' box has been parsed and appended to the `moofs` of
+    /// [`Mp4PushParser::context`].
+    FragmentReady,
+}
+
+/// An incremental MP4 parser for input that arrives in chunks, such as a
+/// network stream.
+///
+/// Data is fed in with [`push`](Self::push) and parsed by calling
+/// [`next_event`](Self::next_event) until it returns
+/// [`PushParseEvent::NeedMoreData`]. Only the boxes `read_mp4` interprets are
+/// buffered; everything else, notably 'mdat', is discarded as it arrives.
+pub struct Mp4PushParser {
+    strictness: ParseStrictness,
+    /// Data of the current top-level box not yet parsed.
+    buffer: TryVec<u8>,
+    /// File offset of the start of `buffer`.
+    offset: u64,
+    /// Bytes of a skipped box still to be discarded.
+    skip: u64,
+    /// File offset of the content of the skipped box if it's an 'mdat'.
+    skipped_mdat: Option<u64>,
+    context: Option<MediaContext>,
+    found_ftyp: bool,
+}
+
+impl Mp4PushParser {
+    pub fn new(strictness: ParseStrictness) -> Self {
+        Self {
+            strictness,
+            buffer: TryVec::new(),
+            offset: 0,
+            skip: 0,
+            skipped_mdat: None,
+            context: None,
+            found_ftyp: false,
+        }
+    }
+
+    /// Append the next chunk of the stream.
+    pub fn push(&mut self, data: &[u8]) -> Result<()> {
+        let skipped = self.skip.min(data.len().to_u64());
+        let (skipped_data, data) = data.split_at(skipped.try_into()?);
+        if let Some(context) = &mut self.context {
+            context.add_aux_info(self.offset, skipped_data)?;
+        }
+        self.add_skipped_mdat(skipped)?;
+        self.skip -= skipped;
+        self.offset += skipped;
+        self.buffer.extend_from_slice(data)?;
+        Ok(())
+    }
+
+    /// Parse as far as the data pushed so far allows, returning when a
+    /// 'moov' or 'moof' box has been parsed or more data is required.
+    pub fn next_event(&mut self) -> Result<PushParseEvent> {
+        loop {
+            if self.skip > 0 {
+                let skipped = self.skip.min(self.buffer.len().to_u64());
+                self.add_skipped_mdat(skipped)?;
+                self.consume(skipped)?;
+                self.skip -= skipped;
+                if self.skip > 0 {
+                    return Ok(PushParseEvent::NeedMoreData);
+                }
+            }
+
+            let head = match read_box_header(&mut Cursor::new(self.buffer.as_slice())) {
+                Ok(head) => head,
+                Err(Error::UnexpectedEOF) => return Ok(PushParseEvent::NeedMoreData),
+                Err(e) => return Err(e),
+            };
+
+            let buffered = is_parsed_top_level_box(head.name);
+            if !buffered || head.size == 0 {
+                // An unknown size box extends to the end of the stream.
+                self.skip = if head.size == 0 { u64::MAX } else { head.size };
+                self.skipped_mdat =
+                    (head.name == BoxType::MediaDataBox).then_some(self.offset + head.offset);
+                continue;
+            }
+
+            if self.buffer.len().to_u64() < head.size {
+                return Ok(PushParseEvent::NeedMoreData);
+            }
+
+            let size = head.size.try_into()?;
+            let mut cursor = Cursor::new(&self.buffer[..size]);
+            let mut f = OffsetReader {
+                reader: &mut cursor,
+                offset: self.offset,
+            };
+            let mut iter = BoxIter::new(&mut f);
+            if let Some(mut b) = iter.next_box()? {
+                read_mp4_box(
+                    &mut b,
+                    &mut self.context,
+                    &mut self.found_ftyp,
+                    self.strictness,
+                )?;
+                check_parser_state!(b.content);
+            }
+            self.consume(head.size)?;
+
+            match head.name {
+                BoxType::MovieBox => return Ok(PushParseEvent::MoovReady),
+                BoxType::MovieFragmentBox if self.context.is_some() => {
+                    return Ok(PushParseEvent::FragmentReady)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// The media parsed so far, once a 'moov' box has been seen.
+    pub fn context(&self) -> Option<&MediaContext> {
+        self.context.as_ref()
+    }
+
+    /// Finish parsing and return the media, as `read_mp4` would.
+    pub fn finish(self) -> Result<MediaContext> {
+        self.context.ok_or(Error::MoovMissing)
+    }
+
+    /// Record the `len` bytes being skipped from the current offset which
+    /// are 'mdat' content.
+    fn add_skipped_mdat(&mut self, len: u64) -> Result<()> {
+        if let (Some(context), Some(content_offset)) = (&mut self.context, self.skipped_mdat) {
+            let end = self.offset.saturating_add(len);
+            let start = self.offset.max(content_offset);
+            if end > start {
+                context.add_mdat(start, end - start)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Drop the first `len` bytes of the buffer.
+    fn consume(&mut self, len: u64) -> Result<()> {
+        let len: usize = len.try_into()?;
+        if let Some(context) = &mut self.context {
+            context.add_aux_info(self.offset, &self.buffer[..len])?;
+        }
+        let remaining = self.buffer.len() - len;
+        self.buffer.copy_within(len.., 0);
+        self.buffer.resize_with(remaining, Default::default)?;
+        self.offset += len.to_u64();
+        Ok(())
+    }
+}
+
 /// Parse a Movie Header Box
 /// See ISOBMFF (ISO 14496-12:2020) § 8.2.2
 fn parse_mvhd<T: Read>(f: &mut BMFFBox<T>) -> Result<Option<MediaTimeScale>> {
@@ -4262,6 +7642,11 @@ pub fn read_moov<T: Read>(
         mut userdata,
         #[cfg(feature = "meta-xml")]
         metadata,
+        moofs,
+        mdats,
+        sidxs,
+        mfra,
+        aux_info_requests,
     } = context.unwrap_or_default();
 
     let mut iter = f.box_iter();
@@ -4322,13 +7707,18 @@ pub fn read_moov<T: Read>(
         userdata,
         #[cfg(feature = "meta-xml")]
         metadata,
+        moofs,
+        mdats,
+        sidxs,
+        mfra,
+        aux_info_requests,
     })
 }
 
 fn read_pssh<T: Read>(src: &mut BMFFBox<T>) -> Result<ProtectionSystemSpecificHeaderBox> {
     let len = src.bytes_left();
     let mut box_content = read_buf(src, len)?;
-    let (system_id, kid, data) = {
+    let (version, system_id, kid, data) = {
         let pssh = &mut Cursor::new(&box_content);
 
         let (version, _) = read_fullbox_extra(pssh)?;
@@ -4353,7 +7743,7 @@ fn read_pssh<T: Read>(src: &mut BMFFBox<T>) -> Result<ProtectionSystemSpecificHe
         let data_size = be_u32(pssh)?;
         let data = read_buf(pssh, data_size.into())?;
 
-        (system_id, kid, data)
+        (version, system_id, kid, data)
     };
 
     let mut pssh_box = TryVec::new();
@@ -4362,6 +7752,7 @@ fn read_pssh<T: Read>(src: &mut BMFFBox<T>) -> Result<ProtectionSystemSpecificHe
     pssh_box.append(&mut box_content)?;
 
     Ok(ProtectionSystemSpecificHeaderBox {
+        version,
         system_id,
         kid,
         data,
@@ -4374,16 +7765,23 @@ fn read_pssh<T: Read>(src: &mut BMFFBox<T>) -> Result<ProtectionSystemSpecificHe
 fn read_mvex<T: Read>(src: &mut BMFFBox<T>) -> Result<MovieExtendsBox> {
     let mut iter = src.box_iter();
     let mut fragment_duration = None;
+    let mut trex = TryVec::new();
     while let Some(mut b) = iter.next_box()? {
         match b.head.name {
             BoxType::MovieExtendsHeaderBox => {
                 let duration = read_mehd(&mut b)?;
                 fragment_duration = Some(duration);
             }
+            BoxType::TrackExtendsBox => {
+                trex.push(read_trex(&mut b)?)?;
+            }
             _ => skip_box_content(&mut b)?,
         }
     }
-    Ok(MovieExtendsBox { fragment_duration })
+    Ok(MovieExtendsBox {
+        fragment_duration,
+        trex,
+    })
 }
 
 fn read_mehd<T: Read>(src: &mut BMFFBox<T>) -> Result<MediaScaledTime> {
@@ -4396,25 +7794,447 @@ fn read_mehd<T: Read>(src: &mut BMFFBox<T>) -> Result<MediaScaledTime> {
     Ok(MediaScaledTime(fragment_duration))
 }
 
-/// Parse a Track Box
-/// See ISOBMFF (ISO 14496-12:2020) § 8.3.1.
-fn read_trak<T: Read>(
-    f: &mut BMFFBox<T>,
-    track: &mut Track,
-    strictness: ParseStrictness,
-) -> Result<()> {
-    let mut iter = f.box_iter();
+/// Parse a Track Extends Box
+/// See ISOBMFF (ISO 14496-12:2020) § 8.8.3
+fn read_trex<T: Read>(src: &mut BMFFBox<T>) -> Result<TrackExtendsBox> {
+    let (_, _) = read_fullbox_extra(src)?;
+    let track_id = be_u32(src)?;
+    let default_sample_description_index = be_u32(src)?;
+    let default_sample_duration = be_u32(src)?;
+    let default_sample_size = be_u32(src)?;
+    let default_sample_flags = be_u32(src)?;
+    Ok(TrackExtendsBox {
+        track_id,
+        default_sample_description_index,
+        default_sample_duration,
+        default_sample_size,
+        default_sample_flags,
+    })
+}
+
+/// Parse a Movie Fragment Box
+/// See ISOBMFF (ISO 14496-12:2020) § 8.8.4
+///
+/// `offset` is the file offset of the start of the 'moof' box.
+fn read_moof<T: Read>(src: &mut BMFFBox<T>, offset: u64) -> Result<MovieFragmentBox> {
+    let mut moof = MovieFragmentBox {
+        offset,
+        ..Default::default()
+    };
+    let mut iter = src.box_iter();
     while let Some(mut b) = iter.next_box()? {
         match b.head.name {
-            BoxType::TrackHeaderBox => {
-                let tkhd = read_tkhd(&mut b)?;
-                track.track_id = Some(tkhd.track_id);
-                track.tkhd = Some(tkhd.clone());
-                debug!("{tkhd:?}");
+            BoxType::MovieFragmentHeaderBox => {
+                let (_, _) = read_fullbox_extra(&mut b)?;
+                moof.sequence_number = be_u32(&mut b)?;
             }
-            BoxType::EditBox => read_edts(&mut b, track)?,
-            BoxType::MediaBox => read_mdia(&mut b, track, strictness)?,
-            BoxType::TrackReferenceBox => track.tref = Some(read_tref(&mut b)?),
+            BoxType::TrackFragmentBox => {
+                let traf = read_traf(&mut b)?;
+                moof.trafs.push(traf)?;
+            }
+            BoxType::ProtectionSystemSpecificHeaderBox => {
+                let pssh = read_pssh(&mut b)?;
+                debug!("{pssh:?}");
+                moof.psshs.push(pssh)?;
+            }
+            _ => skip_box_content(&mut b)?,
+        };
+        check_parser_state!(b.content);
+    }
+    Ok(moof)
+}
+
+/// Parse a Track Fragment Box
+/// See ISOBMFF (ISO 14496-12:2020) § 8.8.6
+fn read_traf<T: Read>(src: &mut BMFFBox<T>) -> Result<TrackFragmentBox> {
+    let mut tfhd = None;
+    let mut base_media_decode_time = None;
+    let mut truns = TryVec::new();
+    let mut senc = None;
+    let mut saiz = None;
+    let mut saio = None;
+    let mut sgpds = TryVec::new();
+    let mut sbgps = TryVec::new();
+    let mut iter = src.box_iter();
+    while let Some(mut b) = iter.next_box()? {
+        match b.head.name {
+            BoxType::TrackFragmentHeaderBox => {
+                if tfhd.is_some() {
+                    return Status::TfhdBadQuantity.into();
+                }
+                tfhd = Some(read_tfhd(&mut b)?);
+            }
+            BoxType::TrackFragmentBaseMediaDecodeTimeBox => {
+                base_media_decode_time = Some(read_tfdt(&mut b)?);
+            }
+            BoxType::TrackRunBox => {
+                truns.push(read_trun(&mut b)?)?;
+            }
+            BoxType::SampleEncryptionBox => {
+                senc = Some(read_senc(&mut b)?);
+            }
+            BoxType::SampleAuxiliaryInformationSizesBox => {
+                let box_saiz = read_saiz(&mut b)?;
+                if is_encryption_aux_info(&box_saiz.aux_info_type) {
+                    saiz = Some(box_saiz);
+                }
+            }
+            BoxType::SampleAuxiliaryInformationOffsetsBox => {
+                let box_saio = read_saio(&mut b)?;
+                if is_encryption_aux_info(&box_saio.aux_info_type) {
+                    saio = Some(box_saio);
+                }
+            }
+            BoxType::SampleGroupDescriptionBox => {
+                sgpds.push(read_sgpd(&mut b)?)?;
+            }
+            BoxType::SampleToGroupBox => {
+                sbgps.push(read_sbgp(&mut b)?)?;
+            }
+            _ => skip_box_content(&mut b)?,
+        };
+        check_parser_state!(b.content);
+    }
+    let tfhd = match tfhd {
+        Some(tfhd) => tfhd,
+        None => return Status::TfhdBadQuantity.into(),
+    };
+    Ok(TrackFragmentBox {
+        tfhd,
+        base_media_decode_time,
+        truns,
+        senc,
+        saiz,
+        saio,
+        aux_info: None,
+        sgpds,
+        sbgps,
+    })
+}
+
+/// Parse a Track Fragment Header Box
+/// See ISOBMFF (ISO 14496-12:2020) § 8.8.7
+fn read_tfhd<T: Read>(src: &mut BMFFBox<T>) -> Result<TrackFragmentHeaderBox> {
+    const BASE_DATA_OFFSET_PRESENT: u32 = 0x00_0001;
+    const SAMPLE_DESCRIPTION_INDEX_PRESENT: u32 = 0x00_0002;
+    const DEFAULT_SAMPLE_DURATION_PRESENT: u32 = 0x00_0008;
+    const DEFAULT_SAMPLE_SIZE_PRESENT: u32 = 0x00_0010;
+    const DEFAULT_SAMPLE_FLAGS_PRESENT: u32 = 0x00_0020;
+    const DURATION_IS_EMPTY: u32 = 0x01_0000;
+    const DEFAULT_BASE_IS_MOOF: u32 = 0x02_0000;
+
+    let (_, flags) = read_fullbox_extra(src)?;
+    let track_id = be_u32(src)?;
+    let base_data_offset = if flags & BASE_DATA_OFFSET_PRESENT != 0 {
+        Some(be_u64(src)?)
+    } else {
+        None
+    };
+    let mut read_optional = |flag: u32| -> Result<Option<u32>> {
+        Ok(if flags & flag != 0 {
+            Some(be_u32(src)?)
+        } else {
+            None
+        })
+    };
+    let sample_description_index = read_optional(SAMPLE_DESCRIPTION_INDEX_PRESENT)?;
+    let default_sample_duration = read_optional(DEFAULT_SAMPLE_DURATION_PRESENT)?;
+    let default_sample_size = read_optional(DEFAULT_SAMPLE_SIZE_PRESENT)?;
+    let default_sample_flags = read_optional(DEFAULT_SAMPLE_FLAGS_PRESENT)?;
+
+    Ok(TrackFragmentHeaderBox {
+        track_id,
+        base_data_offset,
+        sample_description_index,
+        default_sample_duration,
+        default_sample_size,
+        default_sample_flags,
+        duration_is_empty: flags & DURATION_IS_EMPTY != 0,
+        default_base_is_moof: flags & DEFAULT_BASE_IS_MOOF != 0,
+    })
+}
+
+/// Parse a Track Fragment Base Media Decode Time Box
+/// See ISOBMFF (ISO 14496-12:2020) § 8.8.12
+fn read_tfdt<T: Read>(src: &mut BMFFBox<T>) -> Result<u64> {
+    let (version, _) = read_fullbox_extra(src)?;
+    match version {
+        1 => be_u64(src),
+        0 => Ok(u64::from(be_u32(src)?)),
+        _ => Status::TfdtBadVersion.into(),
+    }
+}
+
+/// Read the movie fragment random access box 'mfra' from the end of a
+/// seekable source, as located by the trailing 'mfro' box, without reading
+/// the rest of the file.
+///
+/// Returns None if the source doesn't end with an 'mfro' box locating an
+/// 'mfra'. The position of the source is restored before returning.
+pub fn read_mfra<T: Read + Seek>(f: &mut T) -> Result<Option<MovieFragmentRandomAccessBox>> {
+    let position = f.stream_position()?;
+    let file_size = f.seek(SeekFrom::End(0))?;
+    let result = read_mfra_from_end(f, file_size);
+    f.seek(SeekFrom::Start(position))?;
+    result
+}
+
+fn read_mfra_from_end<T: Read + Seek>(
+    f: &mut T,
+    file_size: u64,
+) -> Result<Option<MovieFragmentRandomAccessBox>> {
+    const MFRO_SIZE: u64 = 16;
+
+    if file_size < MFRO_SIZE {
+        return Ok(None);
+    }
+    f.seek(SeekFrom::Start(file_size - MFRO_SIZE))?;
+    let mut mfro = [0; MFRO_SIZE as usize];
+    f.read_exact(&mut mfro)?;
+    let mut mfro = Cursor::new(&mfro[..]);
+    if u64::from(be_u32(&mut mfro)?) != MFRO_SIZE
+        || BoxType::from(be_u32(&mut mfro)?) != BoxType::MovieFragmentRandomAccessOffsetBox
+    {
+        return Ok(None);
+    }
+    let (_, _) = read_fullbox_extra(&mut mfro)?;
+    let mfra_size = u64::from(be_u32(&mut mfro)?);
+    if mfra_size > file_size {
+        return Ok(None);
+    }
+
+    let mfra_offset = file_size - mfra_size;
+    f.seek(SeekFrom::Start(mfra_offset))?;
+    let mut reader = OffsetReader {
+        reader: &mut *f,
+        offset: mfra_offset,
+    };
+    let mut iter = BoxIter::new(&mut reader);
+    let mut b = match iter.next_box()? {
+        Some(b) => b,
+        None => return Ok(None),
+    };
+    if b.head.name != BoxType::MovieFragmentRandomAccessBox || b.head.size != mfra_size {
+        return Ok(None);
+    }
+    let mfra = read_mfra_box(&mut b)?;
+    check_parser_state!(b.content);
+    Ok(Some(mfra))
+}
+
+/// Parse a Movie Fragment Random Access Box
+/// See ISOBMFF (ISO 14496-12:2020) § 8.8.9
+fn read_mfra_box<T: Read>(src: &mut BMFFBox<T>) -> Result<MovieFragmentRandomAccessBox> {
+    let mut mfra = MovieFragmentRandomAccessBox::default();
+    let mut iter = src.box_iter();
+    while let Some(mut b) = iter.next_box()? {
+        match b.head.name {
+            BoxType::TrackFragmentRandomAccessBox => {
+                let tfra = read_tfra(&mut b)?;
+                mfra.tfras.push(tfra)?;
+            }
+            _ => skip_box_content(&mut b)?,
+        };
+        check_parser_state!(b.content);
+    }
+    Ok(mfra)
+}
+
+/// Parse a Track Fragment Random Access Box
+/// See ISOBMFF (ISO 14496-12:2020) § 8.8.10
+fn read_tfra<T: Read>(src: &mut BMFFBox<T>) -> Result<TrackFragmentRandomAccessBox> {
+    let (version, _) = read_fullbox_extra(src)?;
+    if version > 1 {
+        return Status::TfraBadVersion.into();
+    }
+    let track_id = be_u32(src)?;
+    let sizes = be_u32(src)?;
+    let traf_number_size = ((sizes >> 4) & 0x3) as usize + 1;
+    let trun_number_size = ((sizes >> 2) & 0x3) as usize + 1;
+    let sample_number_size = (sizes & 0x3) as usize + 1;
+    let number_of_entry = be_u32(src)?;
+
+    let time_size = if version == 1 { 16 } else { 8 };
+    let entry_size = time_size + traf_number_size + trun_number_size + sample_number_size;
+    if number_of_entry
+        .checked_mul(entry_size.try_into()?)
+        .is_none_or(|bytes| u64::from(bytes) > src.bytes_left())
+    {
+        return Status::TfraBadSize.into();
+    }
+
+    let mut entries = TryVec::with_capacity(number_of_entry.to_usize())?;
+    for _ in 0..number_of_entry {
+        let (time, moof_offset) = if version == 1 {
+            (be_u64(src)?, be_u64(src)?)
+        } else {
+            (u64::from(be_u32(src)?), u64::from(be_u32(src)?))
+        };
+        let mut read_number = |size: usize| -> Result<u32> {
+            let number = src.read_uint::<byteorder::BigEndian>(size)?;
+            Ok(number.try_into()?)
+        };
+        entries.push(TrackFragmentRandomAccessEntry {
+            time,
+            moof_offset,
+            traf_number: read_number(traf_number_size)?,
+            trun_number: read_number(trun_number_size)?,
+            sample_number: read_number(sample_number_size)?,
+        })?;
+    }
+
+    Ok(TrackFragmentRandomAccessBox { track_id, entries })
+}
+
+/// Parse a Segment Index Box
+/// See ISOBMFF (ISO 14496-12:2020) § 8.16.3
+///
+/// `offset` is the file offset of the start of the 'sidx' box.
+fn read_sidx<T: Read + Offset>(src: &mut BMFFBox<T>, offset: u64) -> Result<SegmentIndexBox> {
+    let (version, _) = read_fullbox_extra(src)?;
+    let reference_id = be_u32(src)?;
+    let timescale = be_u32(src)?;
+    let (earliest_presentation_time, first_offset) = match version {
+        0 => (u64::from(be_u32(src)?), u64::from(be_u32(src)?)),
+        1 => (be_u64(src)?, be_u64(src)?),
+        _ => return Status::SidxBadVersion.into(),
+    };
+    skip(src, 2)?; // reserved
+    let reference_count = be_u16(src)?;
+    if u64::from(reference_count) * 12 > src.bytes_left() {
+        return Status::SidxBadReference.into();
+    }
+
+    let mut references = TryVec::with_capacity(reference_count.into())?;
+    for _ in 0..reference_count {
+        let reference = be_u32(src)?;
+        let subsegment_duration = be_u32(src)?;
+        let sap = be_u32(src)?;
+        references.push(SegmentReference {
+            references_index: reference & 0x8000_0000 != 0,
+            referenced_size: reference & 0x7fff_ffff,
+            subsegment_duration,
+            starts_with_sap: sap & 0x8000_0000 != 0,
+            sap_type: ((sap >> 28) & 0x7) as u8,
+            sap_delta_time: sap & 0x0fff_ffff,
+        })?;
+    }
+
+    Ok(SegmentIndexBox {
+        offset,
+        anchor_point: src.offset() + src.bytes_left(),
+        reference_id,
+        timescale,
+        earliest_presentation_time,
+        first_offset,
+        references,
+    })
+}
+
+/// Parse a Track Fragment Run Box
+/// See ISOBMFF (ISO 14496-12:2020) § 8.8.8
+fn read_trun<T: Read>(src: &mut BMFFBox<T>) -> Result<TrackRunBox> {
+    const DATA_OFFSET_PRESENT: u32 = 0x00_0001;
+    const FIRST_SAMPLE_FLAGS_PRESENT: u32 = 0x00_0004;
+    const SAMPLE_DURATION_PRESENT: u32 = 0x00_0100;
+    const SAMPLE_SIZE_PRESENT: u32 = 0x00_0200;
+    const SAMPLE_FLAGS_PRESENT: u32 = 0x00_0400;
+    const SAMPLE_COMPOSITION_TIME_OFFSET_PRESENT: u32 = 0x00_0800;
+
+    let (version, flags) = read_fullbox_extra(src)?;
+    if version > 1 {
+        return Status::TrunBadVersion.into();
+    }
+    let sample_count = be_u32(src)?;
+    let data_offset = if flags & DATA_OFFSET_PRESENT != 0 {
+        Some(be_i32(src)?)
+    } else {
+        None
+    };
+    let first_sample_flags = if flags & FIRST_SAMPLE_FLAGS_PRESENT != 0 {
+        Some(be_u32(src)?)
+    } else {
+        None
+    };
+
+    let sample_fields = [
+        SAMPLE_DURATION_PRESENT,
+        SAMPLE_SIZE_PRESENT,
+        SAMPLE_FLAGS_PRESENT,
+        SAMPLE_COMPOSITION_TIME_OFFSET_PRESENT,
+    ];
+    let bytes_per_sample = 4 * sample_fields.iter().filter(|&&f| flags & f != 0).count();
+    if sample_count
+        .checked_mul(bytes_per_sample.try_into()?)
+        .is_none_or(|bytes| u64::from(bytes) > src.bytes_left())
+    {
+        return Status::TrunBadSize.into();
+    }
+
+    let mut samples = TryVec::new();
+    if bytes_per_sample == 0 {
+        // Every sample uses the defaults, so there is nothing to store, and
+        // the sample count is only bounded by the 'mdat' holding their data.
+        return Ok(TrackRunBox {
+            data_offset,
+            first_sample_flags,
+            sample_count,
+            samples,
+        });
+    }
+
+    samples.reserve(sample_count.to_usize())?;
+    for _ in 0..sample_count {
+        let mut read_optional = |flag: u32| -> Result<Option<u32>> {
+            Ok(if flags & flag != 0 {
+                Some(be_u32(src)?)
+            } else {
+                None
+            })
+        };
+        let duration = read_optional(SAMPLE_DURATION_PRESENT)?;
+        let size = read_optional(SAMPLE_SIZE_PRESENT)?;
+        let sample_flags = read_optional(SAMPLE_FLAGS_PRESENT)?;
+        // According to spec, version 0 offsets are unsigned; however, as
+        // with 'ctts', some buggy contents have negative values when
+        // version == 0. So we always read them as signed here.
+        let composition_time_offset = read_optional(SAMPLE_COMPOSITION_TIME_OFFSET_PRESENT)?
+            .map(|offset| i64::from(offset as i32));
+        samples.push(TrackRunSample {
+            duration,
+            size,
+            flags: sample_flags,
+            composition_time_offset,
+        })?;
+    }
+
+    Ok(TrackRunBox {
+        data_offset,
+        first_sample_flags,
+        sample_count,
+        samples,
+    })
+}
+
+/// Parse a Track Box
+/// See ISOBMFF (ISO 14496-12:2020) § 8.3.1.
+fn read_trak<T: Read>(
+    f: &mut BMFFBox<T>,
+    track: &mut Track,
+    strictness: ParseStrictness,
+) -> Result<()> {
+    let mut iter = f.box_iter();
+    while let Some(mut b) = iter.next_box()? {
+        match b.head.name {
+            BoxType::TrackHeaderBox => {
+                let tkhd = read_tkhd(&mut b)?;
+                track.track_id = Some(tkhd.track_id);
+                track.tkhd = Some(tkhd.clone());
+                debug!("{tkhd:?}");
+            }
+            BoxType::EditBox => read_edts(&mut b, track)?,
+            BoxType::MediaBox => read_mdia(&mut b, track, strictness)?,
+            BoxType::TrackReferenceBox => track.tref = Some(read_tref(&mut b)?),
             _ => skip_box_content(&mut b)?,
         };
         check_parser_state!(b.content);
@@ -4611,6 +8431,35 @@ fn read_stbl<T: Read>(
                 debug!("{ctts:?}");
                 track.ctts = Some(ctts);
             }
+            BoxType::SampleEncryptionBox => {
+                let senc = read_senc(&mut b)?;
+                debug!("{senc:?}");
+                track.senc = Some(senc);
+            }
+            BoxType::SampleAuxiliaryInformationSizesBox => {
+                let saiz = read_saiz(&mut b)?;
+                debug!("{saiz:?}");
+                if is_encryption_aux_info(&saiz.aux_info_type) {
+                    track.saiz = Some(saiz);
+                }
+            }
+            BoxType::SampleAuxiliaryInformationOffsetsBox => {
+                let saio = read_saio(&mut b)?;
+                debug!("{saio:?}");
+                if is_encryption_aux_info(&saio.aux_info_type) {
+                    track.saio = Some(saio);
+                }
+            }
+            BoxType::SampleGroupDescriptionBox => {
+                let sgpd = read_sgpd(&mut b)?;
+                debug!("{sgpd:?}");
+                track.sgpds.push(sgpd)?;
+            }
+            BoxType::SampleToGroupBox => {
+                let sbgp = read_sbgp(&mut b)?;
+                debug!("{sbgp:?}");
+                track.sbgps.push(sbgp)?;
+            }
             _ => skip_box_content(&mut b)?,
         };
         check_parser_state!(b.content);
@@ -4949,145 +8798,1086 @@ fn read_stsz<T: Read>(src: &mut BMFFBox<T>) -> Result<SampleSizeBox> {
     })
 }
 
-/// Parse a stts box.
-/// See ISOBMFF (ISO 14496-12:2020) § 8.6.1.2
-fn read_stts<T: Read>(src: &mut BMFFBox<T>) -> Result<TimeToSampleBox> {
-    let (_, _) = read_fullbox_extra(src)?;
-    let sample_count = be_u32(src)?;
-    let mut samples = TryVec::with_capacity(sample_count.to_usize())?;
-    for _ in 0..sample_count {
-        let sample_count = be_u32(src)?;
-        let sample_delta = be_u32(src)?;
-        samples.push(Sample {
-            sample_count,
-            sample_delta,
+/// Parse a stts box.
+/// See ISOBMFF (ISO 14496-12:2020) § 8.6.1.2
+fn read_stts<T: Read>(src: &mut BMFFBox<T>) -> Result<TimeToSampleBox> {
+    let (_, _) = read_fullbox_extra(src)?;
+    let sample_count = be_u32(src)?;
+    let mut samples = TryVec::with_capacity(sample_count.to_usize())?;
+    for _ in 0..sample_count {
+        let sample_count = be_u32(src)?;
+        let sample_delta = be_u32(src)?;
+        samples.push(Sample {
+            sample_count,
+            sample_delta,
+        })?;
+    }
+
+    // Padding could be added in some contents.
+    skip_box_remain(src)?;
+
+    Ok(TimeToSampleBox { samples })
+}
+
+/// Parse a VPx Config Box.
+fn read_vpcc<T: Read>(src: &mut BMFFBox<T>) -> Result<VPxConfigBox> {
+    let (version, _) = read_fullbox_extra(src)?;
+    let supported_versions = [0, 1];
+    if !supported_versions.contains(&version) {
+        return Err(Error::Unsupported("unknown vpcC version"));
+    }
+
+    let profile = src.read_u8()?;
+    let level = src.read_u8()?;
+    let (
+        bit_depth,
+        colour_primaries,
+        chroma_subsampling,
+        transfer_characteristics,
+        matrix_coefficients,
+        video_full_range_flag,
+    ) = if version == 0 {
+        let (bit_depth, colour_primaries) = {
+            let byte = src.read_u8()?;
+            ((byte >> 4) & 0x0f, byte & 0x0f)
+        };
+        // Note, transfer_characteristics was known as transfer_function in v0
+        let (chroma_subsampling, transfer_characteristics, video_full_range_flag) = {
+            let byte = src.read_u8()?;
+            ((byte >> 4) & 0x0f, (byte >> 1) & 0x07, (byte & 1) == 1)
+        };
+        (
+            bit_depth,
+            colour_primaries,
+            chroma_subsampling,
+            transfer_characteristics,
+            None,
+            video_full_range_flag,
+        )
+    } else {
+        let (bit_depth, chroma_subsampling, video_full_range_flag) = {
+            let byte = src.read_u8()?;
+            ((byte >> 4) & 0x0f, (byte >> 1) & 0x07, (byte & 1) == 1)
+        };
+        let colour_primaries = src.read_u8()?;
+        let transfer_characteristics = src.read_u8()?;
+        let matrix_coefficients = src.read_u8()?;
+
+        (
+            bit_depth,
+            colour_primaries,
+            chroma_subsampling,
+            transfer_characteristics,
+            Some(matrix_coefficients),
+            video_full_range_flag,
+        )
+    };
+
+    let codec_init_size = be_u16(src)?;
+    let codec_init = read_buf(src, codec_init_size.into())?;
+
+    // TODO(rillian): validate field value ranges.
+    Ok(VPxConfigBox {
+        profile,
+        level,
+        bit_depth,
+        colour_primaries,
+        chroma_subsampling,
+        transfer_characteristics,
+        matrix_coefficients,
+        video_full_range_flag,
+        codec_init,
+    })
+}
+
+/// See [AV1-ISOBMFF § 2.3.3](https://aomediacodec.github.io/av1-isobmff/#av1codecconfigurationbox-syntax)
+fn read_av1c<T: Read>(src: &mut BMFFBox<T>) -> Result<AV1ConfigBox> {
+    // We want to store the raw config as well as a structured (parsed) config, so create a copy of
+    // the raw config so we have it later, and then parse the structured data from that.
+    let raw_config = src.read_into_try_vec()?;
+    let mut raw_config_slice = raw_config.as_slice();
+    let marker_byte = raw_config_slice.read_u8()?;
+    if marker_byte & 0x80 != 0x80 {
+        return Err(Error::Unsupported("missing av1C marker bit"));
+    }
+    if marker_byte & 0x7f != 0x01 {
+        return Err(Error::Unsupported("missing av1C marker bit"));
+    }
+    let profile_byte = raw_config_slice.read_u8()?;
+    let profile = (profile_byte & 0xe0) >> 5;
+    let level = profile_byte & 0x1f;
+    let flags_byte = raw_config_slice.read_u8()?;
+    let tier = (flags_byte & 0x80) >> 7;
+    let bit_depth = match flags_byte & 0x60 {
+        0x60 => 12,
+        0x40 => 10,
+        _ => 8,
+    };
+    let monochrome = flags_byte & 0x10 == 0x10;
+    let chroma_subsampling_x = (flags_byte & 0x08) >> 3;
+    let chroma_subsampling_y = (flags_byte & 0x04) >> 2;
+    let chroma_sample_position = flags_byte & 0x03;
+    let delay_byte = raw_config_slice.read_u8()?;
+    let initial_presentation_delay_present = (delay_byte & 0x10) == 0x10;
+    let initial_presentation_delay_minus_one = if initial_presentation_delay_present {
+        delay_byte & 0x0f
+    } else {
+        0
+    };
+
+    // The configOBUs are only informative, so failing to parse them isn't
+    // an error
+    let sequence_header = match read_av1_sequence_header_obu(raw_config_slice) {
+        Ok(sequence_header) => sequence_header,
+        Err(e) => {
+            warn!("Failed to parse av1C sequence header: {e:?}");
+            None
+        }
+    };
+
+    Ok(AV1ConfigBox {
+        profile,
+        level,
+        tier,
+        bit_depth,
+        monochrome,
+        chroma_subsampling_x,
+        chroma_subsampling_y,
+        chroma_sample_position,
+        initial_presentation_delay_present,
+        initial_presentation_delay_minus_one,
+        sequence_header,
+        raw_config,
+    })
+}
+
+const OBU_SEQUENCE_HEADER: u8 = 1;
+
+/// Find and parse the sequence header OBU among `obus`.
+/// See [AV1 § 5.3](https://aomediacodec.github.io/av1-spec/#obu-syntax)
+fn read_av1_sequence_header_obu(mut obus: &[u8]) -> Result<Option<AV1SequenceHeader>> {
+    while !obus.is_empty() {
+        let header = obus.read_u8()?;
+        let obu_type = (header >> 3) & 0x0f;
+        let obu_extension_flag = header & 0x04 != 0;
+        let obu_has_size_field = header & 0x02 != 0;
+        if obu_extension_flag {
+            skip(&mut obus, 1)?;
+        }
+        let obu_size = if obu_has_size_field {
+            read_leb128(&mut obus)?.try_into()?
+        } else {
+            obus.len()
+        };
+        if obu_size > obus.len() {
+            return Err(Error::UnexpectedEOF);
+        }
+        let (obu, rest) = obus.split_at(obu_size);
+        if obu_type == OBU_SEQUENCE_HEADER {
+            return read_av1_sequence_header(obu).map(Some);
+        }
+        obus = rest;
+    }
+    Ok(None)
+}
+
+/// See [AV1 § 4.10.5](https://aomediacodec.github.io/av1-spec/#leb128)
+fn read_leb128<T: ReadBytesExt>(src: &mut T) -> Result<u64> {
+    let mut value = 0;
+    for i in 0..8 {
+        let byte = src.read_u8()?;
+        value |= u64::from(byte & 0x7f) << (i * 7);
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Status::BitReaderError.into()
+}
+
+/// See [AV1 § 4.10.3](https://aomediacodec.github.io/av1-spec/#uvlc)
+fn read_uvlc(bit_reader: &mut BitReader) -> Result<u64> {
+    let mut leading_zeros = 0u8;
+    while !bit_reader.read_bool()? {
+        leading_zeros = leading_zeros.saturating_add(1);
+    }
+    if leading_zeros >= 32 {
+        return Ok((1 << 32) - 1);
+    }
+    let value = bit_reader.read_u64(leading_zeros)?;
+    Ok(value + (1 << leading_zeros) - 1)
+}
+
+/// See [AV1 § 5.5.1](https://aomediacodec.github.io/av1-spec/#general-sequence-header-obu-syntax)
+fn read_av1_sequence_header(obu: &[u8]) -> Result<AV1SequenceHeader> {
+    let bit_reader = &mut BitReader::new(obu);
+    let seq_profile = bit_reader.read_u8(3)?;
+    if seq_profile > 2 {
+        return Err(Error::Unsupported("unsupported AV1 seq_profile"));
+    }
+    let still_picture = bit_reader.read_bool()?;
+    let reduced_still_picture_header = bit_reader.read_bool()?;
+
+    let mut timing_info = None;
+    let mut operating_points = TryVec::new();
+    if reduced_still_picture_header {
+        operating_points.push(AV1OperatingPoint {
+            idc: 0,
+            seq_level_idx: bit_reader.read_u8(5)?,
+            seq_tier: 0,
+        })?;
+    } else {
+        let mut buffer_delay_length = 0;
+        if bit_reader.read_bool()? {
+            // timing_info_present_flag
+            let num_units_in_display_tick = bit_reader.read_u32(32)?;
+            let time_scale = bit_reader.read_u32(32)?;
+            let num_ticks_per_picture = if bit_reader.read_bool()? {
+                // equal_picture_interval
+                Some(read_uvlc(bit_reader)? + 1)
+            } else {
+                None
+            };
+            timing_info = Some(AV1TimingInfo {
+                num_units_in_display_tick,
+                time_scale,
+                num_ticks_per_picture,
+            });
+            if bit_reader.read_bool()? {
+                // decoder_model_info_present_flag
+                buffer_delay_length = bit_reader.read_u8(5)? + 1;
+                bit_reader.skip(32)?; // num_units_in_decoding_tick
+                bit_reader.skip(5)?; // buffer_removal_time_length_minus_1
+                bit_reader.skip(5)?; // frame_presentation_time_length_minus_1
+            }
+        }
+        let initial_display_delay_present_flag = bit_reader.read_bool()?;
+        let operating_points_cnt = bit_reader.read_u8(5)? + 1;
+        for _ in 0..operating_points_cnt {
+            let idc = bit_reader.read_u16(12)?;
+            let seq_level_idx = bit_reader.read_u8(5)?;
+            let seq_tier = if seq_level_idx > 7 {
+                bit_reader.read_u8(1)?
+            } else {
+                0
+            };
+            if buffer_delay_length > 0 && bit_reader.read_bool()? {
+                // decoder_model_present_for_this_op
+                bit_reader.skip(buffer_delay_length.into())?; // decoder_buffer_delay
+                bit_reader.skip(buffer_delay_length.into())?; // encoder_buffer_delay
+                bit_reader.skip(1)?; // low_delay_mode_flag
+            }
+            if initial_display_delay_present_flag && bit_reader.read_bool()? {
+                // initial_display_delay_present_for_this_op
+                bit_reader.skip(4)?; // initial_display_delay_minus_1
+            }
+            operating_points.push(AV1OperatingPoint {
+                idc,
+                seq_level_idx,
+                seq_tier,
+            })?;
+        }
+    }
+
+    let frame_width_bits = bit_reader.read_u8(4)? + 1;
+    let frame_height_bits = bit_reader.read_u8(4)? + 1;
+    let max_frame_width = bit_reader.read_u32(frame_width_bits)? + 1;
+    let max_frame_height = bit_reader.read_u32(frame_height_bits)? + 1;
+    if !reduced_still_picture_header && bit_reader.read_bool()? {
+        // frame_id_numbers_present_flag
+        bit_reader.skip(4)?; // delta_frame_id_length_minus_2
+        bit_reader.skip(3)?; // additional_frame_id_length_minus_1
+    }
+    bit_reader.skip(1)?; // use_128x128_superblock
+    bit_reader.skip(1)?; // enable_filter_intra
+    bit_reader.skip(1)?; // enable_intra_edge_filter
+    if !reduced_still_picture_header {
+        bit_reader.skip(1)?; // enable_interintra_compound
+        bit_reader.skip(1)?; // enable_masked_compound
+        bit_reader.skip(1)?; // enable_warped_motion
+        bit_reader.skip(1)?; // enable_dual_filter
+        let enable_order_hint = bit_reader.read_bool()?;
+        if enable_order_hint {
+            bit_reader.skip(1)?; // enable_jnt_comp
+            bit_reader.skip(1)?; // enable_ref_frame_mvs
+        }
+        let seq_force_screen_content_tools = if bit_reader.read_bool()? {
+            // seq_choose_screen_content_tools
+            true
+        } else {
+            bit_reader.read_bool()?
+        };
+        if seq_force_screen_content_tools && !bit_reader.read_bool()? {
+            // seq_choose_integer_mv
+            bit_reader.skip(1)?; // seq_force_integer_mv
+        }
+        if enable_order_hint {
+            bit_reader.skip(3)?; // order_hint_bits_minus_1
+        }
+    }
+    bit_reader.skip(1)?; // enable_superres
+    bit_reader.skip(1)?; // enable_cdef
+    bit_reader.skip(1)?; // enable_restoration
+    let color_config = read_av1_color_config(bit_reader, seq_profile)?;
+    let film_grain_params_present = bit_reader.read_bool()?;
+
+    Ok(AV1SequenceHeader {
+        seq_profile,
+        still_picture,
+        reduced_still_picture_header,
+        timing_info,
+        operating_points,
+        max_frame_width,
+        max_frame_height,
+        color_config,
+        film_grain_params_present,
+    })
+}
+
+/// See [AV1 § 5.5.2](https://aomediacodec.github.io/av1-spec/#color-config-syntax)
+fn read_av1_color_config(bit_reader: &mut BitReader, seq_profile: u8) -> Result<AV1ColorConfig> {
+    // See AV1 § 6.4.2
+    const CP_BT_709: u8 = 1;
+    const CP_UNSPECIFIED: u8 = 2;
+    const TC_UNSPECIFIED: u8 = 2;
+    const TC_SRGB: u8 = 13;
+    const MC_IDENTITY: u8 = 0;
+    const MC_UNSPECIFIED: u8 = 2;
+
+    let high_bitdepth = bit_reader.read_bool()?;
+    let bit_depth = match (seq_profile, high_bitdepth) {
+        (2, true) if bit_reader.read_bool()? => 12, // twelve_bit
+        (_, true) => 10,
+        (_, false) => 8,
+    };
+    let mono_chrome = seq_profile != 1 && bit_reader.read_bool()?;
+    let color_description_present = bit_reader.read_bool()?;
+    let (color_primaries, transfer_characteristics, matrix_coefficients) =
+        if color_description_present {
+            (
+                bit_reader.read_u8(8)?,
+                bit_reader.read_u8(8)?,
+                bit_reader.read_u8(8)?,
+            )
+        } else {
+            (CP_UNSPECIFIED, TC_UNSPECIFIED, MC_UNSPECIFIED)
+        };
+
+    let mut color_config = AV1ColorConfig {
+        bit_depth,
+        mono_chrome,
+        color_description_present,
+        color_primaries,
+        transfer_characteristics,
+        matrix_coefficients,
+        full_range: true,
+        subsampling_x: true,
+        subsampling_y: true,
+        chroma_sample_position: 0,
+        separate_uv_delta_q: false,
+    };
+    if mono_chrome {
+        color_config.full_range = bit_reader.read_bool()?;
+        return Ok(color_config);
+    }
+    if color_primaries == CP_BT_709
+        && transfer_characteristics == TC_SRGB
+        && matrix_coefficients == MC_IDENTITY
+    {
+        color_config.subsampling_x = false;
+        color_config.subsampling_y = false;
+    } else {
+        color_config.full_range = bit_reader.read_bool()?;
+        match seq_profile {
+            0 => {}
+            1 => {
+                color_config.subsampling_x = false;
+                color_config.subsampling_y = false;
+            }
+            _ if bit_depth == 12 => {
+                color_config.subsampling_x = bit_reader.read_bool()?;
+                color_config.subsampling_y =
+                    color_config.subsampling_x && bit_reader.read_bool()?;
+            }
+            _ => color_config.subsampling_y = false,
+        }
+        if color_config.subsampling_x && color_config.subsampling_y {
+            color_config.chroma_sample_position = bit_reader.read_u8(2)?;
+        }
+    }
+    color_config.separate_uv_delta_q = bit_reader.read_bool()?;
+    Ok(color_config)
+}
+
+/// Parse an AVCDecoderConfigurationRecord
+/// See ISO 14496-15:2019 § 5.3.2.1
+fn read_avcc<T: Read>(src: &mut BMFFBox<T>, strictness: ParseStrictness) -> Result<AVCConfigBox> {
+    // We want to store the raw config as well as the parsed one, since
+    // decoders take it as a binary blob; as with 'esds', it isn't an error
+    // for the blob not to be parsable unless parsing strictly.
+    let raw_config = src.read_into_try_vec()?;
+    match parse_avcc(&raw_config) {
+        Ok(avcc) => Ok(AVCConfigBox { raw_config, ..avcc }),
+        Err(e) if strictness != ParseStrictness::Strict => {
+            warn!("Failed to parse avcC: {e:?}");
+            Ok(AVCConfigBox {
+                raw_config,
+                ..Default::default()
+            })
+        }
+        Err(e) => Err(e),
+    }
+}
+
+fn parse_avcc(mut avcc: &[u8]) -> Result<AVCConfigBox> {
+    let configuration_version = avcc.read_u8()?;
+    if configuration_version != 1 {
+        return Err(Error::Unsupported("unsupported avcC configurationVersion"));
+    }
+    let profile_indication = avcc.read_u8()?;
+    let profile_compatibility = avcc.read_u8()?;
+    let level_indication = avcc.read_u8()?;
+    let nal_unit_length_size = (avcc.read_u8()? & 0x03) + 1;
+
+    let sps_count = avcc.read_u8()? & 0x1f;
+    let sequence_parameter_sets = read_parameter_set_nal_units(&mut avcc, sps_count.into())?;
+    let pps_count = avcc.read_u8()?;
+    let picture_parameter_sets = read_parameter_set_nal_units(&mut avcc, pps_count.into())?;
+
+    let mut sequence_parameter_set_extensions = TryVec::new();
+    if matches!(profile_indication, 100 | 110 | 122 | 144) && avcc.len() >= 4 {
+        // chroma_format, bit_depth_luma_minus8 and bit_depth_chroma_minus8,
+        // which are repeated in the SPS
+        skip(&mut avcc, 3)?;
+        let sps_ext_count = avcc.read_u8()?;
+        sequence_parameter_set_extensions =
+            read_parameter_set_nal_units(&mut avcc, sps_ext_count.into())?;
+    }
+
+    // The parameter sets are passed to the decoder as they are, so failing to
+    // parse the SPS isn't an error
+    let sps = match sequence_parameter_sets.first() {
+        Some(nal_unit) => match read_avc_sps(nal_unit) {
+            Ok(sps) => Some(sps),
+            Err(e) => {
+                warn!("Failed to parse avcC SPS: {e:?}");
+                None
+            }
+        },
+        None => None,
+    };
+
+    Ok(AVCConfigBox {
+        profile_indication,
+        profile_compatibility,
+        level_indication,
+        nal_unit_length_size,
+        sequence_parameter_sets,
+        picture_parameter_sets,
+        sequence_parameter_set_extensions,
+        sps,
+        parsed: true,
+        raw_config: TryVec::new(),
+    })
+}
+
+/// Read `count` NAL units, each preceded by its 16-bit length, as in the
+/// decoder configuration records of ISO 14496-15.
+fn read_parameter_set_nal_units(src: &mut &[u8], count: usize) -> Result<TryVec<TryVec<u8>>> {
+    let mut nal_units = TryVec::with_capacity(count)?;
+    for _ in 0..count {
+        let length = be_u16(src)?;
+        nal_units.push(read_buf(src, length.into())?)?;
+    }
+    Ok(nal_units)
+}
+
+/// Copy the raw byte sequence payload of a NAL unit without its header,
+/// removing the emulation_prevention_three_bytes.
+/// See ITU-T H.264 (08/2021) § 7.3.1
+fn nal_unit_rbsp(nal_unit: &[u8], header_size: usize) -> Result<TryVec<u8>> {
+    let payload = nal_unit.get(header_size..).ok_or(Error::UnexpectedEOF)?;
+    let mut rbsp = TryVec::with_capacity(payload.len())?;
+    let mut zero_count = 0;
+    for &byte in payload {
+        if zero_count >= 2 && byte == 0x03 {
+            zero_count = 0;
+            continue;
+        }
+        rbsp.push(byte)?;
+        zero_count = if byte == 0 { zero_count + 1 } else { 0 };
+    }
+    Ok(rbsp)
+}
+
+/// Read an unsigned Exp-Golomb-coded syntax element, ue(v)
+/// See ITU-T H.264 (08/2021) § 9.1
+fn read_ue(bit_reader: &mut BitReader) -> Result<u32> {
+    let mut leading_zero_bits = 0;
+    while !bit_reader.read_bool()? {
+        leading_zero_bits += 1;
+        if leading_zero_bits > 31 {
+            return Status::BitReaderError.into();
+        }
+    }
+    let suffix = bit_reader.read_u32(leading_zero_bits)?;
+    Ok((1 << leading_zero_bits) - 1 + suffix)
+}
+
+/// Read a signed Exp-Golomb-coded syntax element, se(v)
+/// See ITU-T H.264 (08/2021) § 9.1.1
+fn read_se(bit_reader: &mut BitReader) -> Result<i64> {
+    let code_num = i64::from(read_ue(bit_reader)?);
+    Ok(if code_num % 2 == 1 {
+        (code_num + 1) / 2
+    } else {
+        -(code_num / 2)
+    })
+}
+
+/// The sample aspect ratios of the aspect_ratio_idc values 1 to 16
+/// See ITU-T H.264 (08/2021) Table E-1
+const SAMPLE_ASPECT_RATIOS: [(u16, u16); 16] = [
+    (1, 1),
+    (12, 11),
+    (10, 11),
+    (16, 11),
+    (40, 33),
+    (24, 11),
+    (20, 11),
+    (32, 11),
+    (80, 33),
+    (18, 11),
+    (15, 11),
+    (64, 33),
+    (160, 99),
+    (4, 3),
+    (3, 2),
+    (2, 1),
+];
+
+/// The aspect_ratio_idc of a SAR given explicitly by sar_width and sar_height
+const EXTENDED_SAR: u8 = 255;
+
+/// The fields of the VUI parameters of H.264 and H.265 we're interested in
+#[derive(Default)]
+struct VuiParameters {
+    sample_aspect_ratio: Option<(u16, u16)>,
+    colour_description: Option<NclxColourInformation>,
+}
+
+/// Parse the sample aspect ratio and colour description from the start of
+/// the VUI parameters, which H.264 and H.265 share.
+/// See ITU-T H.264 (08/2021) § E.1.1 and ITU-T H.265 (08/2021) § E.2.1
+fn read_vui_parameters(bit_reader: &mut BitReader) -> Result<VuiParameters> {
+    let mut sample_aspect_ratio = None;
+    if bit_reader.read_bool()? {
+        // aspect_ratio_info_present_flag
+        let aspect_ratio_idc = bit_reader.read_u8(8)?;
+        sample_aspect_ratio = if aspect_ratio_idc == EXTENDED_SAR {
+            Some((bit_reader.read_u16(16)?, bit_reader.read_u16(16)?))
+        } else {
+            aspect_ratio_idc
+                .checked_sub(1)
+                .and_then(|index| SAMPLE_ASPECT_RATIOS.get(usize::from(index)))
+                .copied()
+        };
+    }
+
+    if bit_reader.read_bool()? {
+        // overscan_info_present_flag
+        bit_reader.skip(1)?; // overscan_appropriate_flag
+    }
+
+    let mut colour_description = None;
+    if bit_reader.read_bool()? {
+        // video_signal_type_present_flag
+        bit_reader.skip(3)?; // video_format
+        let full_range_flag = bit_reader.read_bool()?;
+        if bit_reader.read_bool()? {
+            // colour_description_present_flag
+            colour_description = Some(NclxColourInformation {
+                colour_primaries: bit_reader.read_u8(8)?,
+                transfer_characteristics: bit_reader.read_u8(8)?,
+                matrix_coefficients: bit_reader.read_u8(8)?,
+                full_range_flag,
+            });
+        }
+    }
+
+    Ok(VuiParameters {
+        sample_aspect_ratio,
+        colour_description,
+    })
+}
+
+/// Skip a scaling_list() of `size` coefficients
+/// See ITU-T H.264 (08/2021) § 7.3.2.1.1.1
+fn skip_avc_scaling_list(bit_reader: &mut BitReader, size: usize) -> Result<()> {
+    let mut last_scale = 8;
+    let mut next_scale = 8;
+    for _ in 0..size {
+        if next_scale != 0 {
+            let delta_scale = read_se(bit_reader)?;
+            next_scale = (last_scale + delta_scale + 256) % 256;
+        }
+        if next_scale != 0 {
+            last_scale = next_scale;
+        }
+    }
+    Ok(())
+}
+
+/// Parse an H.264 sequence parameter set NAL unit up to the colour
+/// description of its VUI parameters.
+/// See ITU-T H.264 (08/2021) § 7.3.2.1.1
+fn read_avc_sps(nal_unit: &[u8]) -> Result<AVCSequenceParameterSet> {
+    const NAL_UNIT_TYPE_SPS: u8 = 7;
+    match nal_unit.first() {
+        Some(header) if header & 0x1f == NAL_UNIT_TYPE_SPS => {}
+        _ => return Status::SpsBadNalUnitType.into(),
+    }
+    let rbsp = nal_unit_rbsp(nal_unit, 1)?;
+    let bit_reader = &mut BitReader::new(&rbsp);
+
+    let profile_idc = bit_reader.read_u8(8)?;
+    let constraint_set_flags = bit_reader.read_u8(8)? & 0xfc;
+    let level_idc = bit_reader.read_u8(8)?;
+    read_ue(bit_reader)?; // seq_parameter_set_id
+
+    let mut chroma_format_idc = 1;
+    let mut separate_colour_plane_flag = false;
+    let mut bit_depth_luma = 8;
+    let mut bit_depth_chroma = 8;
+    if matches!(
+        profile_idc,
+        100 | 110 | 122 | 244 | 44 | 83 | 86 | 118 | 128 | 138 | 139 | 134 | 135
+    ) {
+        chroma_format_idc = read_ue(bit_reader)?;
+        if chroma_format_idc > 3 {
+            return Status::SpsBadChromaFormat.into();
+        }
+        if chroma_format_idc == 3 {
+            separate_colour_plane_flag = bit_reader.read_bool()?;
+        }
+        bit_depth_luma = read_ue(bit_reader)?.saturating_add(8);
+        bit_depth_chroma = read_ue(bit_reader)?.saturating_add(8);
+        bit_reader.skip(1)?; // qpprime_y_zero_transform_bypass_flag
+        if bit_reader.read_bool()? {
+            // seq_scaling_matrix_present_flag
+            let scaling_list_count = if chroma_format_idc == 3 { 12 } else { 8 };
+            for i in 0..scaling_list_count {
+                if bit_reader.read_bool()? {
+                    skip_avc_scaling_list(bit_reader, if i < 6 { 16 } else { 64 })?;
+                }
+            }
+        }
+    }
+    if bit_depth_luma > 14 || bit_depth_chroma > 14 {
+        return Status::SpsBadBitDepth.into();
+    }
+
+    read_ue(bit_reader)?; // log2_max_frame_num_minus4
+    match read_ue(bit_reader)? {
+        // pic_order_cnt_type
+        0 => {
+            read_ue(bit_reader)?; // log2_max_pic_order_cnt_lsb_minus4
+        }
+        1 => {
+            bit_reader.skip(1)?; // delta_pic_order_always_zero_flag
+            read_se(bit_reader)?; // offset_for_non_ref_pic
+            read_se(bit_reader)?; // offset_for_top_to_bottom_field
+            let num_ref_frames_in_pic_order_cnt_cycle = read_ue(bit_reader)?;
+            for _ in 0..num_ref_frames_in_pic_order_cnt_cycle {
+                read_se(bit_reader)?; // offset_for_ref_frame
+            }
+        }
+        _ => {}
+    }
+    read_ue(bit_reader)?; // max_num_ref_frames
+    bit_reader.skip(1)?; // gaps_in_frame_num_value_allowed_flag
+
+    let pic_width_in_mbs = u64::from(read_ue(bit_reader)?) + 1;
+    let pic_height_in_map_units = u64::from(read_ue(bit_reader)?) + 1;
+    let frame_mbs_only_flag = bit_reader.read_bool()?;
+    if !frame_mbs_only_flag {
+        bit_reader.skip(1)?; // mb_adaptive_frame_field_flag
+    }
+    bit_reader.skip(1)?; // direct_8x8_inference_flag
+    let frame_height_factor = if frame_mbs_only_flag { 1 } else { 2 };
+    let coded_width: u32 = (pic_width_in_mbs * 16).try_into()?;
+    let coded_height: u32 = (frame_height_factor * pic_height_in_map_units * 16).try_into()?;
+
+    let mut crop = CropRectangle {
+        x: 0,
+        y: 0,
+        width: coded_width,
+        height: coded_height,
+    };
+    if bit_reader.read_bool()? {
+        // frame_cropping_flag
+        // See ITU-T H.264 (08/2021) equations 7-19 to 7-22
+        let (crop_unit_x, crop_unit_y) = if separate_colour_plane_flag || chroma_format_idc == 0 {
+            (1, frame_height_factor)
+        } else {
+            let sub_width_c = if chroma_format_idc == 3 { 1 } else { 2 };
+            let sub_height_c = if chroma_format_idc == 1 { 2 } else { 1 };
+            (sub_width_c, sub_height_c * frame_height_factor)
+        };
+        let left = crop_unit_x * u64::from(read_ue(bit_reader)?);
+        let right = crop_unit_x * u64::from(read_ue(bit_reader)?);
+        let top = crop_unit_y * u64::from(read_ue(bit_reader)?);
+        let bottom = crop_unit_y * u64::from(read_ue(bit_reader)?);
+        let width = u64::from(coded_width)
+            .checked_sub(left + right)
+            .filter(|&width| width > 0);
+        let height = u64::from(coded_height)
+            .checked_sub(top + bottom)
+            .filter(|&height| height > 0);
+        match (width, height) {
+            (Some(width), Some(height)) => {
+                crop = CropRectangle {
+                    x: left.try_into()?,
+                    y: top.try_into()?,
+                    width: width.try_into()?,
+                    height: height.try_into()?,
+                };
+            }
+            _ => return Status::SpsBadCrop.into(),
+        }
+    }
+
+    let vui = if bit_reader.read_bool()? {
+        // vui_parameters_present_flag
+        read_vui_parameters(bit_reader)?
+    } else {
+        VuiParameters::default()
+    };
+
+    Ok(AVCSequenceParameterSet {
+        profile_idc,
+        constraint_set_flags,
+        level_idc,
+        chroma_format_idc: chroma_format_idc.try_into()?,
+        bit_depth_luma: bit_depth_luma.try_into()?,
+        bit_depth_chroma: bit_depth_chroma.try_into()?,
+        coded_width,
+        coded_height,
+        crop,
+        sample_aspect_ratio: vui.sample_aspect_ratio,
+        colour_description: vui.colour_description,
+    })
+}
+
+/// Parse an HEVCDecoderConfigurationRecord
+/// See ISO 14496-15:2019 § 8.3.3.1
+fn read_hvcc<T: Read>(src: &mut BMFFBox<T>, strictness: ParseStrictness) -> Result<HEVCConfigBox> {
+    // As for read_avcc, we keep the raw config for decoders, and it's only
+    // an error for it not to be parsable if parsing strictly
+    let raw_config = src.read_into_try_vec()?;
+    match parse_hvcc(&raw_config) {
+        Ok(hvcc) => Ok(HEVCConfigBox { raw_config, ..hvcc }),
+        Err(e) if strictness != ParseStrictness::Strict => {
+            warn!("Failed to parse hvcC: {e:?}");
+            Ok(HEVCConfigBox {
+                raw_config,
+                ..Default::default()
+            })
+        }
+        Err(e) => Err(e),
+    }
+}
+
+fn parse_hvcc(mut hvcc: &[u8]) -> Result<HEVCConfigBox> {
+    let configuration_version = hvcc.read_u8()?;
+    if configuration_version != 1 {
+        return Err(Error::Unsupported("unsupported hvcC configurationVersion"));
+    }
+    let profile_byte = hvcc.read_u8()?;
+    let general_profile_space = profile_byte >> 6;
+    let general_tier_flag = profile_byte & 0x20 != 0;
+    let general_profile_idc = profile_byte & 0x1f;
+    let general_profile_compatibility_flags = be_u32(&mut hvcc)?;
+    let general_constraint_indicator_flags =
+        u64::from(be_u32(&mut hvcc)?) << 16 | u64::from(be_u16(&mut hvcc)?);
+    let general_level_idc = hvcc.read_u8()?;
+    // min_spatial_segmentation_idc and parallelismType
+    skip(&mut hvcc, 3)?;
+    let chroma_format_idc = hvcc.read_u8()? & 0x03;
+    let bit_depth_luma = (hvcc.read_u8()? & 0x07) + 8;
+    let bit_depth_chroma = (hvcc.read_u8()? & 0x07) + 8;
+    // avgFrameRate
+    skip(&mut hvcc, 2)?;
+    let nal_unit_length_size = (hvcc.read_u8()? & 0x03) + 1;
+
+    let array_count = hvcc.read_u8()?;
+    let mut nal_unit_arrays = TryVec::with_capacity(array_count.into())?;
+    for _ in 0..array_count {
+        let array_byte = hvcc.read_u8()?;
+        let nal_unit_count = be_u16(&mut hvcc)?;
+        nal_unit_arrays.push(HEVCNalUnitArray {
+            array_completeness: array_byte & 0x80 != 0,
+            nal_unit_type: array_byte & 0x3f,
+            nal_units: read_parameter_set_nal_units(&mut hvcc, nal_unit_count.into())?,
         })?;
     }
 
-    // Padding could be added in some contents.
-    skip_box_remain(src)?;
+    let mut hvcc = HEVCConfigBox {
+        general_profile_space,
+        general_tier_flag,
+        general_profile_idc,
+        general_profile_compatibility_flags,
+        general_constraint_indicator_flags,
+        general_level_idc,
+        chroma_format_idc,
+        bit_depth_luma,
+        bit_depth_chroma,
+        nal_unit_length_size,
+        nal_unit_arrays,
+        sps: None,
+        parsed: true,
+        raw_config: TryVec::new(),
+    };
 
-    Ok(TimeToSampleBox { samples })
+    // As for avcC, failing to parse the SPS isn't an error
+    let sps = hvcc
+        .nal_units(HEVCConfigBox::NAL_UNIT_TYPE_SPS)
+        .next()
+        .map(read_hevc_sps);
+    match sps {
+        Some(Ok(sps)) => hvcc.sps = Some(sps),
+        Some(Err(e)) => warn!("Failed to parse hvcC SPS: {e:?}"),
+        None => {}
+    }
+
+    Ok(hvcc)
 }
 
-/// Parse a VPx Config Box.
-fn read_vpcc<T: Read>(src: &mut BMFFBox<T>) -> Result<VPxConfigBox> {
-    let (version, _) = read_fullbox_extra(src)?;
-    let supported_versions = [0, 1];
-    if !supported_versions.contains(&version) {
-        return Err(Error::Unsupported("unknown vpcC version"));
+/// Skip the profile_tier_level() of an H.265 parameter set
+/// See ITU-T H.265 (08/2021) § 7.3.3
+fn skip_hevc_profile_tier_level(
+    bit_reader: &mut BitReader,
+    max_sub_layers_minus1: u8,
+) -> Result<()> {
+    // The general profile, tier and level
+    bit_reader.skip(96)?;
+    let mut sub_layer_flags = [(false, false); 8];
+    for flags in sub_layer_flags
+        .iter_mut()
+        .take(max_sub_layers_minus1.into())
+    {
+        // sub_layer_profile_present_flag and sub_layer_level_present_flag
+        *flags = (bit_reader.read_bool()?, bit_reader.read_bool()?);
     }
+    if max_sub_layers_minus1 > 0 {
+        // reserved_zero_2bits
+        bit_reader.skip(2 * (8 - u64::from(max_sub_layers_minus1)))?;
+    }
+    for &(profile_present, level_present) in
+        sub_layer_flags.iter().take(max_sub_layers_minus1.into())
+    {
+        if profile_present {
+            bit_reader.skip(88)?;
+        }
+        if level_present {
+            bit_reader.skip(8)?;
+        }
+    }
+    Ok(())
+}
 
-    let profile = src.read_u8()?;
-    let level = src.read_u8()?;
-    let (
-        bit_depth,
-        colour_primaries,
-        chroma_subsampling,
-        transfer_characteristics,
-        matrix_coefficients,
-        video_full_range_flag,
-    ) = if version == 0 {
-        let (bit_depth, colour_primaries) = {
-            let byte = src.read_u8()?;
-            ((byte >> 4) & 0x0f, byte & 0x0f)
-        };
-        // Note, transfer_characteristics was known as transfer_function in v0
-        let (chroma_subsampling, transfer_characteristics, video_full_range_flag) = {
-            let byte = src.read_u8()?;
-            ((byte >> 4) & 0x0f, (byte >> 1) & 0x07, (byte & 1) == 1)
-        };
-        (
-            bit_depth,
-            colour_primaries,
-            chroma_subsampling,
-            transfer_characteristics,
-            None,
-            video_full_range_flag,
-        )
-    } else {
-        let (bit_depth, chroma_subsampling, video_full_range_flag) = {
-            let byte = src.read_u8()?;
-            ((byte >> 4) & 0x0f, (byte >> 1) & 0x07, (byte & 1) == 1)
-        };
-        let colour_primaries = src.read_u8()?;
-        let transfer_characteristics = src.read_u8()?;
-        let matrix_coefficients = src.read_u8()?;
-
-        (
-            bit_depth,
-            colour_primaries,
-            chroma_subsampling,
-            transfer_characteristics,
-            Some(matrix_coefficients),
-            video_full_range_flag,
-        )
-    };
+/// Skip the scaling_list_data() of an H.265 SPS
+/// See ITU-T H.265 (08/2021) § 7.3.4
+fn skip_hevc_scaling_list_data(bit_reader: &mut BitReader) -> Result<()> {
+    for size_id in 0..4 {
+        let matrix_id_step = if size_id == 3 { 3 } else { 1 };
+        for _ in (0..6).step_by(matrix_id_step) {
+            if !bit_reader.read_bool()? {
+                // scaling_list_pred_mode_flag
+                read_ue(bit_reader)?; // scaling_list_pred_matrix_id_delta
+            } else {
+                let coef_num = std::cmp::min(64, 1 << (4 + (size_id << 1)));
+                if size_id > 1 {
+                    read_se(bit_reader)?; // scaling_list_dc_coef_minus8
+                }
+                for _ in 0..coef_num {
+                    read_se(bit_reader)?; // scaling_list_delta_coef
+                }
+            }
+        }
+    }
+    Ok(())
+}
 
-    let codec_init_size = be_u16(src)?;
-    let codec_init = read_buf(src, codec_init_size.into())?;
+/// Skip the st_ref_pic_set(`index`) of an H.265 SPS, given the NumDeltaPocs
+/// of the preceding sets, returning its own NumDeltaPocs.
+/// See ITU-T H.265 (08/2021) § 7.3.7 and § 7.4.8
+fn skip_hevc_st_ref_pic_set(bit_reader: &mut BitReader, num_delta_pocs: &[u32]) -> Result<u32> {
+    // inter_ref_pic_set_prediction_flag
+    if !num_delta_pocs.is_empty() && bit_reader.read_bool()? {
+        bit_reader.skip(1)?; // delta_rps_sign
+        read_ue(bit_reader)?; // abs_delta_rps_minus1
+
+        // In an SPS, the reference set is always the preceding one
+        let ref_num_delta_pocs = num_delta_pocs[num_delta_pocs.len() - 1];
+        let mut count = 0;
+        for _ in 0..=ref_num_delta_pocs {
+            let used_by_curr_pic_flag = bit_reader.read_bool()?;
+            let use_delta_flag = used_by_curr_pic_flag || bit_reader.read_bool()?;
+            if use_delta_flag {
+                count += 1;
+            }
+        }
+        return Ok(count);
+    }
 
-    // TODO(rillian): validate field value ranges.
-    Ok(VPxConfigBox {
-        profile,
-        level,
-        bit_depth,
-        colour_primaries,
-        chroma_subsampling,
-        transfer_characteristics,
-        matrix_coefficients,
-        video_full_range_flag,
-        codec_init,
-    })
+    let num_negative_pics = read_ue(bit_reader)?;
+    let num_positive_pics = read_ue(bit_reader)?;
+    let count = num_negative_pics
+        .checked_add(num_positive_pics)
+        .filter(|&count| count <= 32)
+        .ok_or(Error::InvalidData(Status::SpsBadRefPicSets))?;
+    for _ in 0..count {
+        read_ue(bit_reader)?; // delta_poc_s0_minus1 or delta_poc_s1_minus1
+        bit_reader.skip(1)?; // used_by_curr_pic_s0_flag or used_by_curr_pic_s1_flag
+    }
+    Ok(count)
 }
 
-/// See [AV1-ISOBMFF § 2.3.3](https://aomediacodec.github.io/av1-isobmff/#av1codecconfigurationbox-syntax)
-fn read_av1c<T: Read>(src: &mut BMFFBox<T>) -> Result<AV1ConfigBox> {
-    // We want to store the raw config as well as a structured (parsed) config, so create a copy of
-    // the raw config so we have it later, and then parse the structured data from that.
-    let raw_config = src.read_into_try_vec()?;
-    let mut raw_config_slice = raw_config.as_slice();
-    let marker_byte = raw_config_slice.read_u8()?;
-    if marker_byte & 0x80 != 0x80 {
-        return Err(Error::Unsupported("missing av1C marker bit"));
+/// Parse an H.265 sequence parameter set NAL unit up to the colour
+/// description of its VUI parameters.
+/// See ITU-T H.265 (08/2021) § 7.3.2.2.1
+fn read_hevc_sps(nal_unit: &[u8]) -> Result<HEVCSequenceParameterSet> {
+    match nal_unit.first() {
+        Some(header) if (header >> 1) & 0x3f == HEVCConfigBox::NAL_UNIT_TYPE_SPS => {}
+        _ => return Status::SpsBadNalUnitType.into(),
+    }
+    let rbsp = nal_unit_rbsp(nal_unit, 2)?;
+    let bit_reader = &mut BitReader::new(&rbsp);
+
+    bit_reader.skip(4)?; // sps_video_parameter_set_id
+    let max_sub_layers_minus1 = bit_reader.read_u8(3)?;
+    bit_reader.skip(1)?; // sps_temporal_id_nesting_flag
+    skip_hevc_profile_tier_level(bit_reader, max_sub_layers_minus1)?;
+    read_ue(bit_reader)?; // sps_seq_parameter_set_id
+
+    let chroma_format_idc = read_ue(bit_reader)?;
+    if chroma_format_idc > 3 {
+        return Status::SpsBadChromaFormat.into();
+    }
+    let separate_colour_plane_flag = chroma_format_idc == 3 && bit_reader.read_bool()?;
+    let width = read_ue(bit_reader)?;
+    let height = read_ue(bit_reader)?;
+    let mut conformance_window = CropRectangle {
+        x: 0,
+        y: 0,
+        width,
+        height,
+    };
+    if bit_reader.read_bool()? {
+        // conformance_window_flag
+        // See ITU-T H.265 (08/2021) Table 6-1
+        let (sub_width_c, sub_height_c) = match chroma_format_idc {
+            _ if separate_colour_plane_flag => (1, 1),
+            1 => (2, 2),
+            2 => (2, 1),
+            _ => (1, 1),
+        };
+        let left = sub_width_c * u64::from(read_ue(bit_reader)?);
+        let right = sub_width_c * u64::from(read_ue(bit_reader)?);
+        let top = sub_height_c * u64::from(read_ue(bit_reader)?);
+        let bottom = sub_height_c * u64::from(read_ue(bit_reader)?);
+        let cropped_width = u64::from(width)
+            .checked_sub(left + right)
+            .filter(|&width| width > 0);
+        let cropped_height = u64::from(height)
+            .checked_sub(top + bottom)
+            .filter(|&height| height > 0);
+        match (cropped_width, cropped_height) {
+            (Some(cropped_width), Some(cropped_height)) => {
+                conformance_window = CropRectangle {
+                    x: left.try_into()?,
+                    y: top.try_into()?,
+                    width: cropped_width.try_into()?,
+                    height: cropped_height.try_into()?,
+                };
+            }
+            _ => return Status::SpsBadCrop.into(),
+        }
     }
-    if marker_byte & 0x7f != 0x01 {
-        return Err(Error::Unsupported("missing av1C marker bit"));
+    let bit_depth_luma = read_ue(bit_reader)?.saturating_add(8);
+    let bit_depth_chroma = read_ue(bit_reader)?.saturating_add(8);
+    if bit_depth_luma > 16 || bit_depth_chroma > 16 {
+        return Status::SpsBadBitDepth.into();
     }
-    let profile_byte = raw_config_slice.read_u8()?;
-    let profile = (profile_byte & 0xe0) >> 5;
-    let level = profile_byte & 0x1f;
-    let flags_byte = raw_config_slice.read_u8()?;
-    let tier = (flags_byte & 0x80) >> 7;
-    let bit_depth = match flags_byte & 0x60 {
-        0x60 => 12,
-        0x40 => 10,
-        _ => 8,
+
+    let log2_max_pic_order_cnt_lsb = read_ue(bit_reader)?.saturating_add(4);
+    let sub_layer_ordering_info_present_flag = bit_reader.read_bool()?;
+    let sub_layer_ordering_info_count = if sub_layer_ordering_info_present_flag {
+        max_sub_layers_minus1 + 1
+    } else {
+        1
     };
-    let monochrome = flags_byte & 0x10 == 0x10;
-    let chroma_subsampling_x = (flags_byte & 0x08) >> 3;
-    let chroma_subsampling_y = (flags_byte & 0x04) >> 2;
-    let chroma_sample_position = flags_byte & 0x03;
-    let delay_byte = raw_config_slice.read_u8()?;
-    let initial_presentation_delay_present = (delay_byte & 0x10) == 0x10;
-    let initial_presentation_delay_minus_one = if initial_presentation_delay_present {
-        delay_byte & 0x0f
+    for _ in 0..sub_layer_ordering_info_count {
+        read_ue(bit_reader)?; // sps_max_dec_pic_buffering_minus1
+        read_ue(bit_reader)?; // sps_max_num_reorder_pics
+        read_ue(bit_reader)?; // sps_max_latency_increase_plus1
+    }
+    read_ue(bit_reader)?; // log2_min_luma_coding_block_size_minus3
+    read_ue(bit_reader)?; // log2_diff_max_min_luma_coding_block_size
+    read_ue(bit_reader)?; // log2_min_luma_transform_block_size_minus2
+    read_ue(bit_reader)?; // log2_diff_max_min_luma_transform_block_size
+    read_ue(bit_reader)?; // max_transform_hierarchy_depth_inter
+    read_ue(bit_reader)?; // max_transform_hierarchy_depth_intra
+    if bit_reader.read_bool()? && bit_reader.read_bool()? {
+        // scaling_list_enabled_flag and sps_scaling_list_data_present_flag
+        skip_hevc_scaling_list_data(bit_reader)?;
+    }
+    bit_reader.skip(1)?; // amp_enabled_flag
+    bit_reader.skip(1)?; // sample_adaptive_offset_enabled_flag
+    if bit_reader.read_bool()? {
+        // pcm_enabled_flag
+        bit_reader.skip(8)?; // pcm_sample_bit_depth_luma_minus1 and chroma_minus1
+        read_ue(bit_reader)?; // log2_min_pcm_luma_coding_block_size_minus3
+        read_ue(bit_reader)?; // log2_diff_max_min_pcm_luma_coding_block_size
+        bit_reader.skip(1)?; // pcm_loop_filter_disabled_flag
+    }
+
+    let num_short_term_ref_pic_sets = read_ue(bit_reader)?;
+    if num_short_term_ref_pic_sets > 64 {
+        return Status::SpsBadRefPicSets.into();
+    }
+    let mut num_delta_pocs = TryVec::with_capacity(num_short_term_ref_pic_sets.to_usize())?;
+    for _ in 0..num_short_term_ref_pic_sets {
+        let count = skip_hevc_st_ref_pic_set(bit_reader, &num_delta_pocs)?;
+        num_delta_pocs.push(count)?;
+    }
+    if bit_reader.read_bool()? {
+        // long_term_ref_pics_present_flag
+        let num_long_term_ref_pics_sps = read_ue(bit_reader)?;
+        for _ in 0..num_long_term_ref_pics_sps {
+            bit_reader.skip(log2_max_pic_order_cnt_lsb.into())?; // lt_ref_pic_poc_lsb_sps
+            bit_reader.skip(1)?; // used_by_curr_pic_lt_sps_flag
+        }
+    }
+    bit_reader.skip(1)?; // sps_temporal_mvp_enabled_flag
+    bit_reader.skip(1)?; // strong_intra_smoothing_enabled_flag
+
+    let vui = if bit_reader.read_bool()? {
+        // vui_parameters_present_flag
+        read_vui_parameters(bit_reader)?
     } else {
-        0
+        VuiParameters::default()
     };
 
-    Ok(AV1ConfigBox {
-        profile,
-        level,
-        tier,
-        bit_depth,
-        monochrome,
-        chroma_subsampling_x,
-        chroma_subsampling_y,
-        chroma_sample_position,
-        initial_presentation_delay_present,
-        initial_presentation_delay_minus_one,
-        raw_config,
+    Ok(HEVCSequenceParameterSet {
+        chroma_format_idc: chroma_format_idc.try_into()?,
+        bit_depth_luma: bit_depth_luma.try_into()?,
+        bit_depth_chroma: bit_depth_chroma.try_into()?,
+        width,
+        height,
+        conformance_window,
+        sample_aspect_ratio: vui.sample_aspect_ratio,
+        colour_description: vui.colour_description,
     })
 }
 
@@ -5252,6 +10042,10 @@ fn read_ds_descriptor(
         5 | 29 => Some(5),
         _ => None,
     };
+    let sbr_audio_object_type = match audio_object_type {
+        5 | 29 => Some(audio_object_type),
+        _ => None,
+    };
 
     if audio_object_type == 5 || audio_object_type == 29 {
         // We have an explicit signaling for BSAC extension, should the decoder
@@ -5328,6 +10122,7 @@ fn read_ds_descriptor(
             // omits the GASpecificConfig tail).
             esds.audio_object_type = Some(audio_object_type);
             esds.extended_audio_object_type = extended_audio_object_type;
+            esds.sbr_audio_object_type = sbr_audio_object_type;
             esds.audio_sample_rate = Some(sample_frequency_value);
             if let Some(cc) = channel_count_from_config {
                 esds.audio_channel_count = Some(cc);
@@ -5405,6 +10200,7 @@ fn read_dc_descriptor(
 ) -> Result<()> {
     let des = &mut Cursor::new(data);
     let object_profile = des.read_u8()?;
+    esds.object_type_indication = Some(object_profile);
 
     #[cfg(feature = "mp4v")]
     {
@@ -5683,14 +10479,19 @@ fn read_video_sample_entry<T: Read>(
 ) -> Result<SampleEntry> {
     let name = src.get_header().name;
     let codec_type = match name {
-        BoxType::AVCSampleEntry | BoxType::AVC3SampleEntry => CodecType::H264,
+        BoxType::AVCSampleEntry | BoxType::AVC3SampleEntry | BoxType::DVA1SampleEntry => {
+            CodecType::H264
+        }
         BoxType::MP4VideoSampleEntry => CodecType::MP4V,
         BoxType::VP8SampleEntry => CodecType::VP8,
         BoxType::VP9SampleEntry => CodecType::VP9,
-        BoxType::AV1SampleEntry => CodecType::AV1,
+        BoxType::AV1SampleEntry | BoxType::DAV1SampleEntry => CodecType::AV1,
         BoxType::ProtectedVisualSampleEntry => CodecType::EncryptedVideo,
         BoxType::H263SampleEntry => CodecType::H263,
-        BoxType::HEV1SampleEntry | BoxType::HVC1SampleEntry => CodecType::HEVC,
+        BoxType::HEV1SampleEntry
+        | BoxType::HVC1SampleEntry
+        | BoxType::DVH1SampleEntry
+        | BoxType::DVHESampleEntry => CodecType::HEVC,
         _ => {
             debug!("Unsupported video codec, box {name:?} found");
             CodecType::Unknown
@@ -5711,13 +10512,14 @@ fn read_video_sample_entry<T: Read>(
     // Skip uninteresting fields.
     skip(src, 50)?;
 
-    // Skip clap/pasp/etc. for now.
     let mut codec_specific = None;
     let mut pixel_aspect_ratio = None;
+    let mut clean_aperture = None;
     let mut colour_info = None;
     let mut colr_types_seen = TryVec::<FourCC>::new();
     let mut hdr_mastering_display = None;
     let mut hdr_content_light_level = None;
+    let mut dolby_vision_config = None;
     let mut protection_info = TryVec::new();
     let mut iter = src.box_iter();
     while let Some(mut b) = iter.next_box()? {
@@ -5725,19 +10527,14 @@ fn read_video_sample_entry<T: Read>(
             BoxType::AVCConfigurationBox => {
                 if (name != BoxType::AVCSampleEntry
                     && name != BoxType::AVC3SampleEntry
+                    && name != BoxType::DVA1SampleEntry
                     && name != BoxType::ProtectedVisualSampleEntry)
                     || codec_specific.is_some()
                 {
                     return Status::StsdBadVideoSampleEntry.into();
                 }
-                let avcc_size = b
-                    .head
-                    .size
-                    .checked_sub(b.head.offset)
-                    .expect("offset invalid");
-                let avcc = read_buf(&mut b.content, avcc_size)?;
+                let avcc = read_avcc(&mut b, strictness)?;
                 debug!("{avcc:?} (avcc)");
-                // TODO(kinetik): Parse avcC box?  For now we just stash the data.
                 codec_specific = Some(VideoCodecSpecific::AVCConfig(avcc));
             }
             BoxType::H263SpecificBox => {
@@ -5767,7 +10564,10 @@ fn read_video_sample_entry<T: Read>(
                 codec_specific = Some(VideoCodecSpecific::VPxConfig(vpcc));
             }
             BoxType::AV1CodecConfigurationBox => {
-                if name != BoxType::AV1SampleEntry && name != BoxType::ProtectedVisualSampleEntry {
+                if name != BoxType::AV1SampleEntry
+                    && name != BoxType::DAV1SampleEntry
+                    && name != BoxType::ProtectedVisualSampleEntry
+                {
                     return Status::StsdBadVideoSampleEntry.into();
                 }
                 let av1c = read_av1c(&mut b)?;
@@ -5810,17 +10610,14 @@ fn read_video_sample_entry<T: Read>(
             BoxType::HEVCConfigurationBox => {
                 if (name != BoxType::HEV1SampleEntry
                     && name != BoxType::HVC1SampleEntry
+                    && name != BoxType::DVH1SampleEntry
+                    && name != BoxType::DVHESampleEntry
                     && name != BoxType::ProtectedVisualSampleEntry)
                     || codec_specific.is_some()
                 {
                     return Status::StsdBadVideoSampleEntry.into();
                 }
-                let hvcc_size = b
-                    .head
-                    .size
-                    .checked_sub(b.head.offset)
-                    .expect("offset invalid");
-                let hvcc = read_buf(&mut b.content, hvcc_size)?;
+                let hvcc = read_hvcc(&mut b, strictness)?;
                 debug!("{hvcc:?} (hvcc)");
                 codec_specific = Some(VideoCodecSpecific::HEVCConfig(hvcc));
             }
@@ -5834,6 +10631,11 @@ fn read_video_sample_entry<T: Read>(
                 }
                 debug!("Parsed pasp box: {pasp:?}, PAR {pixel_aspect_ratio:?}");
             }
+            BoxType::CleanApertureBox => {
+                let clap = read_clap(&mut b)?;
+                debug!("Parsed clap box: {clap:?}");
+                clean_aperture = Some(clap);
+            }
             BoxType::ColourInformationBox => {
                 // ISO/IEC 14496-12:2015 § 12.1.5.1 permits one or more colr boxes
                 // in a VisualSampleEntry (at most one for a given colour_type) and
@@ -5873,6 +10675,16 @@ fn read_video_sample_entry<T: Read>(
                 debug!("Parsed clli box: {clli:?}");
                 hdr_content_light_level = Some(clli);
             }
+            BoxType::DVCConfigurationBox
+            | BoxType::DVVConfigurationBox
+            | BoxType::DVWConfigurationBox => {
+                if dolby_vision_config.is_some() {
+                    return Status::StsdBadVideoSampleEntry.into();
+                }
+                let dovi = read_dovi(&mut b)?;
+                debug!("Parsed {:?} box: {dovi:?}", b.head.name);
+                dolby_vision_config = Some(dovi);
+            }
             _ => {
                 debug!("Unsupported video codec, box {:?} found", b.head.name);
                 skip_box_content(&mut b)?;
@@ -5883,18 +10695,25 @@ fn read_video_sample_entry<T: Read>(
 
     Ok(
         codec_specific.map_or(SampleEntry::Unknown, |codec_specific| {
-            SampleEntry::Video(VideoSampleEntry {
+            let mut entry = VideoSampleEntry {
                 codec_type,
+                sample_entry_type: name.into(),
                 data_reference_index,
                 width,
                 height,
                 codec_specific,
                 protection_info,
                 pixel_aspect_ratio,
+                clean_aperture,
                 colour_info,
                 hdr_mastering_display,
                 hdr_content_light_level,
-            })
+                dolby_vision_config,
+            };
+            if entry.codec_type != CodecType::EncryptedVideo && entry.requires_dolby_vision() {
+                entry.codec_type = CodecType::DolbyVision;
+            }
+            SampleEntry::Video(entry)
         }),
     )
 }
@@ -6177,21 +10996,24 @@ fn read_schi<T: Read>(src: &mut BMFFBox<T>) -> Result<Option<TrackEncryptionBox>
 
 fn read_tenc<T: Read>(src: &mut BMFFBox<T>) -> Result<TrackEncryptionBox> {
     let (version, _) = read_fullbox_extra(src)?;
+    read_encryption_defaults(src, version >= 1)
+}
 
+/// Parse the fields shared by a 'tenc' box and a 'seig' sample group entry,
+/// which has the pattern byte of a version 1 'tenc'.
+/// See Common Encryption (ISO 23001-7:2016) § 6 and § 8.2
+fn read_encryption_defaults<T: Read>(src: &mut T, has_pattern: bool) -> Result<TrackEncryptionBox> {
     // reserved byte
     skip(src, 1)?;
     // the next byte is used to signal the default pattern in version >= 1
-    let (default_crypt_byte_block, default_skip_byte_block) = match version {
-        0 => {
-            skip(src, 1)?;
-            (None, None)
-        }
-        _ => {
-            let pattern_byte = src.read_u8()?;
-            let crypt_bytes = pattern_byte >> 4;
-            let skip_bytes = pattern_byte & 0x0f;
-            (Some(crypt_bytes), Some(skip_bytes))
-        }
+    let (default_crypt_byte_block, default_skip_byte_block) = if has_pattern {
+        let pattern_byte = src.read_u8()?;
+        let crypt_bytes = pattern_byte >> 4;
+        let skip_bytes = pattern_byte & 0x0f;
+        (Some(crypt_bytes), Some(skip_bytes))
+    } else {
+        skip(src, 1)?;
+        (None, None)
     };
     let default_is_encrypted = src.read_u8()?;
     let default_iv_size = src.read_u8()?;
@@ -6215,6 +11037,177 @@ fn read_tenc<T: Read>(src: &mut BMFFBox<T>) -> Result<TrackEncryptionBox> {
     })
 }
 
+/// Parse a sample group description box.
+/// See ISOBMFF (ISO 14496-12:2020) § 8.9.3
+fn read_sgpd<T: Read>(src: &mut BMFFBox<T>) -> Result<SampleGroupDescriptionBox> {
+    let (version, _) = read_fullbox_extra(src)?;
+    let grouping_type = FourCC::from(be_u32(src)?);
+    let default_length = if version == 1 { be_u32(src)? } else { 0 };
+    let default_group_description_index = if version >= 2 {
+        Some(be_u32(src)?)
+    } else {
+        None
+    };
+    let entry_count = be_u32(src)?;
+    if u64::from(entry_count) > src.bytes_left() {
+        return Status::SgpdBadSize.into();
+    }
+
+    let is_seig = grouping_type == b"seig";
+    let mut entries = TryVec::with_capacity(entry_count.to_usize())?;
+    for _ in 0..entry_count {
+        // Only version 1 records entry lengths; otherwise they're implied
+        // by the grouping type.
+        let length = match (version, default_length) {
+            (1, 0) => Some(be_u32(src)?),
+            (1, length) => Some(length),
+            _ => None,
+        };
+        let entry = match (is_seig, length) {
+            (true, Some(length)) => {
+                let data = read_buf(src, length.into())?;
+                SampleGroupDescriptionEntry::CencSampleEncryptionInformation(
+                    read_encryption_defaults(&mut Cursor::new(data.as_slice()), true)?,
+                )
+            }
+            (true, None) => SampleGroupDescriptionEntry::CencSampleEncryptionInformation(
+                read_encryption_defaults(src, true)?,
+            ),
+            (false, Some(length)) => {
+                SampleGroupDescriptionEntry::Unknown(read_buf(src, length.into())?)
+            }
+            (false, None) => {
+                // The entries of an unknown grouping type can't be delimited.
+                skip_box_remain(src)?;
+                break;
+            }
+        };
+        entries.push(entry)?;
+    }
+
+    Ok(SampleGroupDescriptionBox {
+        grouping_type,
+        default_group_description_index,
+        entries,
+    })
+}
+
+/// Parse a sample to group box.
+/// See ISOBMFF (ISO 14496-12:2020) § 8.9.2
+fn read_sbgp<T: Read>(src: &mut BMFFBox<T>) -> Result<SampleToGroupBox> {
+    let (version, _) = read_fullbox_extra(src)?;
+    let grouping_type = FourCC::from(be_u32(src)?);
+    let grouping_type_parameter = if version == 1 {
+        Some(be_u32(src)?)
+    } else {
+        None
+    };
+    let entry_count = be_u32(src)?;
+    if u64::from(entry_count) * 8 > src.bytes_left() {
+        return Status::SbgpBadSize.into();
+    }
+
+    let mut entries = TryVec::with_capacity(entry_count.to_usize())?;
+    for _ in 0..entry_count {
+        entries.push(SampleToGroupEntry {
+            sample_count: be_u32(src)?,
+            group_description_index: be_u32(src)?,
+        })?;
+    }
+
+    Ok(SampleToGroupBox {
+        grouping_type,
+        grouping_type_parameter,
+        entries,
+    })
+}
+
+/// Parse a sample encryption box.
+/// See Common Encryption (ISO 23001-7:2016) § 7.2
+fn read_senc<T: Read>(src: &mut BMFFBox<T>) -> Result<SampleEncryptionBox> {
+    const USE_SUBSAMPLE_ENCRYPTION: u32 = 0x2;
+
+    let (_, flags) = read_fullbox_extra(src)?;
+    let sample_count = be_u32(src)?;
+    let data = read_buf(src, src.bytes_left())?;
+    Ok(SampleEncryptionBox {
+        uses_subsamples: flags & USE_SUBSAMPLE_ENCRYPTION != 0,
+        sample_count,
+        data,
+    })
+}
+
+/// Parse a sample auxiliary information sizes box.
+/// See ISOBMFF (ISO 14496-12:2020) § 8.7.8
+fn read_saiz<T: Read>(src: &mut BMFFBox<T>) -> Result<SampleAuxiliaryInformationSizesBox> {
+    let (_, flags) = read_fullbox_extra(src)?;
+    let aux_info_type = if flags & 1 != 0 {
+        let aux_info_type = FourCC::from(be_u32(src)?);
+        let _aux_info_type_parameter = be_u32(src)?;
+        Some(aux_info_type)
+    } else {
+        None
+    };
+    let default_sample_info_size = src.read_u8()?;
+    let sample_count = be_u32(src)?;
+    let sample_info_sizes = if default_sample_info_size == 0 {
+        if u64::from(sample_count) > src.bytes_left() {
+            return Status::SaizBadSize.into();
+        }
+        read_buf(src, sample_count.into())?
+    } else {
+        TryVec::new()
+    };
+    Ok(SampleAuxiliaryInformationSizesBox {
+        aux_info_type,
+        default_sample_info_size,
+        sample_count,
+        sample_info_sizes,
+    })
+}
+
+/// Parse a sample auxiliary information offsets box.
+/// See ISOBMFF (ISO 14496-12:2020) § 8.7.9
+fn read_saio<T: Read>(src: &mut BMFFBox<T>) -> Result<SampleAuxiliaryInformationOffsetsBox> {
+    let (version, flags) = read_fullbox_extra(src)?;
+    let aux_info_type = if flags & 1 != 0 {
+        let aux_info_type = FourCC::from(be_u32(src)?);
+        let _aux_info_type_parameter = be_u32(src)?;
+        Some(aux_info_type)
+    } else {
+        None
+    };
+    let entry_count = be_u32(src)?;
+    let offset_size = if version == 0 { 4 } else { 8 };
+    if u64::from(entry_count) * offset_size > src.bytes_left() {
+        return Status::SaioBadSize.into();
+    }
+    let mut offsets = TryVec::with_capacity(entry_count.to_usize())?;
+    for _ in 0..entry_count {
+        let offset = if version == 0 {
+            u64::from(be_u32(src)?)
+        } else {
+            be_u64(src)?
+        };
+        offsets.push(offset)?;
+    }
+    Ok(SampleAuxiliaryInformationOffsetsBox {
+        aux_info_type,
+        offsets,
+    })
+}
+
+/// Whether a 'saiz' or 'saio' with the given `aux_info_type` describes
+/// Common Encryption sample information, which is the case when the type is
+/// omitted or is a protection scheme.
+/// See Common Encryption (ISO 23001-7:2016) § 7.1
+fn is_encryption_aux_info(aux_info_type: &Option<FourCC>) -> bool {
+    match aux_info_type {
+        None => true,
+        Some(fourcc) => matches!(&fourcc.value, b"cenc" | b"cens" | b"cbc1" | b"cbcs"),
+    }
+}
+
 fn read_schm<T: Read>(src: &mut BMFFBox<T>) -> Result<SchemeTypeBox> {
     // Flags can be used to signal presence of URI in the box, but we don't
     // use the URI so don't bother storing the flags.
@@ -489,6 +489,103 @@ fn read_vpcc_version_1() {
     }
 }
 
+#[test]
+fn read_av1c_sequence_header() {
+    // A 1920x1080 10-bit sequence header with timing info, a decoder model
+    // and two operating points, signalling BT.2100 PQ colour
+    let sequence_header = [
+        0x04, 0x00, 0x00, 0x0f, 0xa4, 0x00, 0x03, 0xa9, 0x83, 0xa4, 0x00, 0x00, 0x00, 0x04, 0x00,
+        0x84, 0x40, 0xd3, 0x80, 0xa0, 0x2a, 0x62, 0x02, 0x52, 0xab, 0xbf, 0xc3, 0x77, 0xff, 0xe7,
+        0x42, 0x44, 0x02, 0x4b,
+    ];
+    let mut stream = make_box(BoxSize::Auto, b"av1C", |s| {
+        s.B8(0x81)
+            .B8(0x09)
+            .B8(0xcd)
+            .B8(0)
+            // A temporal delimiter OBU
+            .B8(0x12)
+            .B8(0)
+            // The sequence header OBU, with a non-minimal leb128 size
+            .B8(0x0a)
+            .B8(0x80 | sequence_header.len() as u8)
+            .B8(0)
+            .append_bytes(&sequence_header)
+    });
+    let mut iter = super::BoxIter::new(&mut stream);
+    let mut stream = iter.next_box().unwrap().unwrap();
+    let av1c = super::read_av1c(&mut stream).expect("read_av1c failed");
+    let sequence_header = av1c.sequence_header.expect("no sequence header");
+    assert_eq!(sequence_header.seq_profile, 0);
+    assert!(!sequence_header.still_picture);
+    assert!(!sequence_header.reduced_still_picture_header);
+    let timing_info = sequence_header.timing_info.expect("no timing info");
+    assert_eq!(timing_info.num_units_in_display_tick, 1001);
+    assert_eq!(timing_info.time_scale, 60000);
+    assert_eq!(timing_info.num_ticks_per_picture, Some(1));
+    let operating_points = &sequence_header.operating_points;
+    assert_eq!(operating_points.len(), 2);
+    assert_eq!(operating_points[0].idc, 0x103);
+    assert_eq!(operating_points[0].seq_level_idx, 9);
+    assert_eq!(operating_points[0].seq_tier, 1);
+    assert_eq!(operating_points[1].idc, 0x101);
+    assert_eq!(operating_points[1].seq_level_idx, 5);
+    assert_eq!(operating_points[1].seq_tier, 0);
+    assert_eq!(
+        (
+            sequence_header.max_frame_width,
+            sequence_header.max_frame_height
+        ),
+        (1920, 1080)
+    );
+    assert!(sequence_header.film_grain_params_present);
+
+    let color_config = &sequence_header.color_config;
+    assert_eq!(color_config.bit_depth, 10);
+    assert!(!color_config.mono_chrome);
+    assert!(color_config.subsampling_x && color_config.subsampling_y);
+    assert_eq!(color_config.chroma_sample_position, 1);
+    assert!(!color_config.separate_uv_delta_q);
+    let colour = color_config
+        .colour_information()
+        .expect("no colour description");
+    assert_eq!(colour.colour_primaries, 9);
+    assert_eq!(colour.transfer_characteristics, 16);
+    assert_eq!(colour.matrix_coefficients, 9);
+    assert!(!colour.full_range_flag);
+}
+
+#[test]
+fn read_uvlc() {
+    let read = |bytes: &[u8]| {
+        let mut bit_reader = bitreader::BitReader::new(bytes);
+        let value = super::read_uvlc(&mut bit_reader).unwrap();
+        (value, bit_reader.position())
+    };
+    assert_eq!(read(&[0b1000_0000]), (0, 1));
+    assert_eq!(read(&[0b0110_0000]), (2, 3));
+    assert_eq!(
+        read(&[0, 0, 0, 0b1000_0000, 0, 0, 0b1000_0000]),
+        (1 << 24, 49)
+    );
+    // The value saturates from 32 leading zeros, after reading up to the 1
+    assert_eq!(read(&[0, 0, 0, 0, 0b1000_0000]), (u32::MAX.into(), 33));
+    assert_eq!(read(&[0, 0, 0, 0, 0, 0b0100_0000]), (u32::MAX.into(), 42));
+}
+
+#[test]
+fn read_av1c_bad_sequence_header() {
+    // A truncated sequence header isn't an error, as configOBUs are optional
+    let mut stream = make_box(BoxSize::Auto, b"av1C", |s| {
+        s.B8(0x81).B8(0).B8(0).B8(0).B8(0x0a).B8(2).B8(0).B8(0)
+    });
+    let mut iter = super::BoxIter::new(&mut stream);
+    let mut stream = iter.next_box().unwrap().unwrap();
+    let av1c = super::read_av1c(&mut stream).expect("read_av1c failed");
+    assert_eq!(av1c.config_obus().len(), 4);
+    assert!(av1c.sequence_header.is_none());
+}
+
 #[test]
 fn read_hdlr() {
     let mut stream = make_fullbox(BoxSize::Short(45), b"hdlr", 0, |s| {
@@ -649,6 +746,7 @@ fn read_flac() {
     let mut stream = iter.next_box().unwrap().unwrap();
     let r = super::read_audio_sample_entry(&mut stream, ParseStrictness::Normal);
     assert!(r.is_ok());
+    assert_eq!(r.unwrap().codec_string().unwrap(), "fLaC");
 }
 
 #[derive(Clone, Copy)]
@@ -740,6 +838,7 @@ fn read_opus() {
     let mut stream = iter.next_box().unwrap().unwrap();
     let r = super::read_audio_sample_entry(&mut stream, ParseStrictness::Normal);
     assert!(r.is_ok());
+    assert_eq!(r.unwrap().codec_string().unwrap(), "opus");
 }
 
 fn make_dops() -> Cursor<Vec<u8>> {
@@ -965,6 +1064,303 @@ fn skip_padding_in_stsd() {
         .expect("fail to skip padding: stsd");
 }
 
+fn make_video_sample_entry(name: &[u8; 4], config: Cursor<Vec<u8>>) -> Cursor<Vec<u8>> {
+    let config = config.into_inner();
+    make_box(BoxSize::Auto, name, |s| {
+        s.append_repeated(0, 6)
+            .B16(1)
+            .append_repeated(0, 16)
+            .B16(320)
+            .B16(240)
+            .append_repeated(0, 50)
+            .append_bytes(&config)
+    })
+}
+
+fn video_sample_entry_codec_string(mut stream: Cursor<Vec<u8>>) -> super::Result<super::TryString> {
+    let mut iter = super::BoxIter::new(&mut stream);
+    let mut stream = iter.next_box().unwrap().unwrap();
+    super::read_video_sample_entry(&mut stream, ParseStrictness::Normal)?.codec_string()
+}
+
+#[test]
+fn video_codec_string() {
+    // A Main 10 profile in profile space 1, with zero constraint flags
+    // between non-zero ones
+    let hvcc = make_box(BoxSize::Auto, b"hvcC", |s| {
+        s.B8(1)
+            .B8(0x62)
+            .B32(0x2000_0000)
+            .B32(0xb000_0100)
+            .B16(0)
+            .B8(153)
+            .B16(0xf000)
+            .B8(0xfc)
+            .B8(0xfd)
+            .B8(0xfa)
+            .B8(0xfa)
+            .B16(0)
+            .B8(0x0f)
+            .B8(0)
+    });
+    let codec_string = video_sample_entry_codec_string(make_video_sample_entry(b"hvc1", hvcc));
+    assert_eq!(codec_string.unwrap(), "hvc1.A2.4.H153.B0.00.01");
+
+    let vpcc = make_fullbox(BoxSize::Auto, b"vpcC", 1, |s| {
+        s.B8(2).B8(41).B8(0xa2).B8(9).B8(16).B8(9).B16(0)
+    });
+    let codec_string = video_sample_entry_codec_string(make_video_sample_entry(b"vp09", vpcc));
+    assert_eq!(codec_string.unwrap(), "vp09.02.41.10");
+
+    // An avcC which can't be parsed has no profile or level
+    let avcc = make_box(BoxSize::Auto, b"avcC", |s| s.append_repeated(0, 8));
+    match video_sample_entry_codec_string(make_video_sample_entry(b"avc3", avcc)) {
+        Err(Error::Unsupported(_)) => {}
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+fn make_dovi(name: &[u8; 4], profile_level: u16, compatibility_id: u8) -> Vec<u8> {
+    make_box(BoxSize::Auto, name, |s| {
+        s.B8(1)
+            .B8(0)
+            .B16(profile_level)
+            .B8(compatibility_id << 4)
+            .append_repeated(0, 19)
+    })
+    .into_inner()
+}
+
+#[test]
+fn read_dolby_vision() {
+    let mut hvcc = make_box(BoxSize::Auto, b"hvcC", |s| {
+        s.B8(1)
+            .B8(0x02)
+            .B32(0x2000_0000)
+            .B32(0x9000_0000)
+            .B16(0)
+            .B8(150)
+            .B16(0xf000)
+            .B8(0xfc)
+            .B8(0xfd)
+            .B8(0xfa)
+            .B8(0xfa)
+            .B16(0)
+            .B8(0x0f)
+            .B8(0)
+    })
+    .into_inner();
+
+    // Profile 5, level 6 with RPU and base layer
+    let mut config = hvcc.clone();
+    config.extend(make_dovi(b"dvcC", 0x0a35, 0));
+    let mut stream = make_video_sample_entry(b"dvh1", Cursor::new(config));
+    let mut iter = super::BoxIter::new(&mut stream);
+    let mut stream = iter.next_box().unwrap().unwrap();
+    let entry = super::read_video_sample_entry(&mut stream, ParseStrictness::Normal).unwrap();
+    let v = match entry {
+        super::SampleEntry::Video(v) => v,
+        _ => panic!("expected a VideoSampleEntry"),
+    };
+    // The base layer can only be presented by Dolby Vision decoders.
+    assert_eq!(v.codec_type, super::CodecType::DolbyVision);
+    let dovi = v.dolby_vision_config.as_ref().unwrap();
+    assert_eq!(dovi.dv_version_major, 1);
+    assert_eq!(dovi.dv_version_minor, 0);
+    assert_eq!(dovi.dv_profile, 5);
+    assert_eq!(dovi.dv_level, 6);
+    assert!(dovi.rpu_present);
+    assert!(!dovi.el_present);
+    assert!(dovi.bl_present);
+    assert_eq!(dovi.dv_bl_signal_compatibility_id, 0);
+    assert_eq!(
+        super::SampleEntry::Video(v).codec_string().unwrap(),
+        "dvh1.05.06"
+    );
+
+    // Profile 8.1 in a Dolby Vision sample entry, with an HDR10 base layer
+    let mut config = hvcc.clone();
+    config.extend(make_dovi(b"dvcC", 0x1025, 1));
+    let mut stream = make_video_sample_entry(b"dvh1", Cursor::new(config));
+    let mut iter = super::BoxIter::new(&mut stream);
+    let mut stream = iter.next_box().unwrap().unwrap();
+    let entry = super::read_video_sample_entry(&mut stream, ParseStrictness::Normal).unwrap();
+    match entry {
+        super::SampleEntry::Video(v) => assert_eq!(v.codec_type, super::CodecType::HEVC),
+        _ => panic!("expected a VideoSampleEntry"),
+    }
+
+    // Profile 8.1, level 4 in an ordinary hvc1 sample entry
+    let mut config = hvcc.clone();
+    config.extend(make_dovi(b"dvvC", 0x1025, 1));
+    let mut stream = make_video_sample_entry(b"hvc1", Cursor::new(config));
+    let mut iter = super::BoxIter::new(&mut stream);
+    let mut stream = iter.next_box().unwrap().unwrap();
+    let entry = super::read_video_sample_entry(&mut stream, ParseStrictness::Normal).unwrap();
+    let v = match entry {
+        super::SampleEntry::Video(v) => v,
+        _ => panic!("expected a VideoSampleEntry"),
+    };
+    let dovi = v.dolby_vision_config.as_ref().unwrap();
+    assert_eq!(dovi.dv_profile, 8);
+    assert_eq!(dovi.dv_level, 4);
+    assert_eq!(dovi.dv_bl_signal_compatibility_id, 1);
+    assert_eq!(
+        super::SampleEntry::Video(v).codec_string().unwrap(),
+        "hvc1.2.4.L150.90"
+    );
+
+    // A Dolby Vision sample entry without its configuration
+    let codec_string = video_sample_entry_codec_string(make_video_sample_entry(
+        b"dvhe",
+        Cursor::new(hvcc.clone()),
+    ));
+    match codec_string {
+        Err(Error::Unsupported(_)) => {}
+        r => panic!("unexpected result {:?}", r),
+    }
+
+    // Only one configuration box is allowed
+    hvcc.extend(make_dovi(b"dvcC", 0x0a35, 0));
+    hvcc.extend(make_dovi(b"dvvC", 0x1025, 1));
+    let mut stream = make_video_sample_entry(b"dvh1", Cursor::new(hvcc));
+    let mut iter = super::BoxIter::new(&mut stream);
+    let mut stream = iter.next_box().unwrap().unwrap();
+    match super::read_video_sample_entry(&mut stream, ParseStrictness::Normal) {
+        Err(Error::InvalidData(s)) => assert_eq!(s, Status::StsdBadVideoSampleEntry),
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+#[test]
+fn read_video_clap() {
+    let avcc = make_box(BoxSize::Auto, b"avcC", |s| s.append_repeated(0, 8)).into_inner();
+    let clap = make_box(BoxSize::Auto, b"clap", |s| {
+        s.B32(300)
+            .B32(1)
+            .B32(400)
+            .B32(2)
+            .B32(-10i32 as u32)
+            .B32(1)
+            .B32(0)
+            .B32(1)
+    })
+    .into_inner();
+    let mut stream = make_box(BoxSize::Auto, b"avc1", |s| {
+        s.append_repeated(0, 6)
+            .B16(1)
+            .append_repeated(0, 16)
+            .B16(320)
+            .B16(240)
+            .append_repeated(0, 50)
+            .append_bytes(&avcc)
+            .append_bytes(&clap)
+    });
+
+    let mut iter = super::BoxIter::new(&mut stream);
+    let mut stream = iter.next_box().unwrap().unwrap();
+    match super::read_video_sample_entry(&mut stream, ParseStrictness::Normal) {
+        Ok(super::SampleEntry::Video(v)) => {
+            assert_eq!(v.clean_aperture.unwrap().height_d, 2);
+            assert_eq!(
+                v.crop_rectangle(),
+                Some(super::CropRectangle {
+                    x: 0,
+                    y: 20,
+                    width: 300,
+                    height: 200,
+                })
+            );
+        }
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+#[test]
+fn clap_crop_rectangle() {
+    let clap = |width_n, width_d, horiz_off_n, horiz_off_d| super::CleanApertureBox {
+        width_n,
+        width_d,
+        height_n: 4,
+        height_d: 1,
+        horiz_off_n,
+        horiz_off_d,
+        vert_off_n: 0,
+        vert_off_d: 1,
+    };
+    let crop = |x, width| {
+        Some(super::CropRectangle {
+            x,
+            y: 0,
+            width,
+            height: 4,
+        })
+    };
+    assert_eq!(clap(4, 1, 0, 1).crop_rectangle(4, 4), crop(0, 4));
+    assert_eq!(clap(2, 1, 0, 1).crop_rectangle(4, 4), crop(1, 2));
+    assert_eq!(clap(2, 1, -1, 1).crop_rectangle(4, 4), crop(0, 2));
+    assert_eq!(clap(2, 1, 1, 2).crop_rectangle(3, 4), crop(1, 2));
+    // Fractional sizes and offsets
+    assert_eq!(clap(5, 2, 0, 1).crop_rectangle(4, 4), None);
+    assert_eq!(clap(2, 1, 0, 1).crop_rectangle(3, 4), None);
+    // Outside the image
+    assert_eq!(clap(2, 1, -2, 1).crop_rectangle(4, 4), None);
+    assert_eq!(clap(2, 1, 2, 1).crop_rectangle(4, 4), None);
+    assert_eq!(clap(8, 1, 0, 1).crop_rectangle(4, 4), None);
+    // Zero denominators
+    assert_eq!(clap(2, 0, 0, 1).crop_rectangle(4, 4), None);
+    assert_eq!(clap(2, 1, 0, 0).crop_rectangle(4, 4), None);
+    // The largest sizes and denominators don't overflow
+    assert_eq!(
+        clap(1, 1, 0, u32::MAX).crop_rectangle(u32::MAX, 4),
+        crop(i32::MAX as u32, 1)
+    );
+    assert_eq!(
+        clap(u32::MAX, u32::MAX, i32::MIN, u32::MAX).crop_rectangle(u32::MAX, 4),
+        None
+    );
+}
+
+#[test]
+fn display_geometry() {
+    use super::{DisplayGeometry, ImageMirror, ImageRotation};
+    let geometry = |rotation, mirror| DisplayGeometry::new(3, 2, None, rotation, mirror);
+
+    let g = geometry(ImageRotation::D0, None);
+    assert_eq!((g.width, g.height), (3, 2));
+    assert_eq!(g.matrix, [1, 0, 0, 0, 1, 0]);
+
+    // The top right corner (2, 0) moves to the top left after rotating anticlockwise
+    let g = geometry(ImageRotation::D90, None);
+    assert_eq!((g.width, g.height), (2, 3));
+    assert_eq!(g.matrix, [0, 1, 0, -1, 0, 3]);
+
+    let g = geometry(ImageRotation::D180, None);
+    assert_eq!((g.width, g.height), (3, 2));
+    assert_eq!(g.matrix, [-1, 0, 3, 0, -1, 2]);
+
+    let g = geometry(ImageRotation::D270, Some(&ImageMirror::LeftRight));
+    assert_eq!((g.width, g.height), (2, 3));
+    assert_eq!(g.matrix, [0, 1, 0, 1, 0, 0]);
+
+    let crop = super::CropRectangle {
+        x: 1,
+        y: 0,
+        width: 2,
+        height: 2,
+    };
+    let g = DisplayGeometry::new(
+        3,
+        2,
+        Some(crop),
+        ImageRotation::D0,
+        Some(&ImageMirror::TopBottom),
+    );
+    assert_eq!(g.crop, crop);
+    assert_eq!((g.width, g.height), (2, 2));
+    assert_eq!(g.matrix, [1, 0, -1, 0, -1, 2]);
+}
+
 #[test]
 fn read_qt_wave_atom() {
     let esds = make_fullbox(BoxSize::Auto, b"esds", 0, |s| {
@@ -1170,6 +1566,49 @@ fn read_esds_truncated_dsi() {
     assert_eq!(es.decoder_specific_data, aac_dc_descriptor);
 }
 
+#[test]
+fn read_esds_he_aac_v2() {
+    // DSI `eb 8a 08 00` decodes as:
+    //   AOT=29 (SBR and PS, explicit signalling)
+    //   samplingFrequencyIndex=7 (22050 Hz base)
+    //   channelConfiguration=1 (mono, stereo with PS)
+    //   extensionSamplingFrequencyIndex=4 (44100 Hz extended)
+    //   inner AOT=2 (AAC-LC)
+    let aac_esds = vec![
+        0x03, 0x1b, // ES_Descriptor tag, len=27
+        0x00, 0x00, 0x00, // ES_ID + flags
+        0x04, 0x13, // DC_Descriptor tag, len=19
+        0x40, 0x15, 0x00, 0x00, 0x00, // objType=AAC, streamType, bufferSize
+        0x00, 0x00, 0x00, 0x00, // maxBitrate
+        0x00, 0x00, 0x00, 0x00, // avgBitrate
+        0x05, 0x04, 0xeb, 0x8a, 0x08, 0x00, // DSI: HE-AAC v2
+        0x06, 0x01, 0x02, // SL_Descriptor
+    ];
+
+    let mut stream = make_box(BoxSize::Auto, b"esds", |s| {
+        s.B32(0) // reserved
+            .append_bytes(aac_esds.as_slice())
+    });
+    let mut iter = super::BoxIter::new(&mut stream);
+    let mut stream = iter.next_box().unwrap().unwrap();
+
+    let es = super::read_esds(&mut stream, ParseStrictness::Normal).unwrap();
+
+    assert_eq!(es.audio_object_type, Some(2));
+    assert_eq!(es.extended_audio_object_type, Some(5));
+    assert_eq!(es.sbr_audio_object_type, Some(29));
+    let entry = super::AudioSampleEntry {
+        codec_type: super::CodecType::AAC,
+        data_reference_index: 1,
+        channelcount: 2,
+        samplesize: 16,
+        samplerate: 44100.0,
+        codec_specific: super::AudioCodecSpecific::ES_Descriptor(es),
+        protection_info: super::TryVec::new(),
+    };
+    assert_eq!(entry.codec_string().unwrap(), "mp4a.40.29");
+}
+
 #[test]
 fn read_esds_duplicate_dsi() {
     // Two DecSpecificInfo descriptors with identical AAC-LC 48 kHz stereo
@@ -1693,3 +2132,1973 @@ fn read_colr_nclx_missing_full_range_flag() {
         Err(e) => panic!("unexpected error {:?}", e),
     }
 }
+
+#[test]
+fn read_tfhd() {
+    let mut stream = make_box(BoxSize::Auto, b"tfhd", |s| {
+        s.B32(0x0002_0038) // version 0, default-base-is-moof, duration, size, flags
+            .B32(1) // track_ID
+            .B32(1024) // default_sample_duration
+            .B32(300) // default_sample_size
+            .B32(0x0101_0000) // default_sample_flags
+    });
+    let mut iter = super::BoxIter::new(&mut stream);
+    let mut stream = iter.next_box().unwrap().unwrap();
+    assert_eq!(stream.head.name, BoxType::TrackFragmentHeaderBox);
+    let tfhd = super::read_tfhd(&mut stream).unwrap();
+    assert_eq!(tfhd.track_id, 1);
+    assert_eq!(tfhd.base_data_offset, None);
+    assert_eq!(tfhd.sample_description_index, None);
+    assert_eq!(tfhd.default_sample_duration, Some(1024));
+    assert_eq!(tfhd.default_sample_size, Some(300));
+    assert_eq!(tfhd.default_sample_flags, Some(0x0101_0000));
+    assert!(!tfhd.duration_is_empty);
+    assert!(tfhd.default_base_is_moof);
+}
+
+#[test]
+fn read_trun() {
+    let mut stream = make_box(BoxSize::Auto, b"trun", |s| {
+        s.B32(0x0100_0b05) // version 1, data offset, first sample flags, duration, size, cto
+            .B32(2) // sample_count
+            .B32(120) // data_offset
+            .B32(0x0200_0000) // first_sample_flags
+            .B32(1000)
+            .B32(10)
+            .B32(0)
+            .B32(1000)
+            .B32(20)
+            .B32(-500i32 as u32)
+    });
+    let mut iter = super::BoxIter::new(&mut stream);
+    let mut stream = iter.next_box().unwrap().unwrap();
+    assert_eq!(stream.head.name, BoxType::TrackRunBox);
+    let trun = super::read_trun(&mut stream).unwrap();
+    assert_eq!(trun.data_offset, Some(120));
+    assert_eq!(trun.first_sample_flags, Some(0x0200_0000));
+    assert_eq!(trun.sample_count, 2);
+    assert_eq!(trun.samples.len(), 2);
+    assert_eq!(trun.sample(0).size, Some(10));
+    assert_eq!(trun.sample(1).duration, Some(1000));
+    assert_eq!(trun.sample(1).flags, None);
+    assert_eq!(trun.sample(1).composition_time_offset, Some(-500));
+}
+
+#[test]
+fn read_trun_bad_size() {
+    let mut stream = make_box(BoxSize::Auto, b"trun", |s| {
+        s.B32(0x0000_0200) // version 0, sample size present
+            .B32(u32::MAX) // sample_count
+            .B32(10)
+    });
+    let mut iter = super::BoxIter::new(&mut stream);
+    let mut stream = iter.next_box().unwrap().unwrap();
+    match super::read_trun(&mut stream) {
+        Err(Error::InvalidData(s)) => assert_eq!(s, Status::TrunBadSize),
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+#[test]
+fn read_senc() {
+    let mut stream = make_box(BoxSize::Auto, b"senc", |s| {
+        s.B32(0x0000_0002) // version 0, subsamples present
+            .B32(2) // sample_count
+            .B64(0x0102_0304_0506_0708)
+            .B16(1)
+            .B16(10)
+            .B32(100)
+            .B64(0x1112_1314_1516_1718)
+            .B16(0)
+    });
+    let mut iter = super::BoxIter::new(&mut stream);
+    let mut stream = iter.next_box().unwrap().unwrap();
+    let senc = super::read_senc(&mut stream).unwrap();
+    assert!(senc.uses_subsamples);
+    assert_eq!(senc.sample_count, 2);
+
+    let entries = senc.entries(8).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(*entries[0].iv, [1, 2, 3, 4, 5, 6, 7, 8]);
+    assert_eq!(
+        *entries[0].subsamples,
+        [super::SubsampleEntry {
+            bytes_of_clear_data: 10,
+            bytes_of_protected_data: 100,
+        }]
+    );
+    assert_eq!(entries[1].iv[0], 0x11);
+    assert!(entries[1].subsamples.is_empty());
+
+    // With 16 byte IVs the entries overrun the box.
+    match senc.entries(16) {
+        Err(Error::InvalidData(s)) => assert_eq!(s, Status::SencBadSize),
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+#[test]
+fn read_saiz_saio() {
+    let mut stream = make_box(BoxSize::Auto, b"saiz", |s| {
+        s.B32(0x0000_0001) // version 0, aux_info_type present
+            .append_bytes(b"cenc")
+            .B32(0)
+            .B8(0) // default_sample_info_size
+            .B32(3)
+            .B8(16)
+            .B8(22)
+            .B8(28)
+    });
+    let mut iter = super::BoxIter::new(&mut stream);
+    let mut stream = iter.next_box().unwrap().unwrap();
+    let saiz = super::read_saiz(&mut stream).unwrap();
+    assert_eq!(*saiz.aux_info_type.as_ref().unwrap(), b"cenc");
+    assert_eq!(saiz.sample_info_size(1), Some(22));
+    assert_eq!(saiz.sample_info_size(3), None);
+
+    let mut stream = make_fullbox(BoxSize::Auto, b"saio", 1, |s| s.B32(1).B64(0x1_0000_0000));
+    let mut iter = super::BoxIter::new(&mut stream);
+    let mut stream = iter.next_box().unwrap().unwrap();
+    let saio = super::read_saio(&mut stream).unwrap();
+    assert_eq!(saio.aux_info_type, None);
+    assert_eq!(*saio.offsets, [0x1_0000_0000]);
+
+    let mut stream = make_fullbox(BoxSize::Auto, b"saio", 0, |s| s.B32(2).B32(0));
+    let mut iter = super::BoxIter::new(&mut stream);
+    let mut stream = iter.next_box().unwrap().unwrap();
+    match super::read_saio(&mut stream) {
+        Err(Error::InvalidData(s)) => assert_eq!(s, Status::SaioBadSize),
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+#[test]
+fn read_sgpd_sbgp() {
+    let mut stream = make_fullbox(BoxSize::Auto, b"sgpd", 1, |s| {
+        s.append_bytes(b"seig")
+            .B32(0) // default_length, so each entry has a length
+            .B32(2)
+            .B32(20)
+            .B8(0)
+            .B8(0x19) // crypt_byte_block 1, skip_byte_block 9
+            .B8(1)
+            .B8(8)
+            .append_repeated(0xaa, 16)
+            .B32(37)
+            .B8(0)
+            .B8(0)
+            .B8(1)
+            .B8(0) // constant IV follows
+            .append_repeated(0xbb, 16)
+            .B8(16)
+            .append_repeated(0xcc, 16)
+    });
+    let mut iter = super::BoxIter::new(&mut stream);
+    let mut stream = iter.next_box().unwrap().unwrap();
+    let sgpd = super::read_sgpd(&mut stream).unwrap();
+    assert_eq!(sgpd.grouping_type, b"seig");
+    assert_eq!(sgpd.entries.len(), 2);
+    let seig = sgpd.seig_entry(1).unwrap();
+    assert_eq!(seig.iv_size, 8);
+    assert_eq!(seig.crypt_byte_block_count, Some(1));
+    assert_eq!(seig.skip_byte_block_count, Some(9));
+    assert_eq!(*seig.kid, [0xaa; 16]);
+    let seig = sgpd.seig_entry(2).unwrap();
+    assert_eq!(seig.constant_iv.as_deref(), Some(&[0xcc; 16][..]));
+    assert!(sgpd.seig_entry(0).is_none());
+    assert!(sgpd.seig_entry(3).is_none());
+
+    let mut stream = make_fullbox(BoxSize::Auto, b"sgpd", 1, |s| {
+        s.append_bytes(b"roll").B32(2).B32(1).B16(0xffff)
+    });
+    let mut iter = super::BoxIter::new(&mut stream);
+    let mut stream = iter.next_box().unwrap().unwrap();
+    let sgpd = super::read_sgpd(&mut stream).unwrap();
+    match &sgpd.entries[0] {
+        super::SampleGroupDescriptionEntry::Unknown(data) => assert_eq!(**data, [0xff, 0xff]),
+        e => panic!("unexpected entry {:?}", e),
+    }
+
+    let mut stream = make_fullbox(BoxSize::Auto, b"sbgp", 1, |s| {
+        s.append_bytes(b"seig")
+            .B32(7)
+            .B32(2)
+            .B32(10)
+            .B32(1)
+            .B32(5)
+            .B32(0x1_0001)
+    });
+    let mut iter = super::BoxIter::new(&mut stream);
+    let mut stream = iter.next_box().unwrap().unwrap();
+    let sbgp = super::read_sbgp(&mut stream).unwrap();
+    assert_eq!(sbgp.grouping_type_parameter, Some(7));
+    assert_eq!(
+        *sbgp.entries,
+        [
+            super::SampleToGroupEntry {
+                sample_count: 10,
+                group_description_index: 1,
+            },
+            super::SampleToGroupEntry {
+                sample_count: 5,
+                group_description_index: 0x1_0001,
+            },
+        ]
+    );
+
+    let mut stream = make_fullbox(BoxSize::Auto, b"sbgp", 0, |s| {
+        s.append_bytes(b"seig").B32(2).B32(10).B32(1)
+    });
+    let mut iter = super::BoxIter::new(&mut stream);
+    let mut stream = iter.next_box().unwrap().unwrap();
+    match super::read_sbgp(&mut stream) {
+        Err(Error::InvalidData(s)) => assert_eq!(s, Status::SbgpBadSize),
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+#[test]
+fn read_traf_missing_tfhd() {
+    let mut stream = make_box(BoxSize::Auto, b"traf", |s| {
+        s.append_bytes(&make_fullbox(BoxSize::Auto, b"tfdt", 0, |s| s.B32(0)).into_inner())
+    });
+    let mut iter = super::BoxIter::new(&mut stream);
+    let mut stream = iter.next_box().unwrap().unwrap();
+    match super::read_traf(&mut stream) {
+        Err(Error::InvalidData(s)) => assert_eq!(s, Status::TfhdBadQuantity),
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+/// Build a fragmented file with an 'mdat' following its single 'moof',
+/// returning it with the offset of the 'moof'.
+fn make_fragmented_mp4() -> (Vec<u8>, u64) {
+    let ftyp = make_box(BoxSize::Auto, b"ftyp", |s| {
+        s.append_bytes(b"iso6").B32(0).append_bytes(b"iso6")
+    })
+    .into_inner();
+    let trex = make_fullbox(BoxSize::Auto, b"trex", 0, |s| {
+        s.B32(1).B32(1).B32(512).B32(0).B32(0x0001_0000)
+    })
+    .into_inner();
+    let mvex = make_box(BoxSize::Auto, b"mvex", |s| s.append_bytes(&trex)).into_inner();
+    let moov = make_box(BoxSize::Auto, b"moov", |s| s.append_bytes(&mvex)).into_inner();
+    let mfhd = make_fullbox(BoxSize::Auto, b"mfhd", 0, |s| s.B32(7)).into_inner();
+    let tfhd = make_box(BoxSize::Auto, b"tfhd", |s| s.B32(0x0002_0000).B32(1)).into_inner();
+    let tfdt = make_fullbox(BoxSize::Auto, b"tfdt", 1, |s| s.B64(9000)).into_inner();
+    let trun = make_box(BoxSize::Auto, b"trun", |s| {
+        s.B32(0x0000_0201).B32(2).B32(0).B32(100).B32(50)
+    })
+    .into_inner();
+    let traf = make_box(BoxSize::Auto, b"traf", |s| {
+        s.append_bytes(&tfhd)
+            .append_bytes(&tfdt)
+            .append_bytes(&trun)
+    })
+    .into_inner();
+    let moof = make_box(BoxSize::Auto, b"moof", |s| {
+        s.append_bytes(&mfhd).append_bytes(&traf)
+    })
+    .into_inner();
+    let mdat = make_box(BoxSize::Auto, b"mdat", |s| s.append_repeated(0, 150)).into_inner();
+
+    let mut file = Vec::new();
+    file.extend_from_slice(&ftyp);
+    file.extend_from_slice(&moov);
+    file.extend_from_slice(&moof);
+    file.extend_from_slice(&mdat);
+    (file, (ftyp.len() + moov.len()) as u64)
+}
+
+#[test]
+fn read_mp4_fragments() {
+    let (file, moof_offset) = make_fragmented_mp4();
+    let mut stream = Cursor::new(file);
+
+    let context = read_mp4(&mut stream, ParseStrictness::Normal).expect("read_mp4 failed");
+    let trex = context.mvex.as_ref().unwrap().track_extends(1).unwrap();
+    assert_eq!(trex.default_sample_duration, 512);
+    assert_eq!(trex.default_sample_flags, 0x0001_0000);
+    assert_eq!(context.moofs.len(), 1);
+    let moof = &context.moofs[0];
+    assert_eq!(moof.offset, moof_offset);
+    assert_eq!(moof.sequence_number, 7);
+    assert_eq!(moof.trafs.len(), 1);
+    let traf = &moof.trafs[0];
+    assert_eq!(traf.tfhd.track_id, 1);
+    assert!(traf.tfhd.default_base_is_moof);
+    assert_eq!(traf.base_media_decode_time, Some(9000));
+    assert_eq!(traf.truns.len(), 1);
+    assert_eq!(traf.truns[0].data_offset, Some(0));
+    assert_eq!(traf.truns[0].sample(1).size, Some(50));
+}
+
+#[test]
+fn read_mp4_mdats() {
+    let (mut file, _) = make_fragmented_mp4();
+    let len = file.len() as u64;
+    let expected = [(len - 150, len)];
+
+    let context =
+        read_mp4(&mut Cursor::new(&file), ParseStrictness::Normal).expect("read_mp4 failed");
+    assert_eq!(context.mdats.as_slice(), expected);
+    let context = super::read_mp4_seekable(&mut Cursor::new(&file), ParseStrictness::Normal)
+        .expect("read_mp4_seekable failed");
+    assert_eq!(context.mdats.as_slice(), expected);
+    let mut parser = super::Mp4PushParser::new(ParseStrictness::Normal);
+    for chunk in file.chunks(7) {
+        parser.push(chunk).unwrap();
+        while parser.next_event().unwrap() != super::PushParseEvent::NeedMoreData {}
+    }
+    assert_eq!(parser.finish().unwrap().mdats.as_slice(), expected);
+
+    // Only the content in the file is recorded.
+    file.truncate(file.len() - 50);
+    let context = super::read_mp4_seekable(&mut Cursor::new(&file), ParseStrictness::Normal)
+        .expect("read_mp4_seekable failed");
+    assert_eq!(context.mdats.as_slice(), [(len - 150, len - 50)]);
+}
+
+#[test]
+fn read_mp4_malformed_fragment() {
+    let (file, moof_offset) = make_fragmented_mp4();
+    // A 'trun' missing the sizes of most of its samples
+    let trun = make_box(BoxSize::Auto, b"trun", |s| {
+        s.B32(0x0000_0200).B32(5).B32(10)
+    })
+    .into_inner();
+    let traf = make_box(BoxSize::Auto, b"traf", |s| s.append_bytes(&trun)).into_inner();
+    let moof = make_box(BoxSize::Auto, b"moof", |s| s.append_bytes(&traf)).into_inner();
+    let moof_offset = moof_offset as usize;
+    let file = [&file[..moof_offset], &moof, &file[moof_offset..]].concat();
+
+    let context =
+        read_mp4(&mut Cursor::new(&file), ParseStrictness::Normal).expect("read_mp4 failed");
+    assert_eq!(context.moofs.len(), 1);
+    assert_eq!(context.moofs[0].sequence_number, 7);
+    assert!(read_mp4(&mut Cursor::new(&file), ParseStrictness::Strict).is_err());
+}
+
+/// A fragmented file whose 'traf' has a 'saiz' and 'saio' locating the
+/// auxiliary information of its 2 samples at the start of the 'mdat', with
+/// the expected information.
+fn make_fragmented_mp4_with_aux_info() -> (Vec<u8>, Vec<u8>) {
+    let aux_info = [
+        &[1; 8][..],
+        &[2; 8][..],
+        // One subsample of 2 clear and 5 protected bytes
+        &[0, 1, 0, 2, 0, 0, 0, 5][..],
+    ]
+    .concat();
+    let ftyp = make_box(BoxSize::Auto, b"ftyp", |s| {
+        s.append_bytes(b"iso6").B32(0).append_bytes(b"iso6")
+    })
+    .into_inner();
+    let moov = make_box(BoxSize::Auto, b"moov", |s| s).into_inner();
+    let make_moof = |saio_offset: u32| {
+        let tfhd = make_box(BoxSize::Auto, b"tfhd", |s| {
+            s.B32(0x0002_0010).B32(1).B32(10)
+        })
+        .into_inner();
+        let trun = make_box(BoxSize::Auto, b"trun", |s| s.B32(0).B32(2)).into_inner();
+        let saiz =
+            make_fullbox(BoxSize::Auto, b"saiz", 0, |s| s.B8(0).B32(2).B8(8).B8(16)).into_inner();
+        let saio =
+            make_fullbox(BoxSize::Auto, b"saio", 0, |s| s.B32(1).B32(saio_offset)).into_inner();
+        let traf = make_box(BoxSize::Auto, b"traf", |s| {
+            s.append_bytes(&tfhd)
+                .append_bytes(&trun)
+                .append_bytes(&saiz)
+                .append_bytes(&saio)
+        })
+        .into_inner();
+        make_box(BoxSize::Auto, b"moof", |s| s.append_bytes(&traf)).into_inner()
+    };
+    // The offset is relative to the 'moof', and the size of the 'moof'
+    // doesn't depend on it.
+    let moof = make_moof(make_moof(0).len() as u32 + 8);
+    let mdat = make_box(BoxSize::Auto, b"mdat", |s| {
+        s.append_bytes(&aux_info).append_repeated(0, 20)
+    })
+    .into_inner();
+
+    let file = [ftyp, moov, moof, mdat].concat();
+    (file, aux_info)
+}
+
+#[test]
+fn read_mp4_aux_info() {
+    let (file, aux_info) = make_fragmented_mp4_with_aux_info();
+    let check = |context: &super::MediaContext| {
+        let traf = &context.moofs[0].trafs[0];
+        assert_eq!(traf.aux_info.as_deref(), Some(&aux_info[..]));
+        let entries = traf
+            .saiz
+            .as_ref()
+            .unwrap()
+            .encryption_entries(&aux_info, |_| 8)
+            .unwrap();
+        assert_eq!(*entries[0].iv, [1; 8]);
+        assert!(entries[0].subsamples.is_empty());
+        assert_eq!(*entries[1].iv, [2; 8]);
+        assert_eq!(
+            *entries[1].subsamples,
+            [super::SubsampleEntry {
+                bytes_of_clear_data: 2,
+                bytes_of_protected_data: 5,
+            }]
+        );
+    };
+
+    let context =
+        read_mp4(&mut Cursor::new(&file), ParseStrictness::Normal).expect("read_mp4 failed");
+    check(&context);
+    let context = super::read_mp4_seekable(&mut Cursor::new(&file), ParseStrictness::Normal)
+        .expect("read_mp4_seekable failed");
+    check(&context);
+
+    // The information straddles the chunks pushed.
+    let mut parser = super::Mp4PushParser::new(ParseStrictness::Normal);
+    for chunk in file.chunks(7) {
+        parser.push(chunk).unwrap();
+        while parser.next_event().unwrap() != super::PushParseEvent::NeedMoreData {}
+    }
+    check(&parser.finish().unwrap());
+
+    // IVs larger than the information of the first sample
+    let saiz = &context.moofs[0].trafs[0].saiz.as_ref().unwrap();
+    match saiz.encryption_entries(&aux_info, |_| 16) {
+        Err(Error::InvalidData(s)) => assert_eq!(s, Status::SaizBadInfo),
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+#[test]
+fn read_moof_pssh() {
+    let (mut file, _) = make_fragmented_mp4();
+    let mfhd = make_fullbox(BoxSize::Auto, b"mfhd", 0, |s| s.B32(8)).into_inner();
+    let pssh = make_fullbox(BoxSize::Auto, b"pssh", 1, |s| {
+        s.append_repeated(0x11, 16)
+            .B32(1)
+            .append_repeated(0x22, 16)
+            .B32(3)
+            .append_bytes(&[1, 2, 3])
+    })
+    .into_inner();
+    let moof = make_box(BoxSize::Auto, b"moof", |s| {
+        s.append_bytes(&mfhd).append_bytes(&pssh)
+    })
+    .into_inner();
+    file.extend_from_slice(&moof);
+    let mut stream = Cursor::new(file);
+
+    let context = read_mp4(&mut stream, ParseStrictness::Normal).expect("read_mp4 failed");
+    assert!(context.psshs.is_empty());
+    assert_eq!(context.moofs.len(), 2);
+    assert!(context.moofs[0].psshs.is_empty());
+    let psshs = &context.moofs[1].psshs;
+    assert_eq!(psshs.len(), 1);
+    assert_eq!(psshs[0].version, 1);
+    assert_eq!(*psshs[0].system_id, [0x11; 16]);
+    assert_eq!(psshs[0].kid.len(), 1);
+    assert_eq!(*psshs[0].kid[0], [0x22; 16]);
+    assert_eq!(*psshs[0].data, [1, 2, 3]);
+    assert_eq!(*psshs[0].box_content, *pssh);
+}
+
+#[test]
+fn read_mp4_segment_index() {
+    let (mut file, _) = make_fragmented_mp4();
+    file.truncate(file.len() - 8 - 150); // Drop the 'mdat'.
+    let base = file.len() as u64;
+
+    // A root 'sidx' referencing a child 'sidx' and then a subsegment.
+    let root = make_fullbox(BoxSize::Auto, b"sidx", 1, |s| {
+        s.B32(1)
+            .B32(90000)
+            .B64(1000)
+            .B64(0)
+            .B16(0)
+            .B16(2)
+            .B32(0x8000_0000 | (56 + 700))
+            .B32(6000)
+            .B32(0)
+            .B32(1000)
+            .B32(3000)
+            .B32(0x9000_0000)
+    })
+    .into_inner();
+    let child = make_fullbox(BoxSize::Auto, b"sidx", 0, |s| {
+        s.B32(1)
+            .B32(90000)
+            .B32(1000)
+            .B32(0)
+            .B16(0)
+            .B16(2)
+            .B32(300)
+            .B32(2500)
+            .B32(0x9000_0000)
+            .B32(400)
+            .B32(3500)
+            .B32(0)
+    })
+    .into_inner();
+    assert_eq!(root.len(), 64);
+    assert_eq!(child.len(), 56);
+    file.extend_from_slice(&root);
+    file.extend_from_slice(&child);
+
+    let context =
+        read_mp4(&mut Cursor::new(file), ParseStrictness::Normal).expect("read_mp4 failed");
+    assert_eq!(context.sidxs.len(), 2);
+    assert_eq!(context.sidxs[0].offset, base);
+    assert_eq!(context.sidxs[0].anchor_point, base + 64);
+    assert_eq!(context.sidxs[1].references[0].sap_type, 1);
+
+    assert!(context.segment_index(2).is_none());
+    let index = context.segment_index(1).unwrap().unwrap();
+    assert_eq!(index.timescale, 90000);
+    let expected = [
+        (base + 120, 300, 1000, 2500, true),
+        (base + 420, 400, 3500, 3500, false),
+        (base + 820, 1000, 7000, 3000, true),
+    ];
+    assert_eq!(index.segments.len(), expected.len());
+    for (segment, &(offset, size, start_time, duration, starts_with_sap)) in
+        index.segments.iter().zip(expected.iter())
+    {
+        assert_eq!(
+            *segment,
+            super::Segment {
+                offset,
+                size,
+                start_time,
+                duration,
+                starts_with_sap,
+            }
+        );
+    }
+}
+
+#[test]
+fn read_sidx_missing_child() {
+    let sidx = make_fullbox(BoxSize::Auto, b"sidx", 0, |s| {
+        s.B32(1)
+            .B32(1000)
+            .B32(0)
+            .B32(0)
+            .B16(0)
+            .B16(1)
+            .B32(0x8000_0000 | 100)
+            .B32(10)
+            .B32(0)
+    })
+    .into_inner();
+    let mut stream = make_box(BoxSize::Auto, b"moov", |s| s).into_inner();
+    stream.extend_from_slice(&sidx);
+    let context =
+        read_mp4(&mut Cursor::new(stream), ParseStrictness::Normal).expect("read_mp4 failed");
+    match context.segment_index(1) {
+        Some(Err(Error::InvalidData(s))) => assert_eq!(s, Status::SidxBadReference),
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+#[test]
+fn read_sidx_repeated_child() {
+    // Two empty references to the same child 'sidx', which would expand it
+    // twice.
+    let root = make_fullbox(BoxSize::Auto, b"sidx", 0, |s| {
+        s.B32(1)
+            .B32(1000)
+            .B32(0)
+            .B32(0)
+            .B16(0)
+            .B16(2)
+            .B32(0x8000_0000)
+            .B32(10)
+            .B32(0)
+            .B32(0x8000_0000)
+            .B32(10)
+            .B32(0)
+    })
+    .into_inner();
+    let child = make_fullbox(BoxSize::Auto, b"sidx", 0, |s| {
+        s.B32(1)
+            .B32(1000)
+            .B32(0)
+            .B32(0)
+            .B16(0)
+            .B16(1)
+            .B32(100)
+            .B32(10)
+            .B32(0x9000_0000)
+    })
+    .into_inner();
+    let mut stream = make_box(BoxSize::Auto, b"moov", |s| s).into_inner();
+    stream.extend_from_slice(&root);
+    stream.extend_from_slice(&child);
+    let context =
+        read_mp4(&mut Cursor::new(stream), ParseStrictness::Normal).expect("read_mp4 failed");
+    assert_eq!(context.sidxs.len(), 2);
+    match context.segment_index(1) {
+        Some(Err(Error::InvalidData(s))) => assert_eq!(s, Status::SidxBadReference),
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+#[test]
+fn read_mp4_mfra() {
+    let (mut file, moof_offset) = make_fragmented_mp4();
+    // Entries with 2 byte traf numbers, 1 byte trun numbers and 3 byte
+    // sample numbers.
+    let tfra = make_fullbox(BoxSize::Auto, b"tfra", 1, |s| {
+        s.B32(1)
+            .B32(0b01_00_10)
+            .B32(2)
+            .B64(9000)
+            .B64(moof_offset)
+            .B16(1)
+            .B8(1)
+            .B16(0)
+            .B8(1)
+            .B64(9512)
+            .B64(moof_offset)
+            .B16(1)
+            .B8(1)
+            .B16(0)
+            .B8(2)
+    })
+    .into_inner();
+    let mfra_size = (8 + tfra.len() + 16) as u32;
+    let mfra = make_box(BoxSize::Auto, b"mfra", |s| {
+        s.append_bytes(&tfra).append_bytes(
+            &make_fullbox(BoxSize::Auto, b"mfro", 0, |s| s.B32(mfra_size)).into_inner(),
+        )
+    })
+    .into_inner();
+    assert_eq!(mfra.len(), mfra_size as usize);
+    file.extend_from_slice(&mfra);
+
+    let mut stream = Cursor::new(file);
+    let context = read_mp4(&mut stream, ParseStrictness::Normal).expect("read_mp4 failed");
+    let tfras = &context.mfra.as_ref().unwrap().tfras;
+    assert_eq!(tfras.len(), 1);
+    assert_eq!(tfras[0].entries.len(), 2);
+
+    stream.set_position(3);
+    let mfra = super::read_mfra(&mut stream).unwrap().unwrap();
+    assert_eq!(stream.position(), 3);
+    assert!(mfra.find_random_access_point(1, 8999).is_none());
+    assert!(mfra.find_random_access_point(2, 9000).is_none());
+    assert_eq!(
+        mfra.find_random_access_point(1, 9600),
+        Some(&super::TrackFragmentRandomAccessEntry {
+            time: 9512,
+            moof_offset,
+            traf_number: 1,
+            trun_number: 1,
+            sample_number: 2,
+        })
+    );
+    assert_eq!(
+        mfra.find_random_access_point(1, 9000)
+            .unwrap()
+            .sample_number,
+        1
+    );
+
+    // Without a trailing 'mfro' there's nothing to find.
+    let (file, _) = make_fragmented_mp4();
+    assert!(super::read_mfra(&mut Cursor::new(file)).unwrap().is_none());
+}
+
+#[test]
+fn read_tfra_bad_size() {
+    let mut stream = make_fullbox(BoxSize::Auto, b"tfra", 0, |s| {
+        s.B32(1)
+            .B32(0)
+            .B32(u32::MAX)
+            .B32(0)
+            .B32(0)
+            .B8(1)
+            .B8(1)
+            .B8(1)
+    });
+    let mut iter = super::BoxIter::new(&mut stream);
+    let mut stream = iter.next_box().unwrap().unwrap();
+    match super::read_tfra(&mut stream) {
+        Err(Error::InvalidData(s)) => assert_eq!(s, Status::TfraBadSize),
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+#[test]
+fn push_parser_events() {
+    use super::PushParseEvent;
+
+    let (file, moof_offset) = make_fragmented_mp4();
+    for chunk_size in [1, 7, file.len()] {
+        let mut parser = super::Mp4PushParser::new(ParseStrictness::Normal);
+        let mut events = Vec::new();
+        for chunk in file.chunks(chunk_size) {
+            parser.push(chunk).unwrap();
+            loop {
+                match parser.next_event().unwrap() {
+                    PushParseEvent::NeedMoreData => break,
+                    event => events.push(event),
+                }
+            }
+        }
+        assert_eq!(
+            events,
+            [PushParseEvent::MoovReady, PushParseEvent::FragmentReady]
+        );
+        let context = parser.finish().unwrap();
+        assert_eq!(context.moofs.len(), 1);
+        assert_eq!(context.moofs[0].offset, moof_offset);
+    }
+}
+
+#[test]
+fn push_parser_moov_missing() {
+    let mut parser = super::Mp4PushParser::new(ParseStrictness::Normal);
+    parser
+        .push(&make_box(BoxSize::Auto, b"free", |s| s.B32(0)).into_inner())
+        .unwrap();
+    assert_eq!(
+        parser.next_event().unwrap(),
+        super::PushParseEvent::NeedMoreData
+    );
+    assert!(parser.context().is_none());
+    assert!(matches!(parser.finish(), Err(Error::MoovMissing)));
+}
+
+#[test]
+fn read_mp4_seekable_skips_mdat() {
+    /// A reader that records how many bytes have been read through it.
+    struct CountingReader {
+        inner: Cursor<Vec<u8>>,
+        bytes_read: usize,
+    }
+
+    impl std::io::Read for CountingReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = self.inner.read(buf)?;
+            self.bytes_read += n;
+            Ok(n)
+        }
+    }
+
+    impl std::io::Seek for CountingReader {
+        fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    const MDAT_SIZE: usize = 1 << 20;
+    let ftyp = make_box(BoxSize::Auto, b"ftyp", |s| {
+        s.append_bytes(b"isom").B32(0).append_bytes(b"isom")
+    })
+    .into_inner();
+    let mdat = make_box(BoxSize::Auto, b"mdat", |s| s.append_repeated(0, MDAT_SIZE)).into_inner();
+    let mvhd = make_fullbox(BoxSize::Auto, b"mvhd", 0, |s| {
+        s.B32(0).B32(0).B32(1000).B32(0).append_repeated(0, 80)
+    })
+    .into_inner();
+    let moov = make_box(BoxSize::Auto, b"moov", |s| s.append_bytes(&mvhd)).into_inner();
+
+    let mut file = Vec::new();
+    file.extend_from_slice(&ftyp);
+    file.extend_from_slice(&mdat);
+    file.extend_from_slice(&moov);
+    let mut reader = CountingReader {
+        inner: Cursor::new(file),
+        bytes_read: 0,
+    };
+
+    let context = super::read_mp4_seekable(&mut reader, ParseStrictness::Normal)
+        .expect("read_mp4_seekable failed");
+    assert_eq!(context.timescale, Some(super::MediaTimeScale(1000)));
+    assert!(reader.bytes_read < MDAT_SIZE);
+}
+
+/// An item of a synthetic image file built by [`make_heif`]
+struct TestItem<'a> {
+    id: u16,
+    item_type: &'a [u8; 4],
+    /// The content_type of 'mime' items
+    content_type: Option<&'a [u8]>,
+    data: &'a [u8],
+    /// 1-based indices into the properties, with whether they're essential
+    properties: &'a [(u8, bool)],
+}
+
+/// Build an image file whose item data is stored in its 'idat' box.
+/// `references` are `(reference_type, from_item_id, to_item_ids)`.
+fn make_heif(
+    brands: &[&[u8; 4]],
+    primary_item_id: u16,
+    items: &[TestItem],
+    references: &[(&[u8; 4], u16, &[u16])],
+    properties: &[Vec<u8>],
+) -> Vec<u8> {
+    make_heif_with_storage(
+        brands,
+        primary_item_id,
+        items,
+        references,
+        properties,
+        false,
+    )
+}
+
+/// Like [`make_heif`], storing the item data in an 'mdat' box following the
+/// 'meta' box if `in_mdat`.
+fn make_heif_with_storage(
+    brands: &[&[u8; 4]],
+    primary_item_id: u16,
+    items: &[TestItem],
+    references: &[(&[u8; 4], u16, &[u16])],
+    properties: &[Vec<u8>],
+    in_mdat: bool,
+) -> Vec<u8> {
+    let ftyp = make_box(BoxSize::Auto, b"ftyp", |mut s| {
+        s = s.append_bytes(brands[0]).B32(0);
+        for brand in brands {
+            s = s.append_bytes(*brand);
+        }
+        s
+    })
+    .into_inner();
+    let hdlr = make_fullbox(BoxSize::Auto, b"hdlr", 0, |s| {
+        s.B32(0).append_bytes(b"pict").append_repeated(0, 12).B8(0)
+    })
+    .into_inner();
+    let pitm = make_fullbox(BoxSize::Auto, b"pitm", 0, |s| s.B16(primary_item_id)).into_inner();
+    let iinf = make_fullbox(BoxSize::Auto, b"iinf", 0, |mut s| {
+        s = s.B16(items.len() as u16);
+        for item in items {
+            let infe = make_fullbox(BoxSize::Auto, b"infe", 2, |mut s| {
+                s = s.B16(item.id).B16(0).append_bytes(item.item_type).B8(0);
+                if let Some(content_type) = item.content_type {
+                    s = s.append_bytes(content_type).B8(0);
+                }
+                s
+            });
+            s = s.append_bytes(&infe.into_inner());
+        }
+        s
+    })
+    .into_inner();
+    let make_iloc = |base_offset: u32| {
+        make_fullbox(BoxSize::Auto, b"iloc", 1, |mut s| {
+            s = s.B8(0x44).B8(0).B16(items.len() as u16);
+            let construction_method = if in_mdat { 0 } else { 1 };
+            let mut offset = base_offset;
+            for item in items {
+                let len = item.data.len() as u32;
+                s = s
+                    .B16(item.id)
+                    .B16(construction_method)
+                    .B16(0)
+                    .B16(1)
+                    .B32(offset)
+                    .B32(len);
+                offset += len;
+            }
+            s
+        })
+        .into_inner()
+    };
+    let iref = make_fullbox(BoxSize::Auto, b"iref", 0, |mut s| {
+        for (reference_type, from_item_id, to_item_ids) in references {
+            let reference = make_box(BoxSize::Auto, reference_type, |mut s| {
+                s = s.B16(*from_item_id).B16(to_item_ids.len() as u16);
+                for to_item_id in to_item_ids.iter() {
+                    s = s.B16(*to_item_id);
+                }
+                s
+            });
+            s = s.append_bytes(&reference.into_inner());
+        }
+        s
+    })
+    .into_inner();
+    let ipco = make_box(BoxSize::Auto, b"ipco", |mut s| {
+        for property in properties {
+            s = s.append_bytes(property);
+        }
+        s
+    })
+    .into_inner();
+    let ipma = make_fullbox(BoxSize::Auto, b"ipma", 0, |mut s| {
+        s = s.B32(items.len() as u32);
+        for item in items {
+            s = s.B16(item.id).B8(item.properties.len() as u8);
+            for &(index, essential) in item.properties {
+                s = s.B8(if essential { 0x80 | index } else { index });
+            }
+        }
+        s
+    })
+    .into_inner();
+    let iprp = make_box(BoxSize::Auto, b"iprp", |s| {
+        s.append_bytes(&ipco).append_bytes(&ipma)
+    })
+    .into_inner();
+    let data_box = make_box(
+        BoxSize::Auto,
+        if in_mdat { b"mdat" } else { b"idat" },
+        |mut s| {
+            for item in items {
+                s = s.append_bytes(item.data);
+            }
+            s
+        },
+    )
+    .into_inner();
+    let make_meta = |iloc: &[u8]| {
+        make_fullbox(BoxSize::Auto, b"meta", 0, |mut s| {
+            s = s
+                .append_bytes(&hdlr)
+                .append_bytes(&pitm)
+                .append_bytes(&iinf);
+            s = s.append_bytes(iloc);
+            if !references.is_empty() {
+                s = s.append_bytes(&iref);
+            }
+            s = s.append_bytes(&iprp);
+            if !in_mdat {
+                s = s.append_bytes(&data_box);
+            }
+            s
+        })
+        .into_inner()
+    };
+
+    let mut meta = make_meta(&make_iloc(0));
+    if in_mdat {
+        // The data follows the 'meta' box and the 'mdat' box header, which
+        // doesn't change the size of the 'meta' box.
+        let data_offset = ftyp.len() + meta.len() + 8;
+        meta = make_meta(&make_iloc(data_offset as u32));
+    }
+    let mut file = ftyp;
+    file.extend_from_slice(&meta);
+    if in_mdat {
+        file.extend_from_slice(&data_box);
+    }
+    file
+}
+
+fn make_av1c_property() -> Vec<u8> {
+    make_box(BoxSize::Auto, b"av1C", |s| s.B8(0x81).B8(0).B8(0).B8(0)).into_inner()
+}
+
+fn make_ispe_property(width: u32, height: u32) -> Vec<u8> {
+    make_fullbox(BoxSize::Auto, b"ispe", 0, |s| s.B32(width).B32(height)).into_inner()
+}
+
+fn make_pixi_property() -> Vec<u8> {
+    make_fullbox(BoxSize::Auto, b"pixi", 0, |s| s.B8(3).B8(8).B8(8).B8(8)).into_inner()
+}
+
+/// A 2x2 'grid' primary item of 48x48 output, with its tiles referenced by
+/// the 'dimg' references `tile_ids`.
+fn make_avif_grid(tile_ids: &[u16]) -> Vec<u8> {
+    make_avif_grid_with_tiles(tile_ids, b"av01", false)
+}
+
+/// Like [`make_avif_grid`], with tiles of `tile_type` and the item data
+/// stored in 'mdat' if `in_mdat`.
+fn make_avif_grid_with_tiles(tile_ids: &[u16], tile_type: &[u8; 4], in_mdat: bool) -> Vec<u8> {
+    let properties = [
+        make_av1c_property(),
+        make_ispe_property(32, 32),
+        make_pixi_property(),
+        make_ispe_property(48, 48),
+    ];
+    let tile_properties = [(1, true), (2, false), (3, false)];
+    let tile = |id: u16, data: &'static [u8]| TestItem {
+        id,
+        item_type: tile_type,
+        content_type: None,
+        data,
+        properties: &tile_properties,
+    };
+    let items = [
+        TestItem {
+            id: 1,
+            item_type: b"grid",
+            content_type: None,
+            data: &[0, 0, 1, 1, 0, 48, 0, 48],
+            properties: &[(4, false)],
+        },
+        tile(2, &[2, 2]),
+        tile(3, &[3, 3]),
+        tile(4, &[4, 4]),
+        tile(5, &[5, 5]),
+    ];
+    make_heif_with_storage(
+        &[b"avif", b"mif1", b"miaf"],
+        1,
+        &items,
+        &[(b"dimg", 1, tile_ids)],
+        &properties,
+        in_mdat,
+    )
+}
+
+#[test]
+fn read_avif_grid() {
+    let file = make_avif_grid(&[2, 3, 5, 4]);
+    let context = super::read_avif(&mut Cursor::new(file), ParseStrictness::Normal)
+        .expect("read_avif failed");
+    assert!(context.unsupported_features.is_empty());
+    assert!(!context.primary_item_is_present());
+    assert_eq!(
+        context.primary_grid(),
+        Some(&super::ImageGrid {
+            rows: 2,
+            columns: 2,
+            output_width: 48,
+            output_height: 48,
+        })
+    );
+    // The properties of the grid item are those of the primary image.
+    let ispe = unsafe { &*context.spatial_extents_ptr().unwrap() };
+    assert_eq!(
+        *ispe,
+        super::ImageSpatialExtentsProperty {
+            image_width: 48,
+            image_height: 48,
+        }
+    );
+
+    let tiles: Vec<_> = context.grid_tiles().collect();
+    assert_eq!(tiles.len(), 4);
+    assert_eq!(tiles[0].data(), Some(&[2, 2][..]));
+    assert_eq!(tiles[2].data(), Some(&[5, 5][..]));
+    assert_eq!(tiles[3].data(), Some(&[4, 4][..]));
+    assert_eq!(
+        tiles[1].spatial_extents().unwrap(),
+        Some(&super::ImageSpatialExtentsProperty {
+            image_width: 32,
+            image_height: 32,
+        })
+    );
+    assert_eq!(tiles[1].bits_per_channel().unwrap(), [8, 8, 8]);
+}
+
+#[test]
+fn read_avif_grid_bad_tile_count() {
+    let file = make_avif_grid(&[2, 3, 4]);
+    match super::read_avif(&mut Cursor::new(file.clone()), ParseStrictness::Normal) {
+        Err(Error::InvalidData(s)) => assert_eq!(s, Status::GridBadTileCount),
+        r => panic!("unexpected result {:?}", r),
+    }
+
+    let context = super::read_avif(&mut Cursor::new(file), ParseStrictness::Permissive)
+        .expect("read_avif failed");
+    assert!(context.primary_grid().is_none());
+    assert_eq!(context.grid_tiles().count(), 0);
+    assert!(context.unsupported_features.contains(super::Feature::Grid));
+}
+
+#[test]
+fn read_avif_grid_bad_tile_type() {
+    let file = make_avif_grid_with_tiles(&[2, 3, 5, 4], b"hvc1", false);
+    match super::read_avif(&mut Cursor::new(file.clone()), ParseStrictness::Normal) {
+        Err(Error::InvalidData(s)) => assert_eq!(s, Status::ImageItemType),
+        r => panic!("unexpected result {:?}", r),
+    }
+
+    let context = super::read_avif(&mut Cursor::new(file), ParseStrictness::Permissive)
+        .expect("read_avif failed");
+    assert!(context.primary_grid().is_none());
+    assert!(context.unsupported_features.contains(super::Feature::Grid));
+}
+
+#[test]
+fn read_avif_lazy_grid() {
+    let file = make_avif_grid_with_tiles(&[2, 3, 5, 4], b"av01", true);
+    let mut stream = Cursor::new(file);
+    let context =
+        super::read_avif_lazy(&mut stream, ParseStrictness::Normal).expect("read_avif failed");
+    assert!(context.unsupported_features.is_empty());
+    assert_eq!(
+        context.primary_grid(),
+        Some(&super::ImageGrid {
+            rows: 2,
+            columns: 2,
+            output_width: 48,
+            output_height: 48,
+        })
+    );
+    let tiles: Vec<_> = context.grid_tiles().collect();
+    assert_eq!(tiles.len(), 4);
+    assert_eq!(tiles[0].data(), None);
+    assert_eq!(*tiles[2].read(&mut stream).unwrap().unwrap(), [5, 5]);
+
+    // The same file read eagerly
+    let context = super::read_avif(
+        &mut Cursor::new(stream.into_inner()),
+        ParseStrictness::Normal,
+    )
+    .expect("read_avif failed");
+    assert!(context.primary_grid().is_some());
+    let tile = context.grid_tiles().nth(2).expect("no tile");
+    assert_eq!(tile.data(), Some(&[5, 5][..]));
+}
+
+/// An 'av01' primary item of 10 bytes with a small size 'a1lx' property
+fn make_avif_a1lx(layer_sizes: [u16; 3]) -> Vec<u8> {
+    let a1lx = make_box(BoxSize::Auto, b"a1lx", |s| {
+        s.B8(0)
+            .B16(layer_sizes[0])
+            .B16(layer_sizes[1])
+            .B16(layer_sizes[2])
+    })
+    .into_inner();
+    let properties = [
+        make_av1c_property(),
+        make_ispe_property(32, 32),
+        make_pixi_property(),
+        a1lx,
+    ];
+    let items = [TestItem {
+        id: 1,
+        item_type: b"av01",
+        content_type: None,
+        data: &[0; 10],
+        properties: &[(1, true), (2, false), (3, false), (4, false)],
+    }];
+    make_heif(&[b"avif", b"mif1", b"miaf"], 1, &items, &[], &properties)
+}
+
+#[test]
+fn read_avif_a1lx() {
+    let file = make_avif_a1lx([2, 5, 0]);
+    let context = super::read_avif(&mut Cursor::new(file), ParseStrictness::Normal)
+        .expect("read_avif failed");
+    let range = |offset, length| super::LayerRange { offset, length };
+    assert_eq!(
+        context.primary_item_layer_ranges(),
+        [range(0, 2), range(2, 5), range(7, 3)]
+    );
+
+    let file = make_avif_a1lx([0, 0, 0]);
+    let context = super::read_avif(&mut Cursor::new(file), ParseStrictness::Normal)
+        .expect("read_avif failed");
+    assert_eq!(context.primary_item_layer_ranges(), [range(0, 10)]);
+}
+
+#[test]
+fn read_avif_a1lx_bad_layer_size() {
+    let file = make_avif_a1lx([4, 6, 0]);
+    match super::read_avif(&mut Cursor::new(file.clone()), ParseStrictness::Normal) {
+        Err(Error::InvalidData(s)) => assert_eq!(s, Status::A1lxBadLayerSize),
+        r => panic!("unexpected result {:?}", r),
+    }
+
+    let context = super::read_avif(&mut Cursor::new(file), ParseStrictness::Permissive)
+        .expect("read_avif failed");
+    assert!(context.primary_item_coded_data().is_some());
+    assert!(context.primary_item_layer_ranges().is_empty());
+}
+
+/// An 'av01' primary item described by an Exif item with `exif_data`, and
+/// an XMP item
+fn make_avif_metadata(exif_data: &[u8]) -> Vec<u8> {
+    let properties = [
+        make_av1c_property(),
+        make_ispe_property(32, 32),
+        make_pixi_property(),
+    ];
+    let items = [
+        TestItem {
+            id: 1,
+            item_type: b"av01",
+            content_type: None,
+            data: &[1, 1],
+            properties: &[(1, true), (2, false), (3, false)],
+        },
+        TestItem {
+            id: 2,
+            item_type: b"Exif",
+            content_type: None,
+            data: exif_data,
+            properties: &[],
+        },
+        TestItem {
+            id: 3,
+            item_type: b"mime",
+            content_type: Some(b"application/rdf+xml"),
+            data: b"<x:xmpmeta/>",
+            properties: &[],
+        },
+    ];
+    make_heif(
+        &[b"avif", b"mif1", b"miaf"],
+        1,
+        &items,
+        &[(b"cdsc", 2, &[1]), (b"cdsc", 3, &[1])],
+        &properties,
+    )
+}
+
+#[test]
+fn read_avif_metadata() {
+    let file = make_avif_metadata(b"\0\0\0\x06Exif\0\0MM\0\x2a");
+    let context = super::read_avif(&mut Cursor::new(file.clone()), ParseStrictness::Normal)
+        .expect("read_avif failed");
+    assert_eq!(context.exif_data(), Some(&b"MM\0\x2a"[..]));
+    assert_eq!(context.xmp_data(), Some(&b"<x:xmpmeta/>"[..]));
+
+    let exif = context.read_exif(&mut Cursor::new(&file)).unwrap().unwrap();
+    assert_eq!(exif.as_slice(), b"MM\0\x2a");
+    let xmp = context.read_xmp(&mut Cursor::new(&file)).unwrap().unwrap();
+    assert_eq!(xmp.as_slice(), b"<x:xmpmeta/>");
+}
+
+#[test]
+fn read_avif_metadata_bad_tiff_header_offset() {
+    let file = make_avif_metadata(b"\0\0\0\x07MM\0\x2a");
+    match super::read_avif(&mut Cursor::new(file.clone()), ParseStrictness::Normal) {
+        Err(Error::InvalidData(s)) => assert_eq!(s, Status::ExifBadTiffHeaderOffset),
+        r => panic!("unexpected result {:?}", r),
+    }
+
+    let context = super::read_avif(&mut Cursor::new(file), ParseStrictness::Permissive)
+        .expect("read_avif failed");
+    assert!(context.exif_data().is_none());
+    assert!(context.xmp_data().is_some());
+}
+
+#[test]
+fn read_avif_thumbnails() {
+    let nclx = make_box(BoxSize::Auto, b"colr", |s| {
+        s.append_bytes(b"nclx").B16(1).B16(13).B16(6).B8(0x80)
+    })
+    .into_inner();
+    let properties = [
+        make_av1c_property(),
+        make_ispe_property(32, 32),
+        make_pixi_property(),
+        make_ispe_property(8, 8),
+        nclx,
+    ];
+    let item = |id: u16, data: &'static [u8], properties: &'static [(u8, bool)]| TestItem {
+        id,
+        item_type: b"av01",
+        content_type: None,
+        data,
+        properties,
+    };
+    let items = [
+        item(1, &[1, 1], &[(1, true), (2, false), (3, false)]),
+        item(2, &[2], &[(1, true), (4, false), (3, false), (5, false)]),
+        // Missing 'ispe', so ignored
+        item(3, &[3], &[(1, true), (3, false)]),
+    ];
+    let file = make_heif(
+        &[b"avif", b"mif1", b"miaf"],
+        1,
+        &items,
+        &[(b"thmb", 2, &[1]), (b"thmb", 3, &[1])],
+        &properties,
+    );
+    let context = super::read_avif(&mut Cursor::new(file), ParseStrictness::Normal)
+        .expect("read_avif failed");
+    assert_eq!(context.primary_item_coded_data(), Some(&[1, 1][..]));
+    assert_eq!(context.thumbnails().count(), 1);
+    let thumbnail = context.thumbnails().next().expect("no thumbnail");
+    assert_eq!(thumbnail.data(), Some(&[2][..]));
+    let ispe = thumbnail.spatial_extents().unwrap();
+    assert_eq!(
+        format!("{:?}", ispe),
+        "Some(ImageSpatialExtentsProperty { image_width: 8, image_height: 8 })"
+    );
+    let av1c = thumbnail.av1_config().unwrap().expect("no av1C");
+    assert_eq!(av1c.raw_config.as_slice(), [0x81, 0, 0, 0]);
+    let nclx = thumbnail
+        .nclx_colour_information()
+        .expect("no nclx")
+        .unwrap();
+    assert_eq!(nclx.transfer_characteristics, 13);
+    assert!(thumbnail.icc_colour_information().is_none());
+    assert_eq!(thumbnail.bits_per_channel().unwrap(), [8, 8, 8]);
+}
+
+#[test]
+fn read_avif_auxiliary_images() {
+    let make_auxc_property = |aux_type: &[u8], aux_subtype: &[u8]| {
+        make_fullbox(BoxSize::Auto, b"auxC", 0, |s| {
+            s.append_bytes(aux_type).B8(0).append_bytes(aux_subtype)
+        })
+        .into_inner()
+    };
+    let properties = [
+        make_av1c_property(),
+        make_ispe_property(32, 32),
+        make_pixi_property(),
+        make_auxc_property(b"urn:mpeg:mpegB:cicp:systems:auxiliary:alpha", b""),
+        make_auxc_property(b"urn:mpeg:hevc:2015:auxid:2", &[1, 2]),
+    ];
+    let item = |id: u16, data: &'static [u8], properties: &'static [(u8, bool)]| TestItem {
+        id,
+        item_type: b"av01",
+        content_type: None,
+        data,
+        properties,
+    };
+    let items = [
+        item(1, &[1, 1], &[(1, true), (2, false), (3, false)]),
+        item(2, &[2], &[(1, true), (2, false), (3, false), (4, true)]),
+        item(3, &[3], &[(1, true), (2, false), (3, false), (5, true)]),
+    ];
+    let file = make_heif(
+        &[b"avif", b"mif1", b"miaf"],
+        1,
+        &items,
+        &[(b"auxl", 2, &[1]), (b"auxl", 3, &[1])],
+        &properties,
+    );
+    let context = super::read_avif(&mut Cursor::new(file), ParseStrictness::Normal)
+        .expect("read_avif failed");
+    assert_eq!(context.alpha_item_coded_data(), Some(&[2][..]));
+    let images: Vec<_> = context.auxiliary_images().collect();
+    assert_eq!(images.len(), 2);
+
+    let alpha = images[0].auxiliary_type().unwrap().expect("no auxC");
+    assert_eq!(
+        alpha.aux_type(),
+        b"urn:mpeg:mpegB:cicp:systems:auxiliary:alpha"
+    );
+    assert_eq!(images[0].data(), Some(&[2][..]));
+
+    let depth = images[1].auxiliary_type().unwrap().expect("no auxC");
+    assert_eq!(depth.aux_type(), b"urn:mpeg:hevc:2015:auxid:2");
+    assert_eq!(depth.aux_subtype(), [1, 2]);
+    assert_eq!(images[1].data(), Some(&[3][..]));
+    assert!(images[1].spatial_extents().unwrap().is_some());
+    assert!(images[1].av1_config().unwrap().is_some());
+    assert_eq!(images[1].bits_per_channel().unwrap(), [8, 8, 8]);
+}
+
+/// A primary item with a 'tmap' derived item whose data is `tmap_data`,
+/// with the item data in an 'mdat' if `in_mdat`
+fn make_avif_gain_map(tmap_data: &[u8], in_mdat: bool) -> Vec<u8> {
+    let nclx = make_box(BoxSize::Auto, b"colr", |s| {
+        s.append_bytes(b"nclx").B16(9).B16(16).B16(9).B8(0)
+    })
+    .into_inner();
+    let pixi_10_bit =
+        make_fullbox(BoxSize::Auto, b"pixi", 0, |s| s.B8(3).B8(10).B8(10).B8(10)).into_inner();
+    let properties = [
+        make_av1c_property(),
+        make_ispe_property(32, 32),
+        make_pixi_property(),
+        make_ispe_property(16, 16),
+        nclx,
+        pixi_10_bit,
+    ];
+    let items = [
+        TestItem {
+            id: 1,
+            item_type: b"av01",
+            content_type: None,
+            data: &[1, 1],
+            properties: &[(1, true), (2, false), (3, false)],
+        },
+        TestItem {
+            id: 2,
+            item_type: b"av01",
+            content_type: None,
+            data: &[2],
+            properties: &[(1, true), (4, false), (3, false)],
+        },
+        TestItem {
+            id: 3,
+            item_type: b"tmap",
+            content_type: None,
+            data: tmap_data,
+            properties: &[(2, false), (5, false), (6, false)],
+        },
+    ];
+    make_heif_with_storage(
+        &[b"avif", b"mif1", b"miaf"],
+        1,
+        &items,
+        &[(b"dimg", 3, &[1, 2])],
+        &properties,
+        in_mdat,
+    )
+}
+
+#[test]
+fn read_avif_gain_map() {
+    let tmap_data = Section::with_endian(Endian::Big)
+        .B8(0) // version
+        .B16(0) // minimum_version
+        .B16(0) // writer_version
+        .B8(0x40) // use_base_colour_space
+        .B32(0)
+        .B32(1) // base_hdr_headroom
+        .B32(13)
+        .B32(10) // alternate_hdr_headroom
+        .B32(-1i32 as u32)
+        .B32(2) // gain_map_min
+        .B32(3)
+        .B32(1) // gain_map_max
+        .B32(1)
+        .B32(1) // gamma
+        .B32(1)
+        .B32(64) // base_offset
+        .B32(1)
+        .B32(64) // alternate_offset
+        .get_contents()
+        .unwrap();
+    let file = make_avif_gain_map(&tmap_data, false);
+    let context = super::read_avif(&mut Cursor::new(&file), ParseStrictness::Normal)
+        .expect("read_avif failed");
+    assert_eq!(context.primary_item_coded_data(), Some(&[1, 1][..]));
+
+    let metadata = context.gain_map_metadata().expect("no gain map");
+    assert!(metadata.use_base_colour_space);
+    assert_eq!(
+        metadata.alternate_hdr_headroom,
+        super::UnsignedFraction {
+            numerator: 13,
+            denominator: 10,
+        }
+    );
+    assert_eq!(metadata.channel_count, 1);
+    assert_eq!(
+        metadata.channels[0].gain_map_min,
+        super::SignedFraction {
+            numerator: -1,
+            denominator: 2,
+        }
+    );
+    assert_eq!(metadata.channels[0].alternate_offset.denominator, 64);
+    assert_eq!(metadata.channels[1], super::GainMapChannel::default());
+
+    let image = context.gain_map_image().expect("no gain map image");
+    assert_eq!(image.data(), Some(&[2][..]));
+    let ispe = image.spatial_extents().unwrap();
+    assert_eq!(
+        format!("{:?}", ispe),
+        "Some(ImageSpatialExtentsProperty { image_width: 16, image_height: 16 })"
+    );
+    assert!(image.av1_config().unwrap().is_some());
+    let tmap = context.tone_map_item().expect("no tmap");
+    let nclx = tmap.nclx_colour_information().expect("no nclx").unwrap();
+    assert_eq!(nclx.transfer_characteristics, 16);
+    assert_eq!(tmap.bits_per_channel().unwrap(), [10, 10, 10]);
+
+    // The metadata is read from the 'mdat' even when the rest of it isn't.
+    let mut stream = Cursor::new(make_avif_gain_map(&tmap_data, true));
+    let context =
+        super::read_avif_lazy(&mut stream, ParseStrictness::Normal).expect("read_avif failed");
+    assert_eq!(context.gain_map_metadata(), Some(metadata));
+    let image = context.gain_map_image().expect("no gain map image");
+    assert!(image.data().is_none());
+    assert_eq!(*image.read(&mut stream).unwrap().unwrap(), [2]);
+}
+
+#[test]
+fn read_avif_gain_map_bad_metadata() {
+    // Truncated after the base_hdr_headroom
+    let file = make_avif_gain_map(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1], false);
+    match super::read_avif(&mut Cursor::new(file.clone()), ParseStrictness::Strict) {
+        Err(Error::InvalidData(s)) => assert_eq!(s, Status::TmapBadMetadata),
+        r => panic!("unexpected result {:?}", r),
+    }
+
+    // The gain map is dropped, leaving the primary image
+    let context = super::read_avif(&mut Cursor::new(file), ParseStrictness::Normal)
+        .expect("read_avif failed");
+    assert!(context.primary_item_coded_data().is_some());
+    assert!(context.gain_map_metadata().is_none());
+    assert!(context.gain_map_image().is_none());
+
+    // Unsupported versions are ignored
+    let file = make_avif_gain_map(&[1], false);
+    let context = super::read_avif(&mut Cursor::new(file), ParseStrictness::Strict)
+        .expect("read_avif failed");
+    assert!(context.gain_map_metadata().is_none());
+}
+
+/// An 'hvc1' primary item with an 'hvc1' thumbnail, with the 'hvcC'
+/// property unless `hvcc` is false
+fn make_heic(brands: &[&[u8; 4]], hvcc: bool) -> Vec<u8> {
+    let hvcc_property = make_box(BoxSize::Auto, b"hvcC", |s| {
+        s.B8(1)
+            .B8(1)
+            .B32(0x6000_0000)
+            .append_repeated(0, 6)
+            .B8(90)
+            .B16(0xf000)
+            .B8(0xfc)
+            .B8(0xfd)
+            .B8(0xf8)
+            .B8(0xf8)
+            .B16(0)
+            .B8(0x0f)
+            // Without NAL unit arrays
+            .B8(0)
+    })
+    .into_inner();
+    let properties = [
+        if hvcc {
+            hvcc_property
+        } else {
+            make_pixi_property()
+        },
+        make_ispe_property(32, 32),
+        make_pixi_property(),
+        make_ispe_property(8, 8),
+    ];
+    let item = |id: u16, data: &'static [u8], properties: &'static [(u8, bool)]| TestItem {
+        id,
+        item_type: b"hvc1",
+        content_type: None,
+        data,
+        properties,
+    };
+    let items = [
+        item(1, &[1, 1], &[(1, true), (2, false), (3, false)]),
+        item(2, &[2], &[(1, true), (4, false), (3, false)]),
+    ];
+    make_heif(brands, 1, &items, &[(b"thmb", 2, &[1])], &properties)
+}
+
+#[test]
+fn read_heif() {
+    let file = make_heic(&[b"heic", b"mif1", b"miaf"], true);
+    let context = super::read_heif(&mut Cursor::new(file), ParseStrictness::Strict)
+        .expect("read_heif failed");
+    assert_eq!(context.codec, super::ImageCodec::Hevc);
+    assert_eq!(context.primary_item_coded_data(), Some(&[1, 1][..]));
+    let hvcc = context
+        .primary_item_hevc_config()
+        .unwrap()
+        .expect("no hvcC");
+    assert_eq!(hvcc.raw_config.len(), 23);
+    assert_eq!(hvcc.general_profile_idc, 1);
+    assert_eq!(hvcc.general_level_idc, 90);
+    assert!(hvcc.nal_unit_arrays.is_empty());
+    let thumbnail = context.thumbnails().next().expect("no thumbnail");
+    assert_eq!(thumbnail.data(), Some(&[2][..]));
+    let thumbnail_hvcc = thumbnail.hevc_config().unwrap().expect("no hvcC");
+    assert_eq!(thumbnail_hvcc.raw_config, hvcc.raw_config);
+    assert!(thumbnail.av1_config().unwrap().is_none());
+}
+
+#[test]
+fn read_heif_missing_hvcc() {
+    let file = make_heic(&[b"heic", b"mif1", b"miaf"], false);
+    match super::read_heif(&mut Cursor::new(file.clone()), ParseStrictness::Normal) {
+        Err(Error::InvalidData(s)) => assert_eq!(s, Status::HvccMissing),
+        r => panic!("unexpected result {:?}", r),
+    }
+
+    let context = super::read_heif(&mut Cursor::new(file), ParseStrictness::Permissive)
+        .expect("read_heif failed");
+    assert!(context.primary_item_hevc_config().unwrap().is_none());
+    // The thumbnail can't be decoded without its 'hvcC'
+    assert_eq!(context.thumbnails().count(), 0);
+}
+
+#[test]
+fn read_heif_missing_heic_brand() {
+    let file = make_heic(&[b"mif1", b"miaf"], true);
+    match super::read_heif(&mut Cursor::new(file.clone()), ParseStrictness::Normal) {
+        Err(Error::InvalidData(s)) => assert_eq!(s, Status::MissingHeicOrHevcBrand),
+        r => panic!("unexpected result {:?}", r),
+    }
+
+    let context = super::read_heif(&mut Cursor::new(file), ParseStrictness::Permissive)
+        .expect("read_heif failed");
+    assert!(context.primary_item_coded_data().is_some());
+}
+
+#[test]
+fn read_avif_hvc1_item() {
+    let file = make_heic(&[b"heic", b"mif1", b"miaf"], true);
+    match super::read_avif(&mut Cursor::new(file), ParseStrictness::Permissive) {
+        Err(Error::InvalidData(s)) => assert_eq!(s, Status::ImageItemType),
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+#[test]
+fn read_infe() {
+    let mut stream = make_fullbox(BoxSize::Auto, b"infe", 2, |s| {
+        s.B16(1)
+            .B16(0)
+            .append_bytes(b"mime")
+            .append_bytes(b"name\0application/rdf+xml\0")
+    });
+    // Set the hidden flag, which is allowed even when parsing strictly
+    stream.get_mut()[11] = 1;
+    let read = |stream: &Cursor<Vec<u8>>, features: &mut super::UnsupportedFeatures| {
+        let mut stream = Cursor::new(stream.get_ref().clone());
+        let mut iter = super::BoxIter::new(&mut stream);
+        let mut stream = iter.next_box().unwrap().unwrap();
+        super::read_infe(&mut stream, ParseStrictness::Strict, features)
+    };
+    let mut features = super::UnsupportedFeatures::new();
+    let infe = read(&stream, &mut features).unwrap();
+    assert_eq!(infe.item_type.to_be_bytes(), *b"mime");
+    assert_eq!(infe.item_name.as_slice(), b"name");
+    assert_eq!(
+        infe.content_type.as_ref().map(|s| s.as_slice()),
+        Some(&b"application/rdf+xml"[..])
+    );
+    assert!(infe.hidden);
+    assert!(!infe.protected);
+    assert!(!features.contains(super::Feature::Ipro));
+
+    // Protected items are kept
+    stream.get_mut()[15] = 1;
+    let infe = read(&stream, &mut features).unwrap();
+    assert!(infe.protected);
+    assert!(features.contains(super::Feature::Ipro));
+
+    // Other flags are only allowed when not parsing strictly
+    stream.get_mut()[11] = 2;
+    match read(&stream, &mut features) {
+        Err(Error::InvalidData(s)) => assert_eq!(s, Status::InfeFlagsNonzero),
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+#[test]
+fn read_heif_items() {
+    let properties = [
+        make_av1c_property(),
+        make_ispe_property(32, 32),
+        make_pixi_property(),
+        make_ispe_property(8, 8),
+    ];
+    let item = |id: u16, data: &'static [u8], properties: &'static [(u8, bool)]| TestItem {
+        id,
+        item_type: b"av01",
+        content_type: None,
+        data,
+        properties,
+    };
+    // An image collection, of which only item 1 is processed
+    let items = [
+        item(1, &[1, 1], &[(1, true), (2, false), (3, false)]),
+        item(2, &[2], &[(1, true), (4, false), (3, false)]),
+        item(3, &[3], &[(1, true), (2, false)]),
+    ];
+    let file = make_heif(
+        &[b"avif", b"mif1", b"miaf"],
+        1,
+        &items,
+        &[(b"base", 3, &[1, 2])],
+        &properties,
+    );
+    let context = super::read_avif(&mut Cursor::new(&file), ParseStrictness::Normal)
+        .expect("read_avif failed");
+    let ids: Vec<u32> = context.items().map(|item| item.id()).collect();
+    assert_eq!(ids, [1, 2, 3]);
+
+    let item = context.item(3).expect("no item 3");
+    assert_eq!(item.item_type(), super::FourCC::from(*b"av01"));
+    assert!(item.name().is_empty());
+    assert!(item.content_type().is_none());
+    assert!(!item.is_hidden());
+    assert_eq!(item.data(), Some(&[3][..]));
+    let references: Vec<_> = item.references().map(|iref| iref.to_item_id).collect();
+    assert_eq!(references, [1, 2]);
+    assert_eq!(item.referenced_by().count(), 0);
+    let properties: Vec<_> = item
+        .properties()
+        .map(|a| (BoxType::from(a.property), a.essential))
+        .collect();
+    assert_eq!(
+        properties,
+        [
+            (BoxType::AV1CodecConfigurationBox, true),
+            (BoxType::ImageSpatialExtentsProperty, false)
+        ]
+    );
+
+    let item = context.item(2).expect("no item 2");
+    assert_eq!(item.data(), Some(&[2][..]));
+    assert_eq!(item.referenced_by().next().unwrap().from_item_id, 3);
+    assert_eq!(context.item_references().count(), 2);
+    assert!(context.item(4).is_none());
+
+    // Items which aren't processed are only located when asked for, and
+    // the items in 'idat' are always read
+    let mut stream = Cursor::new(file);
+    let context =
+        super::read_avif_lazy(&mut stream, ParseStrictness::Normal).expect("read_avif failed");
+    let item = context.item(3).expect("no item 3");
+    assert_eq!(item.data(), Some(&[3][..]));
+    assert!(item.extents().is_none());
+    assert_eq!(*item.read(&mut stream).unwrap().unwrap(), [3]);
+    let item = context.item(1).expect("no item 1");
+    assert_eq!(*item.read(&mut stream).unwrap().unwrap(), [1, 1]);
+}
+
+#[test]
+fn read_avcc() {
+    // A High profile 1920x1080 SPS with a 4:3 SAR and BT.709 colour
+    let sps = [
+        0x67, 0x64, 0x00, 0x28, 0xac, 0xd9, 0x40, 0x78, 0x02, 0x27, 0xe5, 0xff, 0xc0, 0x01, 0x00,
+        0x00, 0xda, 0x80, 0x80, 0x80, 0xa0,
+    ];
+    let pps = [0x68, 0xee, 0x3c, 0x80];
+    let mut stream = make_box(BoxSize::Auto, b"avcC", |s| {
+        s.B8(1)
+            .B8(100)
+            .B8(0)
+            .B8(40)
+            .B8(0xff)
+            .B8(0xe1)
+            .B16(sps.len() as u16)
+            .append_bytes(&sps)
+            .B8(1)
+            .B16(pps.len() as u16)
+            .append_bytes(&pps)
+            // 4:2:0 8-bit, without SPS extensions
+            .B8(0xfd)
+            .B8(0xf8)
+            .B8(0xf8)
+            .B8(0)
+    });
+    let mut iter = super::BoxIter::new(&mut stream);
+    let mut stream = iter.next_box().unwrap().unwrap();
+    let avcc = super::read_avcc(&mut stream, ParseStrictness::Strict).expect("read_avcc failed");
+    assert_eq!(avcc.profile_indication, 100);
+    assert_eq!(avcc.level_indication, 40);
+    assert_eq!(avcc.nal_unit_length_size, 4);
+    assert_eq!(avcc.sequence_parameter_sets.len(), 1);
+    assert_eq!(avcc.picture_parameter_sets[0].as_slice(), pps);
+    assert!(avcc.sequence_parameter_set_extensions.is_empty());
+
+    let sps = avcc.sps.expect("no SPS");
+    assert_eq!(sps.profile_idc, 100);
+    assert_eq!(sps.level_idc, 40);
+    assert_eq!(sps.chroma_format_idc, 1);
+    assert_eq!((sps.bit_depth_luma, sps.bit_depth_chroma), (8, 8));
+    assert_eq!((sps.coded_width, sps.coded_height), (1920, 1088));
+    assert_eq!(
+        sps.crop,
+        super::CropRectangle {
+            x: 0,
+            y: 0,
+            width: 1920,
+            height: 1080,
+        }
+    );
+    assert_eq!(sps.sample_aspect_ratio, Some((4, 3)));
+    let colour = sps.colour_description.expect("no colour description");
+    assert_eq!(colour.colour_primaries, 1);
+    assert_eq!(colour.transfer_characteristics, 1);
+    assert_eq!(colour.matrix_coefficients, 1);
+    assert!(!colour.full_range_flag);
+}
+
+#[test]
+fn read_avcc_bad_version() {
+    let make_avcc = || make_box(BoxSize::Auto, b"avcC", |s| s.append_repeated(0, 8));
+    let mut stream = make_avcc();
+    let mut iter = super::BoxIter::new(&mut stream);
+    let mut stream = iter.next_box().unwrap().unwrap();
+    match super::read_avcc(&mut stream, ParseStrictness::Strict) {
+        Err(Error::Unsupported(_)) => {}
+        r => panic!("unexpected result {:?}", r),
+    }
+
+    let mut stream = make_avcc();
+    let mut iter = super::BoxIter::new(&mut stream);
+    let mut stream = iter.next_box().unwrap().unwrap();
+    let avcc = super::read_avcc(&mut stream, ParseStrictness::Normal).expect("read_avcc failed");
+    assert_eq!(avcc.raw_config.len(), 8);
+    assert!(avcc.sps.is_none());
+}
+
+#[test]
+fn read_avc_sps_errors() {
+    // A Baseline profile 16x16 SPS cropped by 8 luma samples on the left
+    let sps = super::read_avc_sps(&[0x67, 0x42, 0x00, 0x1e, 0xdd, 0xf2, 0xf4]).unwrap();
+    assert_eq!(
+        sps.crop,
+        super::CropRectangle {
+            x: 8,
+            y: 0,
+            width: 8,
+            height: 16,
+        }
+    );
+
+    let sps_error = |nal_unit: &[u8]| match super::read_avc_sps(nal_unit) {
+        Err(Error::InvalidData(status)) => status,
+        r => panic!("unexpected result {:?}", r),
+    };
+    // A PPS
+    assert_eq!(
+        sps_error(&[0x68, 0xee, 0x3c, 0x80]),
+        Status::SpsBadNalUnitType
+    );
+    // A High profile SPS with chroma_format_idc 4
+    assert_eq!(
+        sps_error(&[0x67, 0x64, 0x00, 0x28, 0x94, 0x80]),
+        Status::SpsBadChromaFormat
+    );
+    // Also cropped by 8 luma samples on the right
+    assert_eq!(
+        sps_error(&[0x67, 0x42, 0x00, 0x1e, 0xdd, 0xf2, 0x97, 0x40]),
+        Status::SpsBadCrop
+    );
+}
+
+#[test]
+fn read_hvcc() {
+    // A Main profile 1920x1080 SPS with a 1:1 SAR and BT.2100 PQ colour,
+    // including emulation prevention bytes and an inter-predicted
+    // short-term reference picture set
+    let sps = [
+        0x42, 0x01, 0x01, 0x01, 0x60, 0x00, 0x00, 0x03, 0x00, 0x90, 0x00, 0x00, 0x03, 0x00, 0x00,
+        0x03, 0x00, 0x78, 0xa0, 0x03, 0xc0, 0x80, 0x11, 0x07, 0xcb, 0x96, 0x57, 0x92, 0x4d, 0x9a,
+        0xf7, 0x78, 0x0b, 0x50, 0x91, 0x00, 0x98,
+    ];
+    let vps = [0x40, 0x01, 0x0c];
+    let pps = [0x44, 0x01, 0xc1, 0x72, 0xb4, 0x62, 0x40];
+    let mut stream = make_box(BoxSize::Auto, b"hvcC", |s| {
+        s.B8(1)
+            .B8(0x21)
+            .B32(0x6000_0000)
+            .B32(0x9000_0000)
+            .B16(0)
+            .B8(120)
+            .B16(0xf000)
+            .B8(0xfc)
+            .B8(0xfd)
+            .B8(0xf8)
+            .B8(0xf8)
+            .B16(0)
+            .B8(0x0f)
+            .B8(3)
+            .B8(0x80 | 32)
+            .B16(1)
+            .B16(vps.len() as u16)
+            .append_bytes(&vps)
+            .B8(0x80 | 33)
+            .B16(1)
+            .B16(sps.len() as u16)
+            .append_bytes(&sps)
+            .B8(34)
+            .B16(1)
+            .B16(pps.len() as u16)
+            .append_bytes(&pps)
+    });
+    let mut iter = super::BoxIter::new(&mut stream);
+    let mut stream = iter.next_box().unwrap().unwrap();
+    let hvcc = super::read_hvcc(&mut stream, ParseStrictness::Strict).expect("read_hvcc failed");
+    assert_eq!(hvcc.general_profile_space, 0);
+    assert!(hvcc.general_tier_flag);
+    assert_eq!(hvcc.general_profile_idc, 1);
+    assert_eq!(hvcc.general_profile_compatibility_flags, 0x6000_0000);
+    assert_eq!(hvcc.general_constraint_indicator_flags, 0x9000_0000_0000);
+    assert_eq!(hvcc.general_level_idc, 120);
+    assert_eq!(hvcc.chroma_format_idc, 1);
+    assert_eq!((hvcc.bit_depth_luma, hvcc.bit_depth_chroma), (8, 8));
+    assert_eq!(hvcc.nal_unit_length_size, 4);
+    assert_eq!(hvcc.nal_unit_arrays.len(), 3);
+    assert!(hvcc.nal_unit_arrays[1].array_completeness);
+    assert!(!hvcc.nal_unit_arrays[2].array_completeness);
+    let nal_units = |nal_unit_type| hvcc.nal_units(nal_unit_type).collect::<Vec<_>>();
+    assert_eq!(
+        nal_units(super::HEVCConfigBox::NAL_UNIT_TYPE_VPS),
+        [&vps[..]]
+    );
+    assert_eq!(
+        nal_units(super::HEVCConfigBox::NAL_UNIT_TYPE_SPS),
+        [&sps[..]]
+    );
+    assert_eq!(
+        nal_units(super::HEVCConfigBox::NAL_UNIT_TYPE_PPS),
+        [&pps[..]]
+    );
+
+    let sps = hvcc.sps.expect("no SPS");
+    assert_eq!(sps.chroma_format_idc, 1);
+    assert_eq!((sps.bit_depth_luma, sps.bit_depth_chroma), (8, 8));
+    assert_eq!((sps.width, sps.height), (1920, 1088));
+    assert_eq!(
+        sps.conformance_window,
+        super::CropRectangle {
+            x: 0,
+            y: 0,
+            width: 1920,
+            height: 1080,
+        }
+    );
+    assert_eq!(sps.sample_aspect_ratio, Some((1, 1)));
+    let colour = sps.colour_description.expect("no colour description");
+    assert_eq!(colour.colour_primaries, 9);
+    assert_eq!(colour.transfer_characteristics, 16);
+    assert_eq!(colour.matrix_coefficients, 9);
+    assert!(!colour.full_range_flag);
+}
+
+#[test]
+fn read_hvcc_bad_sps() {
+    // A truncated SPS is tolerated even when parsing strictly
+    let sps = [0x42, 0x01, 0x01, 0x01, 0x60];
+    let mut stream = make_box(BoxSize::Auto, b"hvcC", |s| {
+        s.B8(1)
+            .B8(1)
+            .B32(0x6000_0000)
+            .append_repeated(0, 6)
+            .B8(90)
+            .B16(0xf000)
+            .B8(0xfc)
+            .B8(0xfd)
+            .B8(0xf8)
+            .B8(0xf8)
+            .B16(0)
+            .B8(0x0f)
+            .B8(1)
+            .B8(33)
+            .B16(1)
+            .B16(sps.len() as u16)
+            .append_bytes(&sps)
+    });
+    let mut iter = super::BoxIter::new(&mut stream);
+    let mut stream = iter.next_box().unwrap().unwrap();
+    let hvcc = super::read_hvcc(&mut stream, ParseStrictness::Strict).expect("read_hvcc failed");
+    assert_eq!(hvcc.nal_units(33).count(), 1);
+    assert!(hvcc.sps.is_none());
+}
+
+#[test]
+fn read_hevc_sps_errors() {
+    let sps_error = |nal_unit: &[u8]| match super::read_hevc_sps(nal_unit) {
+        Err(Error::InvalidData(status)) => status,
+        r => panic!("unexpected result {:?}", r),
+    };
+    // A VPS
+    assert_eq!(sps_error(&[0x40, 0x01, 0x0c]), Status::SpsBadNalUnitType);
+    // An SPS with chroma_format_idc 4 following its profile_tier_level
+    let mut sps = vec![0x42, 0x01, 0x01];
+    sps.extend_from_slice(&[0x01; 12]);
+    sps.extend_from_slice(&[0x96]);
+    assert_eq!(sps_error(&sps), Status::SpsBadChromaFormat);
+}
+
+#[test]
+fn nal_unit_rbsp() {
+    let rbsp = super::nal_unit_rbsp(&[0x67, 0, 0, 3, 1, 0, 0, 3, 0, 3], 1).unwrap();
+    assert_eq!(rbsp.as_slice(), [0, 0, 1, 0, 0, 0, 3]);
+}
@@ -249,10 +249,424 @@ pub fn create_sample_table(
         sample.start_decode = CheckedInteger(start_decode.0);
     }
 
-    // Correct composition end time due to 'ctts' causes composition time re-ordering.
-    //
-    // Composition end time is not in specification. However, gecko needs it, so we need to
-    // calculate to correct the composition end time.
+    fix_end_composition(&mut sample_table)?;
+
+    Some(sample_table)
+}
+
+/// Create a vector of `Indice`s with the information about the samples of a
+/// fragmented track. It walks the `trun` boxes of every `moof` in `context`
+/// that belong to `track`, resolving omitted sample fields from `tfhd` and the
+/// `trex` defaults in `mvex`, in the same form as `create_sample_table`.
+///
+/// Returns None if the data of a run of samples doesn't lie within the
+/// 'mdat' content it starts in, as far as it has been read.
+pub fn create_fragmented_sample_table(
+    context: &MediaContext,
+    track: &Track,
+    track_offset_time: CheckedInteger<i64>,
+) -> Option<TryVec<Indice>> {
+    // See ISOBMFF (ISO 14496-12:2020) § 8.8.3.1
+    const SAMPLE_IS_NON_SYNC_SAMPLE: u32 = 0x0001_0000;
+
+    let track_id = track.track_id?;
+    let trex_for = |id| {
+        context
+            .mvex
+            .as_ref()
+            .and_then(|mvex| mvex.track_extends(id))
+    };
+
+    let mut sample_table = TryVec::new();
+    let mut decode_time = CheckedInteger(0i64);
+
+    for moof in context.moofs.iter() {
+        // The end of the data of the preceding track fragment, which is the
+        // base data offset of the next one unless specified otherwise. It's
+        // None if the sizes of the preceding samples are unknown.
+        let mut previous_traf_end = None;
+        let mut follows_traf = false;
+
+        for traf in moof.trafs.iter() {
+            let tfhd = &traf.tfhd;
+            let trex = trex_for(tfhd.track_id);
+            let base_data_offset = match tfhd.base_data_offset {
+                Some(offset) => Some(offset),
+                None if follows_traf && !tfhd.default_base_is_moof => previous_traf_end,
+                None => Some(moof.offset),
+            };
+            let is_track = tfhd.track_id == track_id;
+            if is_track {
+                if let Some(time) = traf.base_media_decode_time {
+                    decode_time = CheckedInteger(i64::try_from(time).ok()?);
+                }
+            }
+
+            let mut cur_position = base_data_offset;
+            for trun in traf.truns.iter() {
+                if let Some(data_offset) = trun.data_offset {
+                    cur_position = base_data_offset
+                        .and_then(|offset| offset.checked_add_signed(data_offset.into()));
+                }
+                if trun.sample_count == 0 {
+                    continue;
+                }
+                let default_size = tfhd
+                    .default_sample_size
+                    .or_else(|| trex.map(|t| t.default_sample_size));
+                // The size of the data of a run whose samples all use the
+                // default size.
+                let default_run_size =
+                    || u64::from(default_size?).checked_mul(trun.sample_count.into());
+
+                // The samples of other tracks only matter for the base data
+                // offset of a following fragment, so they may lack sizes.
+                if !is_track {
+                    cur_position = cur_position.and_then(|position| {
+                        if trun.samples.is_empty() {
+                            return position.checked_add(default_run_size()?);
+                        }
+                        trun.samples.iter().try_fold(position, |position, sample| {
+                            position.checked_add(sample.size.or(default_size)?.into())
+                        })
+                    });
+                    continue;
+                }
+
+                // The samples must lie within the 'mdat' content the run
+                // starts in, which also bounds the number of samples of a
+                // run without per-sample fields.
+                let data_end = mdat_end(context, cur_position?)?;
+                if trun.samples.is_empty()
+                    && (default_size == Some(0)
+                        || cur_position?.checked_add(default_run_size()?)? > data_end)
+                {
+                    return None;
+                }
+
+                for i in 0..trun.sample_count.to_usize() {
+                    let sample = trun.sample(i);
+                    let size = sample.size.or(default_size);
+
+                    let start_offset = CheckedInteger(cur_position?);
+                    let end_offset = (start_offset + size?)?;
+                    if end_offset.0 > data_end {
+                        return None;
+                    }
+                    cur_position = Some(end_offset.0);
+
+                    let duration = sample
+                        .duration
+                        .or(tfhd.default_sample_duration)
+                        .or_else(|| trex.map(|t| t.default_sample_duration))
+                        .unwrap_or(0);
+                    let flags = trun
+                        .first_sample_flags
+                        .filter(|_| i == 0)
+                        .or(sample.flags)
+                        .or(tfhd.default_sample_flags)
+                        .or_else(|| trex.map(|t| t.default_sample_flags))
+                        .unwrap_or(0);
+                    let ctts_offset = sample.composition_time_offset.unwrap_or(0);
+
+                    let start_decode = decode_time;
+                    decode_time = (decode_time + i64::from(duration))?;
+                    let start_composition = ((start_decode + ctts_offset)? + track_offset_time)?;
+                    let end_composition = ((decode_time + ctts_offset)? + track_offset_time)?;
+
+                    sample_table
+                        .push(Indice {
+                            start_offset,
+                            end_offset,
+                            start_composition,
+                            end_composition,
+                            start_decode,
+                            sync: flags & SAMPLE_IS_NON_SYNC_SAMPLE == 0,
+                        })
+                        .ok()?;
+                }
+            }
+            previous_traf_end = cur_position;
+            follows_traf = true;
+        }
+    }
+
+    fix_end_composition(&mut sample_table)?;
+
+    Some(sample_table)
+}
+
+/// Create a vector of the Common Encryption parameters of the samples of a
+/// track, in the same order as the `Indice`s from `create_sample_table` or
+/// `create_fragmented_sample_table`.
+///
+/// The parameters are read from the 'senc' boxes of the track's 'stbl' or of
+/// its 'traf's, or else from the sample auxiliary information their 'saiz'
+/// and 'saio' boxes locate; samples of a 'traf' without either get empty
+/// entries. Returns None if the track has no such information, or isn't
+/// protected with a 'tenc'.
+pub fn create_sample_encryption_table(
+    context: &MediaContext,
+    track: &Track,
+) -> Option<Result<TryVec<SampleEncryptionEntry>>> {
+    let has_info = track.senc.is_some()
+        || track.aux_info.is_some()
+        || fragments_of(context, track)
+            .map(|mut trafs| trafs.any(|traf| traf.senc.is_some() || traf.aux_info.is_some()))
+            .unwrap_or(false);
+    if !has_info {
+        return None;
+    }
+    let parameters = match create_sample_encryption_parameters_table(context, track)? {
+        Ok(parameters) => parameters,
+        Err(e) => return Some(Err(e)),
+    };
+    // The 'tenc' used for any samples beyond those the parameters cover.
+    let tenc = track_encryption(track, 1)?;
+    let iv_size = |i: usize| parameters.get(i).map_or(tenc.iv_size, |p| p.iv_size);
+
+    if let Some(senc) = &track.senc {
+        return Some(senc.entries_with_iv_sizes(iv_size));
+    }
+    if let (Some(saiz), Some(aux_info)) = (&track.saiz, &track.aux_info) {
+        return Some(saiz.encryption_entries(aux_info, iv_size));
+    }
+    Some(create_fragmented_sample_encryption_table(
+        context, track, iv_size,
+    ))
+}
+
+fn create_fragmented_sample_encryption_table<F: Fn(usize) -> u8>(
+    context: &MediaContext,
+    track: &Track,
+    iv_size: F,
+) -> Result<TryVec<SampleEncryptionEntry>> {
+    let mut table = TryVec::new();
+    for traf in fragments_of(context, track).into_iter().flatten() {
+        let sample_count = traf_sample_count(context, traf)?;
+        let first = table.len();
+        let entries = match (&traf.senc, &traf.saiz, &traf.aux_info) {
+            (Some(senc), _, _) => {
+                if u64::from(senc.sample_count) != sample_count {
+                    return Status::SencBadSampleCount.into();
+                }
+                senc.entries_with_iv_sizes(|i| iv_size(first + i))?
+            }
+            (None, Some(saiz), Some(aux_info)) => {
+                if u64::from(saiz.sample_count) != sample_count {
+                    return Status::SaizBadSampleCount.into();
+                }
+                saiz.encryption_entries(aux_info, |i| iv_size(first + i))?
+            }
+            _ => {
+                for _ in 0..sample_count {
+                    table.push(SampleEncryptionEntry::default())?;
+                }
+                continue;
+            }
+        };
+        for entry in entries {
+            table.push(entry)?;
+        }
+    }
+    Ok(table)
+}
+
+/// Create a vector of the effective Common Encryption parameters of each
+/// sample of a track, in the same order as the `Indice`s from
+/// `create_sample_table` or `create_fragmented_sample_table`.
+///
+/// Each sample takes the 'tenc' of its sample description, unless a 'sbgp'
+/// maps it to a 'seig' sample group entry, as is done for key rotation.
+/// Returns None if the track isn't protected with a 'tenc'.
+pub fn create_sample_encryption_parameters_table<'a>(
+    context: &'a MediaContext,
+    track: &'a Track,
+) -> Option<Result<TryVec<&'a TrackEncryptionBox>>> {
+    let tenc = track_encryption(track, 1)?;
+    let seig_sgpd = seig_group_description(&track.sgpds);
+    // The parameters of samples outside any group.
+    let group_default = |tenc| match seig_sgpd {
+        Some(sgpd) => sgpd
+            .default_group_description_index
+            .and_then(|index| sgpd.seig_entry(index))
+            .unwrap_or(tenc),
+        None => tenc,
+    };
+    let resolve = |index: u32| {
+        seig_sgpd
+            .and_then(|sgpd| sgpd.seig_entry(index))
+            .ok_or(Error::InvalidData(Status::SbgpBadIndex))
+    };
+
+    let mut table = TryVec::new();
+    let fragments = match fragments_of(context, track) {
+        Some(trafs)
+            if track
+                .stts
+                .as_ref()
+                .is_none_or(|stts| stts.samples.is_empty()) =>
+        {
+            trafs
+        }
+        _ => {
+            let sample_count = track
+                .stts
+                .iter()
+                .flat_map(|stts| stts.samples.iter())
+                .map(|sample| u64::from(sample.sample_count))
+                .sum();
+            let result = push_group_parameters(
+                &mut table,
+                sample_count,
+                seig_sample_to_group(&track.sbgps),
+                resolve,
+                group_default(tenc),
+            );
+            return Some(result.map(|()| table));
+        }
+    };
+
+    let trex = context
+        .mvex
+        .as_ref()
+        .and_then(|mvex| mvex.track_extends(track.track_id?));
+    for traf in fragments {
+        let description_index = traf
+            .tfhd
+            .sample_description_index
+            .or_else(|| trex.map(|t| t.default_sample_description_index))
+            .unwrap_or(1);
+        let tenc = match track_encryption(track, description_index) {
+            Some(tenc) => tenc,
+            None => return Some(Err(Error::InvalidData(Status::Invalid))),
+        };
+        // In a 'traf', group description indexes above 0x10000 refer to its
+        // own 'sgpd' rather than the one in 'stbl'.
+        let traf_sgpd = seig_group_description(&traf.sgpds);
+        let resolve = |index: u32| match index.checked_sub(0x1_0000) {
+            Some(traf_index) if traf_index > 0 => traf_sgpd
+                .and_then(|sgpd| sgpd.seig_entry(traf_index))
+                .ok_or(Error::InvalidData(Status::SbgpBadIndex)),
+            _ => resolve(index),
+        };
+        let result = traf_sample_count(context, traf).and_then(|sample_count| {
+            push_group_parameters(
+                &mut table,
+                sample_count,
+                seig_sample_to_group(&traf.sbgps),
+                resolve,
+                group_default(tenc),
+            )
+        });
+        if let Err(e) = result {
+            return Some(Err(e));
+        }
+    }
+    Some(Ok(table))
+}
+
+/// Append the parameters of `sample_count` samples to `table`, as mapped to
+/// 'seig' group entries by `sbgp`.
+fn push_group_parameters<'a, F>(
+    table: &mut TryVec<&'a TrackEncryptionBox>,
+    sample_count: u64,
+    sbgp: Option<&SampleToGroupBox>,
+    resolve: F,
+    default: &'a TrackEncryptionBox,
+) -> Result<()>
+where
+    F: Fn(u32) -> Result<&'a TrackEncryptionBox>,
+{
+    let mut remaining = sample_count;
+    for entry in sbgp.iter().flat_map(|sbgp| sbgp.entries.iter()) {
+        let count = remaining.min(entry.sample_count.into());
+        let parameters = match entry.group_description_index {
+            0 => default,
+            index => resolve(index)?,
+        };
+        for _ in 0..count {
+            table.push(parameters)?;
+        }
+        remaining -= count;
+    }
+    for _ in 0..remaining {
+        table.push(default)?;
+    }
+    Ok(())
+}
+
+/// The end of the 'mdat' content of `context` which the file offset `offset`
+/// is in.
+fn mdat_end(context: &MediaContext, offset: u64) -> Option<u64> {
+    context
+        .mdats
+        .iter()
+        .find(|&&(start, end)| start <= offset && offset <= end)
+        .map(|&(_, end)| end)
+}
+
+/// The track fragments of `track`, or None if it has no track ID.
+fn fragments_of<'a>(
+    context: &'a MediaContext,
+    track: &Track,
+) -> Option<impl Iterator<Item = &'a TrackFragmentBox>> {
+    let track_id = track.track_id?;
+    Some(
+        context
+            .moofs
+            .iter()
+            .flat_map(|moof| moof.trafs.iter())
+            .filter(move |traf| traf.tfhd.track_id == track_id),
+    )
+}
+
+/// The number of samples of `traf`. Runs without per-sample fields may
+/// claim any number of samples, so as their samples need data of a nonzero
+/// default size, their count is bounded by the 'mdat' content of `context`.
+fn traf_sample_count(context: &MediaContext, traf: &TrackFragmentBox) -> Result<u64> {
+    let mut count = 0;
+    let mut default_count = 0;
+    for trun in traf.truns.iter() {
+        if trun.samples.is_empty() {
+            default_count += u64::from(trun.sample_count);
+        } else {
+            count += u64::from(trun.sample_count);
+        }
+    }
+    let mdat_size: u64 = context.mdats.iter().map(|&(start, end)| end - start).sum();
+    if default_count > mdat_size {
+        return Status::TrunBadSampleCount.into();
+    }
+    Ok(count + default_count)
+}
+
+fn seig_group_description(
+    sgpds: &TryVec<SampleGroupDescriptionBox>,
+) -> Option<&SampleGroupDescriptionBox> {
+    sgpds.iter().find(|sgpd| sgpd.grouping_type == b"seig")
+}
+
+fn seig_sample_to_group(sbgps: &TryVec<SampleToGroupBox>) -> Option<&SampleToGroupBox> {
+    sbgps.iter().find(|sbgp| sbgp.grouping_type == b"seig")
+}
+
+/// Find the 'tenc' of the 1-based `sample_description_index` of `track`.
+fn track_encryption(track: &Track, sample_description_index: u32) -> Option<&TrackEncryptionBox> {
+    let index = sample_description_index.checked_sub(1)?.to_usize();
+    let protection_info = match track.stsd.as_ref()?.descriptions.get(index)? {
+        SampleEntry::Audio(audio) => &audio.protection_info,
+        SampleEntry::Video(video) => &video.protection_info,
+        SampleEntry::Unknown => return None,
+    };
+    protection_info.iter().find_map(|sinf| sinf.tenc.as_ref())
+}
+
+// Correct composition end time due to 'ctts' causes composition time re-ordering.
+//
+// Composition end time is not in specification. However, gecko needs it, so we need to
+// calculate to correct the composition end time.
+fn fix_end_composition(sample_table: &mut TryVec<Indice>) -> Option<()> {
     if !sample_table.is_empty() {
         // Create an index table refers to sample_table and sorted by start_composisiton time.
         let mut sort_table = TryVec::with_capacity(sample_table.len()).ok()?;
@@ -275,7 +689,7 @@ pub fn create_sample_table(
         }
     }
 
-    Some(sample_table)
+    Some(())
 }
 
 // Convert a 'ctts' compact table to full table by iterator,
@@ -544,3 +958,363 @@ fn track_time_overflow() {
         Some(Microseconds(100_079_991_719_000_000u64))
     );
 }
+
+#[test]
+fn fragmented_sample_table() {
+    let run = |samples: &[TrackRunSample]| -> TrackRunBox {
+        let mut trun = TrackRunBox {
+            sample_count: samples.len().try_into().unwrap(),
+            ..Default::default()
+        };
+        for sample in samples {
+            trun.samples.push(*sample).unwrap();
+        }
+        trun
+    };
+    let sized = |size, composition_time_offset| TrackRunSample {
+        size: Some(size),
+        composition_time_offset: Some(composition_time_offset),
+        ..Default::default()
+    };
+
+    let mut audio = TrackFragmentBox {
+        tfhd: TrackFragmentHeaderBox {
+            track_id: 2,
+            default_sample_size: Some(10),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut audio_run = run(&[]);
+    audio_run.sample_count = 3;
+    audio_run.data_offset = Some(200);
+    audio.truns.push(audio_run).unwrap();
+
+    // The video fragment has no data offset, so its data follows the audio.
+    let mut video = TrackFragmentBox {
+        tfhd: TrackFragmentHeaderBox {
+            track_id: 1,
+            ..Default::default()
+        },
+        base_media_decode_time: Some(1000),
+        ..Default::default()
+    };
+    let mut video_run = run(&[sized(100, 100), sized(50, 200), sized(60, 0)]);
+    video_run.first_sample_flags = Some(0);
+    video.truns.push(video_run).unwrap();
+
+    let mut moof = MovieFragmentBox {
+        offset: 1000,
+        ..Default::default()
+    };
+    moof.trafs.push(audio).unwrap();
+    moof.trafs.push(video).unwrap();
+    // Samples of other tracks without sizes don't prevent building the table.
+    let mut other = TrackFragmentBox {
+        tfhd: TrackFragmentHeaderBox {
+            track_id: 3,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut other_run = run(&[]);
+    other_run.sample_count = 2;
+    other.truns.push(other_run).unwrap();
+    moof.trafs.push(other).unwrap();
+
+    let mut mvex = MovieExtendsBox {
+        fragment_duration: None,
+        trex: TryVec::new(),
+    };
+    mvex.trex
+        .push(TrackExtendsBox {
+            track_id: 1,
+            default_sample_duration: 100,
+            default_sample_flags: 0x0001_0000,
+            ..Default::default()
+        })
+        .unwrap();
+
+    let mut context = MediaContext {
+        mvex: Some(mvex),
+        ..Default::default()
+    };
+    context.moofs.push(moof).unwrap();
+    context.mdats.push((1200, 1440)).unwrap();
+
+    let track = Track {
+        track_id: Some(1),
+        ..Default::default()
+    };
+    let table = create_fragmented_sample_table(&context, &track, 0.into()).unwrap();
+    assert_eq!(table.len(), 3);
+
+    assert_eq!(table[0].start_offset, 1230);
+    assert_eq!(table[0].end_offset, 1330);
+    assert_eq!(table[1].start_offset, 1330);
+    assert_eq!(table[2].end_offset, 1440);
+
+    assert_eq!(table[0].start_decode, 1000);
+    assert_eq!(table[1].start_decode, 1100);
+    assert_eq!(table[0].start_composition, 1100);
+    assert_eq!(table[1].start_composition, 1300);
+    assert_eq!(table[2].start_composition, 1200);
+    // End times are corrected for the reordered composition times.
+    assert_eq!(table[0].end_composition, 1200);
+    assert_eq!(table[2].end_composition, 1300);
+
+    assert!(table[0].sync);
+    assert!(!table[1].sync);
+    assert!(!table[2].sync);
+}
+
+#[test]
+fn fragmented_sample_table_bounds() {
+    // A 'moof' whose runs use the default sample size, with the data of
+    // track 1 at the start of the 'mdat' and of track 2 following it.
+    let make_context = |sample_count, default_sample_size| {
+        let mut video = track_fragment(1, sample_count);
+        video.tfhd.default_sample_size = Some(default_sample_size);
+        video.truns[0].data_offset = Some(100);
+        let mut audio = track_fragment(2, u32::MAX);
+        audio.tfhd.default_sample_size = Some(1);
+        let mut moof = MovieFragmentBox {
+            offset: 900,
+            ..Default::default()
+        };
+        moof.trafs.push(video).unwrap();
+        moof.trafs.push(audio).unwrap();
+        let mut context = MediaContext::default();
+        context.moofs.push(moof).unwrap();
+        context.mdats.push((1000, 2000)).unwrap();
+        context
+    };
+    let track = Track {
+        track_id: Some(1),
+        ..Default::default()
+    };
+
+    // The run of track 2 is skipped over at once, though it's too long.
+    let context = make_context(2, 10);
+    let table = create_fragmented_sample_table(&context, &track, 0.into()).unwrap();
+    assert_eq!(table.len(), 2);
+    assert_eq!(table[1].end_offset, 1020);
+
+    // Runs of track 1 which don't fit in the 'mdat' are rejected up front.
+    let context = make_context(101, 10);
+    assert!(create_fragmented_sample_table(&context, &track, 0.into()).is_none());
+    let context = make_context(u32::MAX, 0);
+    assert!(create_fragmented_sample_table(&context, &track, 0.into()).is_none());
+}
+
+#[test]
+fn sample_group_parameters() {
+    let tenc = TrackEncryptionBox::default();
+    let seig = TrackEncryptionBox {
+        iv_size: 8,
+        ..Default::default()
+    };
+    let mut sbgp = SampleToGroupBox::default();
+    for (sample_count, group_description_index) in [(2, 1), (1, 0), (2, 1)] {
+        sbgp.entries
+            .push(SampleToGroupEntry {
+                sample_count,
+                group_description_index,
+            })
+            .unwrap();
+    }
+    let resolve = |index| match index {
+        1 => Ok(&seig),
+        _ => Err(Error::InvalidData(Status::SbgpBadIndex)),
+    };
+
+    // Samples beyond those mapped take the default, and mappings beyond the
+    // sample count are ignored.
+    let mut table = TryVec::new();
+    push_group_parameters(&mut table, 4, Some(&sbgp), resolve, &tenc).unwrap();
+    push_group_parameters(&mut table, 2, None, resolve, &tenc).unwrap();
+    let iv_sizes: std::vec::Vec<u8> = table.iter().map(|p| p.iv_size).collect();
+    assert_eq!(iv_sizes, [8, 8, 0, 8, 0, 0]);
+
+    sbgp.entries[1].group_description_index = 2;
+    match push_group_parameters(&mut table, 4, Some(&sbgp), resolve, &tenc) {
+        Err(Error::InvalidData(s)) => assert_eq!(s, Status::SbgpBadIndex),
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+/// A track with ID 1 protected with 8 byte IVs.
+#[cfg(test)]
+fn encrypted_track() -> Track {
+    let mut protection_info = TryVec::new();
+    protection_info
+        .push(ProtectionSchemeInfoBox {
+            tenc: Some(TrackEncryptionBox {
+                is_encrypted: 1,
+                iv_size: 8,
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .unwrap();
+    let mut descriptions = TryVec::new();
+    descriptions
+        .push(SampleEntry::Audio(AudioSampleEntry {
+            codec_type: CodecType::AAC,
+            data_reference_index: 1,
+            channelcount: 2,
+            samplesize: 16,
+            samplerate: 48000.0,
+            codec_specific: AudioCodecSpecific::LPCM,
+            protection_info,
+        }))
+        .unwrap();
+    Track {
+        track_id: Some(1),
+        stsd: Some(SampleDescriptionBox { descriptions }),
+        ..Default::default()
+    }
+}
+
+/// A track fragment with a single 'trun' of `sample_count` samples.
+#[cfg(test)]
+fn track_fragment(track_id: u32, sample_count: u32) -> TrackFragmentBox {
+    let mut traf = TrackFragmentBox {
+        tfhd: TrackFragmentHeaderBox {
+            track_id,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    traf.truns
+        .push(TrackRunBox {
+            sample_count,
+            ..Default::default()
+        })
+        .unwrap();
+    traf
+}
+
+#[test]
+fn fragmented_sample_encryption() {
+    let track = encrypted_track();
+    let bytes = |data: &[u8]| {
+        let mut bytes = TryVec::new();
+        bytes.extend_from_slice(data).unwrap();
+        bytes
+    };
+    let mut with_senc = track_fragment(1, 2);
+    with_senc.senc = Some(SampleEncryptionBox {
+        uses_subsamples: false,
+        sample_count: 2,
+        data: bytes(&[[1; 8], [2; 8]].concat()),
+    });
+    let mut with_aux_info = track_fragment(1, 1);
+    with_aux_info.saiz = Some(SampleAuxiliaryInformationSizesBox {
+        default_sample_info_size: 8,
+        sample_count: 1,
+        ..Default::default()
+    });
+    with_aux_info.aux_info = Some(bytes(&[3; 8]));
+
+    let mut moof = MovieFragmentBox::default();
+    moof.trafs.push(with_senc).unwrap();
+    // The fragments of other tracks and without information are counted
+    // separately.
+    moof.trafs.push(track_fragment(2, 5)).unwrap();
+    moof.trafs.push(track_fragment(1, 1)).unwrap();
+    moof.trafs.push(with_aux_info).unwrap();
+    let mut context = MediaContext::default();
+    context.moofs.push(moof).unwrap();
+    context.mdats.push((0, 100)).unwrap();
+
+    let table = create_sample_encryption_table(&context, &track)
+        .unwrap()
+        .unwrap();
+    let ivs: std::vec::Vec<&[u8]> = table.iter().map(|entry| &entry.iv[..]).collect();
+    assert_eq!(ivs, [&[1; 8][..], &[2; 8], &[], &[3; 8]]);
+
+    context.moofs[0].trafs[3].truns[0].sample_count = 2;
+    match create_sample_encryption_table(&context, &track).unwrap() {
+        Err(Error::InvalidData(s)) => assert_eq!(s, Status::SaizBadSampleCount),
+        r => panic!("unexpected result {:?}", r),
+    }
+    context.moofs[0].trafs[0].truns[0].sample_count = 3;
+    match create_sample_encryption_table(&context, &track).unwrap() {
+        Err(Error::InvalidData(s)) => assert_eq!(s, Status::SencBadSampleCount),
+        r => panic!("unexpected result {:?}", r),
+    }
+    // A large run of default samples without information
+    context.moofs[0].trafs[0].truns[0].sample_count = 2;
+    context.moofs[0].trafs[2].truns[0].sample_count = u32::MAX;
+    match create_sample_encryption_table(&context, &track).unwrap() {
+        Err(Error::InvalidData(s)) => assert_eq!(s, Status::TrunBadSampleCount),
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+#[test]
+fn fragmented_sample_encryption_parameters() {
+    let seig_group = |iv_size| {
+        let mut sgpd = SampleGroupDescriptionBox {
+            grouping_type: (*b"seig").into(),
+            ..Default::default()
+        };
+        sgpd.entries
+            .push(
+                SampleGroupDescriptionEntry::CencSampleEncryptionInformation(TrackEncryptionBox {
+                    is_encrypted: 1,
+                    iv_size,
+                    ..Default::default()
+                }),
+            )
+            .unwrap();
+        sgpd
+    };
+    let mut track = encrypted_track();
+    track.sgpds.push(seig_group(16)).unwrap();
+
+    // The samples map to the entry of the 'traf', the entry of the 'stbl'
+    // and no group, while the last sample isn't mapped.
+    let mut traf = track_fragment(1, 4);
+    traf.sgpds.push(seig_group(4)).unwrap();
+    let mut sbgp = SampleToGroupBox {
+        grouping_type: (*b"seig").into(),
+        ..Default::default()
+    };
+    for group_description_index in [0x1_0001, 1, 0] {
+        sbgp.entries
+            .push(SampleToGroupEntry {
+                sample_count: 1,
+                group_description_index,
+            })
+            .unwrap();
+    }
+    traf.sbgps.push(sbgp).unwrap();
+    let mut moof = MovieFragmentBox::default();
+    moof.trafs.push(traf).unwrap();
+    let mut context = MediaContext::default();
+    context.moofs.push(moof).unwrap();
+    context.mdats.push((0, 100)).unwrap();
+
+    let table = create_sample_encryption_parameters_table(&context, &track)
+        .unwrap()
+        .unwrap();
+    let iv_sizes: std::vec::Vec<u8> = table.iter().map(|p| p.iv_size).collect();
+    assert_eq!(iv_sizes, [4, 16, 8, 8]);
+
+    // The 'traf' has no second entry
+    context.moofs[0].trafs[0].sbgps[0].entries[0].group_description_index = 0x1_0002;
+    match create_sample_encryption_parameters_table(&context, &track).unwrap() {
+        Err(Error::InvalidData(s)) => assert_eq!(s, Status::SbgpBadIndex),
+        r => panic!("unexpected result {:?}", r),
+    }
+
+    // A run of default samples can't have more samples than the 'mdat' has
+    // bytes.
+    context.moofs[0].trafs[0].truns[0].sample_count = u32::MAX;
+    match create_sample_encryption_parameters_table(&context, &track).unwrap() {
+        Err(Error::InvalidData(s)) => assert_eq!(s, Status::TrunBadSampleCount),
+        r => panic!("unexpected result {:?}", r),
+    }
+}
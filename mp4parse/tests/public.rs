@@ -288,7 +288,15 @@ fn public_api() {
                 assert_eq!(
                     match v.codec_specific {
                         mp4::VideoCodecSpecific::AVCConfig(ref avc) => {
-                            assert!(!avc.is_empty());
+                            assert!(!avc.raw_config.is_empty());
+                            assert_eq!(avc.profile_indication, 100);
+                            assert_eq!(avc.nal_unit_length_size, 4);
+                            assert_eq!(avc.sequence_parameter_sets.len(), 1);
+                            assert_eq!(avc.picture_parameter_sets.len(), 1);
+                            let sps = avc.sps.as_ref().expect("expected an SPS");
+                            assert_eq!((sps.coded_width, sps.coded_height), (320, 240));
+                            assert_eq!(sps.chroma_format_idc, 1);
+                            assert_eq!(sps.bit_depth_luma, 8);
                             "AVC"
                         }
                         mp4::VideoCodecSpecific::VPxConfig(ref vpx) => {
@@ -310,7 +318,7 @@ fn public_api() {
                             "H263"
                         }
                         mp4::VideoCodecSpecific::HEVCConfig(ref hevc) => {
-                            assert!(!hevc.is_empty());
+                            assert!(!hevc.raw_config.is_empty());
                             "HEVC"
                         }
                     },
@@ -382,6 +390,33 @@ fn public_api() {
     }
 }
 
+#[test]
+fn public_push_parser() {
+    let mut fd = File::open(MINI_MP4).expect("Unknown file");
+    let mut buf = Vec::new();
+    fd.read_to_end(&mut buf).expect("File error");
+
+    let expected =
+        mp4::read_mp4(&mut Cursor::new(&buf), ParseStrictness::Normal).expect("read_mp4 failed");
+
+    let mut parser = mp4::Mp4PushParser::new(ParseStrictness::Normal);
+    let mut moov_ready = false;
+    for chunk in buf.chunks(1000) {
+        parser.push(chunk).expect("push failed");
+        loop {
+            match parser.next_event().expect("next_event failed") {
+                mp4::PushParseEvent::NeedMoreData => break,
+                mp4::PushParseEvent::MoovReady => moov_ready = true,
+                mp4::PushParseEvent::FragmentReady => panic!("unexpected fragment"),
+            }
+        }
+    }
+    assert!(moov_ready);
+    let context = parser.finish().expect("finish failed");
+    assert_eq!(context.timescale, expected.timescale);
+    assert_eq!(context.tracks.len(), expected.tracks.len());
+}
+
 #[test]
 fn public_metadata() {
     let mut fd = File::open(MINI_MP4_WITH_METADATA).expect("Unknown file");
@@ -870,6 +905,19 @@ fn public_video_av1() {
                 assert_eq!(av1c.chroma_sample_position, 0);
                 assert!(!av1c.initial_presentation_delay_present);
                 assert_eq!(av1c.initial_presentation_delay_minus_one, 0);
+                let sequence_header = av1c
+                    .sequence_header
+                    .as_ref()
+                    .expect("expected a sequence header");
+                assert_eq!(
+                    (
+                        sequence_header.max_frame_width,
+                        sequence_header.max_frame_height
+                    ),
+                    (64, 64)
+                );
+                assert_eq!(sequence_header.color_config.bit_depth, 8);
+                assert!(sequence_header.color_config.colour_information().is_none());
             }
             _ => panic!("Invalid test condition"),
         }
@@ -1017,6 +1065,43 @@ fn public_avif_unknown_mdat() {
     );
 }
 
+#[test]
+fn public_avif_lazy_matches_buffered() {
+    for path in &[
+        IMAGE_AVIF,
+        IMAGE_AVIF_EXTENTS,
+        IMAGE_AVIF_ALPHA,
+        IMAGE_AVIF_UNKNOWN_MDAT_SIZE,
+    ] {
+        let input = &mut File::open(path).expect("Unknown file");
+        let buffered = mp4::read_avif(input, ParseStrictness::Normal).expect("read_avif failed");
+
+        let input = &mut File::open(path).expect("Unknown file");
+        let lazy =
+            mp4::read_avif_lazy(input, ParseStrictness::Normal).expect("read_avif_lazy failed");
+        assert!(lazy.primary_item_coded_data().is_none());
+        assert!(lazy.primary_item_extents().is_some());
+
+        let input = &mut File::open(path).expect("Unknown file");
+        let primary = lazy
+            .read_primary_item(input)
+            .expect("no primary item")
+            .expect("read_primary_item failed");
+        assert_eq!(&*primary, buffered.primary_item_coded_data().unwrap());
+
+        match buffered.alpha_item_coded_data() {
+            Some(alpha) => {
+                let read = lazy
+                    .read_alpha_item(input)
+                    .expect("no alpha item")
+                    .expect("read_alpha_item failed");
+                assert_eq!(&*read, alpha);
+            }
+            None => assert!(lazy.read_alpha_item(input).is_none()),
+        }
+    }
+}
+
 #[test]
 fn public_avif_unknown_mdat_in_oversized_meta() {
     let input =
@@ -1092,7 +1177,7 @@ fn public_avif_clap_missing_essential() {
         if strictness == ParseStrictness::Strict {
             assert_eq!(Status::TxformNoEssential, Status::from(result));
         } else {
-            assert_unsupported_nonfatal(&result, mp4::Feature::Clap);
+            assert_clap_1x1_crop(result);
         }
     })
 }
@@ -1107,6 +1192,29 @@ fn public_avif_irot_missing_essential() {
     assert_avif_should(IMAGE_AVIF_IROT_MISSING_ESSENTIAL, Status::TxformNoEssential);
 }
 
+#[test]
+fn public_avif_display_geometry() {
+    // The 'clap' isn't associated, so the whole image is rotated 90 degrees
+    // anticlockwise, then mirrored about the horizontal axis
+    let input = &mut File::open(IMAGE_AVIF_IROT_MISSING_ESSENTIAL).expect("Unknown file");
+    let context = mp4::read_avif(input, ParseStrictness::Normal).expect("read_avif failed");
+    let geometry = context
+        .display_geometry()
+        .expect("display_geometry failed")
+        .expect("no display geometry");
+    assert_eq!(
+        geometry.crop,
+        mp4::CropRectangle {
+            x: 0,
+            y: 0,
+            width: 2048,
+            height: 858,
+        }
+    );
+    assert_eq!((geometry.width, geometry.height), (858, 2048));
+    assert_eq!(geometry.matrix, [0, 1, 0, 1, 0, 0]);
+}
+
 #[test]
 fn public_avif_ipma_bad_version() {
     assert_avif_should(IMAGE_AVIF_IPMA_BAD_VERSION, Status::IpmaBadVersion);
@@ -1217,52 +1325,28 @@ fn public_avif_transform_order() {
     assert_avif_shall(IMAGE_AVIF_TRANSFORM_ORDER, Status::TxformOrder);
 }
 
-#[allow(clippy::uninlined_format_args)]
-fn assert_unsupported_nonfatal(result: &mp4::Result<mp4::AvifContext>, feature: mp4::Feature) {
-    match result {
-        Ok(context) => {
-            assert!(
-                context.unsupported_features.contains(feature),
-                "context.unsupported_features missing expected {:?}",
-                feature
-            );
-        }
-        r => panic!(
-            "Expected Ok with unsupported_features containing {:?}, found {:?}",
-            feature, r
-        ),
-    }
-}
-
-// Assert that across all strictness levels the given feature is tracked as
-// being used, but unsupported. Additionally, if the feature is essential,
-// assert that the primary item is not processed unless using permissive mode.
-// TODO: Add similar tests for alpha
-fn assert_unsupported(path: &str, feature: mp4::Feature, essential: bool) {
-    for_strictness_result(path, |strictness, result| {
-        assert_unsupported_nonfatal(&result, feature);
-        match result {
-            Ok(context) if essential => assert_eq!(
-                context.primary_item_coded_data().is_some(),
-                strictness == ParseStrictness::Permissive
-            ),
-            Ok(context) if !essential => assert!(context.primary_item_coded_data().is_some()),
-            _ => panic!("Expected Ok, got {:?}", result),
-        }
-    });
-}
-
-fn assert_unsupported_nonessential(path: &str, feature: mp4::Feature) {
-    assert_unsupported(path, feature, false);
-}
-
-fn assert_unsupported_essential(path: &str, feature: mp4::Feature) {
-    assert_unsupported(path, feature, true);
-}
-
 #[test]
 fn public_avif_a1lx() {
-    assert_unsupported_nonessential(AVIF_A1LX, mp4::Feature::A1lx);
+    let expected = [
+        mp4::LayerRange {
+            offset: 0,
+            length: 122_336,
+        },
+        mp4::LayerRange {
+            offset: 122_336,
+            length: 1_877_167,
+        },
+    ];
+    for_strictness_result(AVIF_A1LX, |_, result| {
+        let context = result.expect("read_avif failed");
+        assert!(!context.unsupported_features.contains(mp4::Feature::A1lx));
+        assert!(context.primary_item_coded_data().is_some());
+        assert_eq!(context.primary_item_layer_ranges(), expected);
+    });
+
+    let input = &mut File::open(AVIF_A1LX).expect("Unknown file");
+    let context = mp4::read_avif_lazy(input, ParseStrictness::Normal).expect("read_avif failed");
+    assert_eq!(context.primary_item_layer_ranges(), expected);
 }
 
 #[test]
@@ -1272,7 +1356,12 @@ fn public_avif_a1lx_marked_essential() {
 
 #[test]
 fn public_avif_a1op() {
-    assert_unsupported_essential(AVIF_A1OP, mp4::Feature::A1op);
+    for_strictness_result(AVIF_A1OP, |_, result| {
+        let context = result.expect("read_avif failed");
+        assert!(!context.unsupported_features.contains(mp4::Feature::A1op));
+        assert!(context.primary_item_coded_data().is_some());
+        assert_eq!(context.primary_item_operating_point().unwrap(), Some(0));
+    });
 }
 
 #[test]
@@ -1282,7 +1371,12 @@ fn public_avif_a1op_missing_essential() {
 
 #[test]
 fn public_avif_lsel() {
-    assert_unsupported_essential(AVIF_LSEL, mp4::Feature::Lsel);
+    for_strictness_result(AVIF_LSEL, |_, result| {
+        let context = result.expect("read_avif failed");
+        assert!(!context.unsupported_features.contains(mp4::Feature::Lsel));
+        assert!(context.primary_item_coded_data().is_some());
+        assert_eq!(context.primary_item_layer_selection().unwrap(), Some(1));
+    });
 }
 
 #[test]
@@ -1290,9 +1384,7 @@ fn public_avif_lsel_missing_essential() {
     assert_avif_shall(IMAGE_AVIF_LSEL_MISSING_ESSENTIAL, Status::LselNoEssential);
 }
 
-// An lsel property with layer_id 0xffff does not select a specific layer, so the
-// item is still accepted (decodable, and lsel not reported as an unsupported
-// feature) regardless of strictness.
+// An lsel property with layer_id 0xffff does not select a specific layer
 #[test]
 fn public_avif_lsel_no_layer_selection() {
     for_strictness_result(
@@ -1307,6 +1399,7 @@ fn public_avif_lsel_no_layer_selection() {
                     context.primary_item_coded_data().is_some(),
                     "primary item associated with an lsel of layer_id 0xffff should be decodable"
                 );
+                assert_eq!(context.primary_item_layer_selection().unwrap(), None);
             }
             r => panic!("Expected Ok, found {:?}", r),
         },
@@ -1315,17 +1408,41 @@ fn public_avif_lsel_no_layer_selection() {
 
 #[test]
 fn public_avif_clap() {
-    assert_unsupported_essential(AVIF_CLAP, mp4::Feature::Clap);
+    for_strictness_result(AVIF_CLAP, |_, result| assert_clap_1x1_crop(result));
+}
+
+// Assert the result is the center pixel of a 3x3 image
+fn assert_clap_1x1_crop(result: mp4::Result<mp4::AvifContext>) {
+    let context = result.expect("read_avif failed");
+    assert!(!context.unsupported_features.contains(mp4::Feature::Clap));
+    assert!(context.primary_item_coded_data().is_some());
+    let crop = unsafe { context.clean_aperture_ptr().as_ref() };
+    assert_eq!(
+        crop,
+        Some(&mp4::CropRectangle {
+            x: 1,
+            y: 1,
+            width: 1,
+            height: 1,
+        })
+    );
 }
 
 #[test]
 fn public_avif_grid() {
     for file in &[AVIF_GRID, AVIF_GRID_A1LX] {
         let input = &mut File::open(file).expect(file);
-        assert_unsupported_nonfatal(
-            &mp4::read_avif(input, ParseStrictness::Normal),
-            mp4::Feature::Grid,
+        let context = mp4::read_avif(input, ParseStrictness::Normal).expect("read_avif failed");
+        assert!(!context.unsupported_features.contains(mp4::Feature::Grid));
+        assert!(!context.primary_item_is_present());
+        let grid = context.primary_grid().expect("no grid");
+        assert_eq!(
+            context.grid_tiles().count(),
+            usize::from(grid.rows) * usize::from(grid.columns)
         );
+        for tile in context.grid_tiles() {
+            assert!(tile.data().is_some());
+        }
     }
 }
 
@@ -1362,6 +1479,49 @@ fn public_avis_major_with_pitm_and_alpha() {
     }
 }
 
+#[test]
+fn public_avif_av1c_sequence_header() {
+    let input = &mut File::open(AVIF_AVIS_MAJOR_WITH_PITM_AND_ALPHA).expect("Unknown file");
+    let context = mp4::read_avif(input, ParseStrictness::Normal).expect("read_avif failed");
+    let av1c = context
+        .primary_item_av1_config()
+        .unwrap()
+        .expect("expected an av1C");
+    let sequence_header = av1c
+        .sequence_header
+        .as_ref()
+        .expect("expected a sequence header");
+    assert_eq!(sequence_header.seq_profile, 0);
+    assert_eq!(
+        (
+            sequence_header.max_frame_width,
+            sequence_header.max_frame_height
+        ),
+        (640, 480)
+    );
+    assert_eq!(sequence_header.operating_points.len(), 1);
+    assert_eq!(sequence_header.operating_points[0].seq_level_idx, 4);
+    assert!(!sequence_header.film_grain_params_present);
+    let colour = sequence_header
+        .color_config
+        .colour_information()
+        .expect("expected a colour description");
+    assert_eq!(colour.colour_primaries, 1);
+    assert_eq!(colour.transfer_characteristics, 13);
+    assert_eq!(colour.matrix_coefficients, 1);
+    assert!(!colour.full_range_flag);
+
+    let alpha_av1c = context
+        .alpha_item_av1_config()
+        .unwrap()
+        .expect("expected an alpha av1C");
+    let alpha_sequence_header = alpha_av1c
+        .sequence_header
+        .as_ref()
+        .expect("expected an alpha sequence header");
+    assert!(alpha_sequence_header.color_config.mono_chrome);
+}
+
 #[test]
 fn public_avif_avis_major_no_moov() {
     assert_avif_shall(AVIF_AVIS_MAJOR_NO_MOOV, Status::MoovMissing);
@@ -1541,6 +1701,43 @@ fn public_video_h263() {
     }
 }
 
+#[test]
+fn public_codec_strings() {
+    let files: [(&str, &[&str]); 7] = [
+        (MINI_MP4, &["avc1.64000d", "mp4a.40.2"]),
+        (VIDEO_HEVC_MP4, &["hev1.1.6.L90.90"]),
+        (VIDEO_AV1_MP4, &["av01.0.00M.08"]),
+        // Encrypted sample entries are described by their original format
+        (VIDEO_EME_CENC_MP4, &["avc1.4d401e"]),
+        (AUDIO_EME_CENC_MP4, &["mp4a.40.2"]),
+        (AUDIO_XHE_AAC_MP4, &["mp4a.40.42"]),
+        // HE-AAC with explicitly signalled SBR
+        (VIDEO_INVALID_USERDATA, &["avc1.64001f", "mp4a.40.5"]),
+    ];
+    for (path, expected) in files.iter() {
+        let mut fd = File::open(path).expect("Unknown file");
+        let mut buf = Vec::new();
+        fd.read_to_end(&mut buf).expect("File error");
+
+        let mut c = Cursor::new(&buf);
+        let context = mp4::read_mp4(&mut c, ParseStrictness::Normal).expect("read_mp4 failed");
+        let codec_strings: Vec<_> = context
+            .tracks
+            .iter()
+            .flat_map(|track| {
+                track
+                    .stsd
+                    .as_ref()
+                    .expect("expected an stsd")
+                    .descriptions
+                    .iter()
+            })
+            .map(|entry| entry.codec_string().expect("expected a codec string"))
+            .collect();
+        assert_eq!(&codec_strings, expected, "{path}");
+    }
+}
+
 #[test]
 fn public_video_hevc() {
     let mut fd = File::open(VIDEO_HEVC_MP4).expect("Unknown file");
@@ -1558,12 +1755,34 @@ fn public_video_hevc() {
         assert_eq!(v.codec_type, mp4::CodecType::HEVC);
         assert_eq!(v.width, 640);
         assert_eq!(v.height, 480);
-        let _codec_specific = match &v.codec_specific {
-            mp4::VideoCodecSpecific::HEVCConfig(_) => true,
+        let hvcc = match &v.codec_specific {
+            mp4::VideoCodecSpecific::HEVCConfig(hvcc) => hvcc,
             _ => {
                 panic!("expected a HEVCConfig",);
             }
         };
+        assert_eq!(hvcc.general_profile_idc, 1);
+        assert_eq!(hvcc.chroma_format_idc, 1);
+        assert_eq!(hvcc.nal_unit_length_size, 4);
+        assert_eq!(
+            hvcc.nal_units(mp4::HEVCConfigBox::NAL_UNIT_TYPE_VPS)
+                .count(),
+            1
+        );
+        assert_eq!(
+            hvcc.nal_units(mp4::HEVCConfigBox::NAL_UNIT_TYPE_SPS)
+                .count(),
+            1
+        );
+        assert_eq!(
+            hvcc.nal_units(mp4::HEVCConfigBox::NAL_UNIT_TYPE_PPS)
+                .count(),
+            1
+        );
+        let sps = hvcc.sps.as_ref().expect("expected an SPS");
+        assert_eq!((sps.width, sps.height), (640, 480));
+        assert_eq!((sps.bit_depth_luma, sps.bit_depth_chroma), (8, 8));
+        assert_eq!(sps.sample_aspect_ratio, Some((1, 1)));
     }
 }
 
@@ -41,18 +41,23 @@ use std::convert::TryFrom;
 use std::convert::TryInto;
 use std::hash::Hash;
 
-use std::io::Read;
+use std::io::{Read, Seek, SeekFrom};
 
 // Symbols we need from our rust api.
 use mp4parse::serialize_opus_header;
-use mp4parse::unstable::{create_sample_table, CheckedInteger, Indice};
+use mp4parse::unstable::{
+    create_fragmented_sample_table, create_sample_encryption_parameters_table,
+    create_sample_encryption_table, create_sample_table, CheckedInteger, Indice,
+};
 use mp4parse::AV1ConfigBox;
 use mp4parse::AudioCodecSpecific;
 use mp4parse::AvifContext;
 use mp4parse::CodecType;
+use mp4parse::HeifItem;
 use mp4parse::MediaContext;
 // Re-exported so consumers don't have to depend on mp4parse as well
 pub use mp4parse::ParseStrictness;
+use mp4parse::SampleEncryptionEntry;
 use mp4parse::SampleEntry;
 pub use mp4parse::Status as Mp4parseStatus;
 use mp4parse::Track;
@@ -108,6 +113,8 @@ pub enum Mp4parseCodec {
     #[cfg(feature = "3gpp")]
     AMRWB,
     XHEAAC, // xHE-AAC (Extended High Efficiency AAC)
+    /// Dolby Vision without a base layer other decoders can present
+    DolbyVision,
 }
 
 #[repr(C)]
@@ -202,6 +209,116 @@ pub struct Mp4parsePsshInfo {
     pub data: Mp4parseByteData,
 }
 
+/// A 'pssh' box from the 'moov' or a 'moof'.
+#[repr(C)]
+#[derive(Debug)]
+pub struct Mp4parsePssh {
+    pub system_id: [u8; 16],
+    pub version: u8,
+    /// The number of 16-byte key IDs in `kids`. Only version 1 boxes have
+    /// key IDs.
+    pub kid_count: u32,
+    pub kids: Mp4parseByteData,
+    /// The opaque, system specific payload.
+    pub data: Mp4parseByteData,
+    /// Whether the box was delivered in a 'moof' rather than the 'moov'.
+    pub in_moof: bool,
+    /// File offset of the 'moof' the box was delivered in, if `in_moof`.
+    pub moof_offset: u64,
+}
+
+/// The 'pssh' boxes of a file, in file order.
+#[repr(C)]
+#[derive(Debug)]
+pub struct Mp4parsePsshTable {
+    pub pssh_count: usize,
+    pub psshs: *const Mp4parsePssh,
+}
+
+impl Default for Mp4parsePsshTable {
+    fn default() -> Self {
+        Self {
+            pssh_count: 0,
+            psshs: std::ptr::null(),
+        }
+    }
+}
+
+/// Entries returned to C as a pointer and count, with the storage their own
+/// pointers refer to, other than the parser's `MediaContext`. The storage is
+/// only read through those pointers, so it must not move or be dropped
+/// before the parser is freed.
+struct PointerTable<T, S> {
+    entries: TryVec<T>,
+    _storage: S,
+}
+
+impl<T, S> PointerTable<T, S> {
+    /// The count and pointer of the entries, which is null if there are none.
+    fn as_raw_parts(&self) -> (usize, *const T) {
+        if self.entries.is_empty() {
+            (0, std::ptr::null())
+        } else {
+            (self.entries.len(), self.entries.as_ptr())
+        }
+    }
+}
+
+/// The 'pssh' boxes, with the key IDs of every box back to back.
+type PsshTable = PointerTable<Mp4parsePssh, TryVec<u8>>;
+
+/// The size of a clear range followed by a protected range of a sample.
+#[repr(C)]
+#[derive(Default, Debug)]
+pub struct Mp4parseSubsample {
+    pub bytes_of_clear_data: u16,
+    pub bytes_of_protected_data: u32,
+}
+
+/// The Common Encryption parameters of a sample.
+#[repr(C)]
+#[derive(Debug)]
+pub struct Mp4parseSampleEncryption {
+    /// Empty when the sample uses `constant_iv`, or the track has no 'senc'.
+    pub iv: Mp4parseByteData,
+    /// Zero when the whole sample is protected.
+    pub subsample_count: u32,
+    pub subsamples: *const Mp4parseSubsample,
+    /// The effective defaults for the sample, from the 'tenc' of the track
+    /// or a 'seig' sample group entry. This and the following fields have
+    /// the same meaning as in `Mp4parseSinfInfo`.
+    pub is_encrypted: u8,
+    pub iv_size: u8,
+    pub kid: Mp4parseByteData,
+    pub crypt_byte_block: u8,
+    pub skip_byte_block: u8,
+    pub constant_iv: Mp4parseByteData,
+}
+
+/// The Common Encryption parameters of the samples of a track, in the same
+/// order as its `mp4parse_get_indice_table` entries.
+#[repr(C)]
+#[derive(Debug)]
+pub struct Mp4parseSampleEncryptionTable {
+    pub sample_count: usize,
+    pub samples: *const Mp4parseSampleEncryption,
+}
+
+impl Default for Mp4parseSampleEncryptionTable {
+    fn default() -> Self {
+        Self {
+            sample_count: 0,
+            samples: std::ptr::null(),
+        }
+    }
+}
+
+/// The samples of a track, with their decoded entries and subsamples.
+type SampleEncryptionTable = PointerTable<
+    Mp4parseSampleEncryption,
+    (TryVec<SampleEncryptionEntry>, TryVec<Mp4parseSubsample>),
+>;
+
 #[repr(u8)]
 #[derive(Debug, PartialEq, Eq, Default)]
 pub enum OptionalFourCc {
@@ -308,6 +425,76 @@ pub struct Mp4parseTrackVideoSampleInfo {
     /// True when a `clli` box was present. When false, `content_light_level` must not be read.
     pub has_content_light_level: bool,
     pub content_light_level: Mp4parseContentLightLevel,
+    /// True when a valid `clap` box was present. When false, `clean_aperture`
+    /// must not be read.
+    pub has_clean_aperture: bool,
+    /// The region of `image_width` by `image_height` to display.
+    pub clean_aperture: mp4parse::CropRectangle,
+    /// True when the `avcC` box of an AVC track could be parsed. When false,
+    /// `avc_config` must not be read.
+    pub has_avc_config: bool,
+    pub avc_config: Mp4parseAvcConfig,
+    /// True when a `dvcC`, `dvvC` or `dvwC` box was present. When false,
+    /// `dolby_vision_config` must not be read.
+    pub has_dolby_vision_config: bool,
+    pub dolby_vision_config: Mp4parseDolbyVisionConfig,
+}
+
+/// The decoder configuration of an AVC track from its `avcC` box
+/// (ISO 14496-15 § 5.3.2.1) and, when `has_sps`, the first sequence
+/// parameter set in it (ITU-T H.264 § 7.3.2.1.1).
+#[repr(C)]
+#[derive(Default, Debug)]
+pub struct Mp4parseAvcConfig {
+    pub profile_indication: u8,
+    pub profile_compatibility: u8,
+    pub level_indication: u8,
+    /// The size in bytes of the length field preceding each NAL unit.
+    pub nal_unit_length_size: u8,
+    pub sps_count: u32,
+    pub pps_count: u32,
+    pub sps_ext_count: u32,
+    /// True when the first SPS could be parsed. When false, the fields below
+    /// are all zero and must not be read.
+    pub has_sps: bool,
+    /// 0 for monochrome, 1 for 4:2:0, 2 for 4:2:2 and 3 for 4:4:4.
+    pub chroma_format_idc: u8,
+    pub bit_depth_luma: u8,
+    pub bit_depth_chroma: u8,
+    /// The size of the decoded pictures.
+    pub coded_width: u32,
+    pub coded_height: u32,
+    /// The region of the decoded pictures to output.
+    pub crop: mp4parse::CropRectangle,
+    /// True when the VUI specifies the sample aspect ratio. When false,
+    /// `sar_width` and `sar_height` are zero.
+    pub has_sample_aspect_ratio: bool,
+    pub sar_width: u16,
+    pub sar_height: u16,
+    /// True when the VUI has a colour description. When false, the CICP
+    /// fields below are all zero and must not be interpreted.
+    pub has_colour_description: bool,
+    pub colour_primaries: u8,
+    pub transfer_characteristics: u8,
+    pub matrix_coefficients: u8,
+    pub full_range_flag: bool,
+}
+
+/// The Dolby Vision decoder configuration record of a `dvcC`, `dvvC` or
+/// `dvwC` box. The track's `codec_type` is that of the base layer, unless
+/// only Dolby Vision decoders can present it.
+#[repr(C)]
+#[derive(Default, Debug)]
+pub struct Mp4parseDolbyVisionConfig {
+    pub dv_version_major: u8,
+    pub dv_version_minor: u8,
+    pub dv_profile: u8,
+    pub dv_level: u8,
+    pub rpu_present: bool,
+    pub el_present: bool,
+    pub bl_present: bool,
+    /// 0 when the base layer isn't compatible with other decoders.
+    pub dv_bl_signal_compatibility_id: u8,
 }
 
 #[repr(C)]
@@ -339,8 +526,50 @@ impl Default for Mp4parseTrackVideoInfo {
 pub struct Mp4parseFragmentInfo {
     pub fragment_duration: u64, // in ticks
     pub time_scale: u64,
-    // TODO:
-    // info in trex box.
+    // Per-track defaults from the trex box are available through
+    // `mp4parse_get_track_extends`.
+}
+
+/// The defaults for samples in movie fragments of a track, from its 'trex'
+/// box.
+#[repr(C)]
+#[derive(Default, Debug)]
+pub struct Mp4parseTrackExtends {
+    pub default_sample_description_index: u32,
+    pub default_sample_duration: u32,
+    pub default_sample_size: u32,
+    pub default_sample_flags: u32,
+}
+
+/// A media subsegment from the segment index ('sidx') of a track.
+#[repr(C)]
+#[derive(Default, Debug)]
+pub struct Mp4parseSegment {
+    /// File offset of the first byte of the subsegment.
+    pub offset: u64,
+    pub size: u64,
+    /// Earliest presentation time, in the timescale of the segment index.
+    pub start_time: u64,
+    pub duration: u64,
+    pub starts_with_sap: bool,
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct Mp4parseSegmentIndex {
+    pub timescale: u64,
+    pub segment_count: usize,
+    pub segments: *const Mp4parseSegment,
+}
+
+impl Default for Mp4parseSegmentIndex {
+    fn default() -> Self {
+        Self {
+            timescale: 0,
+            segment_count: 0,
+            segments: std::ptr::null(),
+        }
+    }
 }
 
 /// Parser state for MP4 files, exposed to C callers via raw pointer.
@@ -366,6 +595,7 @@ pub struct Mp4parseParser {
     context: MediaContext,
     opus_header: TryHashMap<(u32, usize), TryVec<u8>>,
     pssh_data: Option<TryVec<u8>>,
+    pssh_table: Option<PsshTable>,
     sample_table: TryHashMap<u32, TryVec<Indice>>,
     // Store a mapping from track index (not id) to associated sample
     // descriptions. Because each track has a variable number of sample
@@ -373,6 +603,11 @@ pub struct Mp4parseParser {
     // copied out by callers, we store these on the parser struct.
     audio_track_sample_descriptions: TryHashMap<u32, TryVec<Mp4parseTrackAudioSampleInfo>>,
     video_track_sample_descriptions: TryHashMap<u32, TryVec<Mp4parseTrackVideoSampleInfo>>,
+    // Codec strings by track index and sample description index.
+    codec_strings: TryHashMap<(u32, usize), TryVec<u8>>,
+    sample_encryption_table: TryHashMap<u32, SampleEncryptionTable>,
+    // Segment indexes by track id, with their timescale.
+    segment_indexes: TryHashMap<u32, (u64, TryVec<Mp4parseSegment>)>,
 }
 
 #[repr(C)]
@@ -389,6 +624,11 @@ pub enum Mp4parseAvifLoopMode {
 pub struct Mp4parseAvifInfo {
     pub premultiplied_alpha: bool,
     pub major_brand: [u8; 4],
+    /// The bits of the unsupported `Feature`s encountered. 'a1op' and 'lsel'
+    /// properties aren't reported, even when essential, since
+    /// `mp4parse_avif_get_layers` returns the operating point and layer they
+    /// select; consumers which don't call it and decode all the layers will
+    /// render images with these properties wrongly.
     pub unsupported_features_bitfield: u32,
     /// The size of the image; should never be null unless using permissive parsing
     pub spatial_extents: *const mp4parse::ImageSpatialExtentsProperty,
@@ -424,6 +664,133 @@ pub struct Mp4parseAvifInfo {
     /// The track ID of the alpha track, will be 0 if no alpha track is present.
     pub alpha_track_id: u32,
     pub alpha_track_bit_depth: u8,
+
+    /// Whether the item referenced by `pitm` is a 'grid' of tiles rather than
+    /// a coded image, in which case `has_primary_item` is false. See
+    /// `mp4parse_avif_get_grid`.
+    pub has_grid: bool,
+    /// The region of the image to display, from its 'clap' property, or null
+    /// if it should be displayed whole.
+    pub clean_aperture: *const mp4parse::CropRectangle,
+    /// The number of thumbnails of the primary item. See
+    /// `mp4parse_avif_get_thumbnail`.
+    pub thumbnail_count: u32,
+    /// The number of auxiliary images of the primary item, including any
+    /// alpha item. See `mp4parse_avif_get_auxiliary_image`.
+    pub auxiliary_image_count: u32,
+    /// Whether there is a gain map to render the primary item as HDR. See
+    /// `mp4parse_avif_get_gain_map`.
+    pub has_gain_map: bool,
+}
+
+/// The layout of a 'grid' primary item. Its tiles are placed on a canvas of
+/// `columns` * `rows` tiles in raster order, which is then cropped to
+/// `output_width` by `output_height`.
+#[repr(C)]
+#[derive(Debug, Default)]
+pub struct Mp4parseAvifGrid {
+    pub rows: u32,
+    pub columns: u32,
+    pub output_width: u32,
+    pub output_height: u32,
+    pub tile_count: u32,
+}
+
+/// A coded image item related to the primary item, such as a tile of a
+/// 'grid' primary item, a thumbnail or a gain map image.
+#[repr(C)]
+#[derive(Debug)]
+pub struct Mp4parseAvifImageItem {
+    pub coded_data: Mp4parseByteData,
+    pub spatial_extents: *const mp4parse::ImageSpatialExtentsProperty,
+    /// The contents of the 'av1C' property
+    pub av1_config: Mp4parseByteData,
+    pub nclx_colour_information: *const mp4parse::NclxColourInformation,
+    pub icc_colour_information: Mp4parseByteData,
+    /// Bit depth for the image, or 0 if values are inconsistent.
+    pub bit_depth: u8,
+}
+
+impl Default for Mp4parseAvifImageItem {
+    fn default() -> Self {
+        Self {
+            coded_data: Default::default(),
+            spatial_extents: std::ptr::null(),
+            av1_config: Default::default(),
+            nclx_colour_information: std::ptr::null(),
+            icc_colour_information: Default::default(),
+            bit_depth: 0,
+        }
+    }
+}
+
+/// An auxiliary image of the primary item, such as an alpha plane or a depth
+/// map.
+#[repr(C)]
+#[derive(Debug, Default)]
+pub struct Mp4parseAvifAuxiliaryImage {
+    /// The URN identifying what the image represents, without a nul terminator
+    pub aux_type: Mp4parseByteData,
+    /// Data whose semantics depend on `aux_type`
+    pub aux_subtype: Mp4parseByteData,
+    pub image: Mp4parseAvifImageItem,
+}
+
+/// The gain map of the primary item, from a 'tmap' derived item whose inputs
+/// are the primary item and the gain map image.
+#[repr(C)]
+#[derive(Debug)]
+pub struct Mp4parseAvifGainMap {
+    pub metadata: *const mp4parse::GainMapMetadata,
+    /// The gain map image
+    pub image: Mp4parseAvifImageItem,
+    /// The colour information of the alternate (HDR) image
+    pub alternate_nclx_colour_information: *const mp4parse::NclxColourInformation,
+    pub alternate_icc_colour_information: Mp4parseByteData,
+    /// Bit depth for the alternate image, or 0 if unknown or inconsistent.
+    pub alternate_bit_depth: u8,
+}
+
+impl Default for Mp4parseAvifGainMap {
+    fn default() -> Self {
+        Self {
+            metadata: std::ptr::null(),
+            image: Default::default(),
+            alternate_nclx_colour_information: std::ptr::null(),
+            alternate_icc_colour_information: Default::default(),
+            alternate_bit_depth: 0,
+        }
+    }
+}
+
+/// The layers of a progressive primary image.
+#[repr(C)]
+#[derive(Debug)]
+pub struct Mp4parseAvifLayers {
+    /// The number of entries in `layers`, or 0 if there is no 'a1lx' property.
+    pub layer_count: usize,
+    /// The byte ranges of each layer within the primary item's coded data.
+    pub layers: *const mp4parse::LayerRange,
+    /// Whether there is an 'a1op' property selecting `operating_point`.
+    pub has_operating_point: bool,
+    pub operating_point: u8,
+    /// Whether there is an 'lsel' property selecting `layer_id` for display,
+    /// rather than permitting all layers to be displayed.
+    pub has_layer_selection: bool,
+    pub layer_id: u16,
+}
+
+impl Default for Mp4parseAvifLayers {
+    fn default() -> Self {
+        Self {
+            layer_count: 0,
+            layers: std::ptr::null(),
+            has_operating_point: false,
+            operating_point: 0,
+            has_layer_selection: false,
+            layer_id: 0,
+        }
+    }
 }
 
 #[repr(C)]
@@ -434,6 +801,17 @@ pub struct Mp4parseAvifImage {
     pub alpha_image: Mp4parseByteData,
 }
 
+/// The metadata describing the primary image. Members which aren't present
+/// have a `.length` of 0 and null `.data`.
+#[repr(C)]
+#[derive(Debug, Default)]
+pub struct Mp4parseAvifMetadata {
+    /// Exif metadata, starting at its TIFF header
+    pub exif: Mp4parseByteData,
+    /// XMP metadata
+    pub xmp: Mp4parseByteData,
+}
+
 /// A unified interface for the parsers which have different contexts, but
 /// share the same pattern of construction. This allows unification of
 /// argument validation from C and minimizes the surface of unsafe code.
@@ -446,6 +824,13 @@ where
     fn with_context(context: Self::Context) -> Self;
 
     fn read<T: Read>(io: &mut T, strictness: ParseStrictness) -> mp4parse::Result<Self::Context>;
+
+    fn read_seekable<T: Read + Seek>(
+        io: &mut T,
+        strictness: ParseStrictness,
+    ) -> mp4parse::Result<Self::Context> {
+        Self::read(io, strictness)
+    }
 }
 
 impl Mp4parseParser {
@@ -473,6 +858,15 @@ impl ContextParser for Mp4parseParser {
         log::debug!("mp4parse::read_mp4 -> {r:?}");
         r
     }
+
+    fn read_seekable<T: Read + Seek>(
+        io: &mut T,
+        strictness: ParseStrictness,
+    ) -> mp4parse::Result<Self::Context> {
+        let r = mp4parse::read_mp4_seekable(io, strictness);
+        log::debug!("mp4parse::read_mp4_seekable -> {r:?}");
+        r
+    }
 }
 
 #[derive(Default)]
@@ -550,6 +944,64 @@ impl Read for Mp4parseIo {
     }
 }
 
+/// An `Mp4parseIo` which can also seek, so that large boxes such as `mdat`
+/// are skipped by seeking rather than by reading through them.
+#[repr(C)]
+#[derive(Clone)]
+pub struct Mp4parseSeekableIo {
+    pub read: Option<
+        extern "C" fn(buffer: *mut u8, size: usize, userdata: *mut std::os::raw::c_void) -> isize,
+    >,
+    /// Seek to `offset`, returning 0 on success. Offsets are relative to the
+    /// position of the input when it's passed to `mp4parse_new_seekable`,
+    /// as are the sample offsets the parser reports, so a `seek` of an input
+    /// which doesn't start at the beginning of its file must add the
+    /// starting position.
+    pub seek: Option<extern "C" fn(offset: u64, userdata: *mut std::os::raw::c_void) -> i32>,
+    pub userdata: *mut std::os::raw::c_void,
+}
+
+/// Wraps an `Mp4parseSeekableIo` to track the read position, which the
+/// callback interface doesn't report.
+struct PositionedIo {
+    io: Mp4parseSeekableIo,
+    position: u64,
+}
+
+impl Read for PositionedIo {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let mut io = Mp4parseIo {
+            read: self.io.read,
+            userdata: self.io.userdata,
+        };
+        let n = io.read(buf)?;
+        self.position = self
+            .position
+            .checked_add(n.try_into().map_err(std::io::Error::other)?)
+            .ok_or_else(|| std::io::Error::other("position overflow in Mp4parseIo"))?;
+        Ok(n)
+    }
+}
+
+impl Seek for PositionedIo {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+            SeekFrom::End(_) => None,
+        }
+        .ok_or_else(|| std::io::Error::other("unsupported seek in Mp4parseIo"))?;
+        if position != self.position {
+            let seek = self.io.seek.expect("PositionedIo requires seek");
+            if seek(position, self.io.userdata) != 0 {
+                return Err(std::io::Error::other("I/O error in Mp4parseIo seek"));
+            }
+            self.position = position;
+        }
+        Ok(position)
+    }
+}
+
 // C API wrapper functions.
 
 /// Allocate an `Mp4parseParser*` to read from the supplied `Mp4parseIo` and
@@ -577,6 +1029,44 @@ pub unsafe extern "C" fn mp4parse_new(
     mp4parse_new_common(io, ParseStrictness::Normal, parser_out)
 }
 
+/// Allocate an `Mp4parseParser*` to read from the supplied
+/// `Mp4parseSeekableIo`, seeking over the boxes which aren't parsed.
+///
+/// See mp4parse_new; this function is identical except for its input.
+///
+/// # Safety
+///
+/// Same as mp4parse_new, and the `seek` function of io should also be sanely
+/// implemented.
+#[no_mangle]
+pub unsafe extern "C" fn mp4parse_new_seekable(
+    io: *const Mp4parseSeekableIo,
+    parser_out: *mut *mut Mp4parseParser,
+) -> Mp4parseStatus {
+    // Validate arguments from C.
+    if io.is_null()
+        || (*io).userdata.is_null()
+        || (*io).read.is_none()
+        || (*io).seek.is_none()
+        || parser_out.is_null()
+        || !(*parser_out).is_null()
+    {
+        Mp4parseStatus::BadArg
+    } else {
+        let mut io = PositionedIo {
+            io: (*io).clone(),
+            position: 0,
+        };
+        match mp4parse_new_common_seekable_safe(&mut io, ParseStrictness::Normal) {
+            Ok(parser) => {
+                *parser_out = parser;
+                Mp4parseStatus::Ok
+            }
+            Err(status) => status,
+        }
+    }
+}
+
 /// Allocate an `Mp4parseAvifParser*` to read from the supplied `Mp4parseIo`.
 ///
 /// See mp4parse_new; this function is identical except that it allocates an
@@ -630,6 +1120,17 @@ fn mp4parse_new_common_safe<T: Read, P: ContextParser>(
         .map_err(Mp4parseStatus::from)
 }
 
+fn mp4parse_new_common_seekable_safe<T: Read + Seek, P: ContextParser>(
+    io: &mut T,
+    strictness: ParseStrictness,
+) -> Result<*mut P, Mp4parseStatus> {
+    P::read_seekable(io, strictness)
+        .map(P::with_context)
+        .and_then(|x| TryBox::try_new(x).map_err(mp4parse::Error::from))
+        .map(TryBox::into_raw)
+        .map_err(Mp4parseStatus::from)
+}
+
 /// Free an `Mp4parseParser*` allocated by `mp4parse_new()`.
 ///
 /// # Safety
@@ -1099,6 +1600,7 @@ fn mp4parse_get_track_video_info_safe(
 
         // UNKNOWN for unsupported format.
         sample_info.codec_type = match video.codec_specific {
+            _ if video.requires_dolby_vision() => Mp4parseCodec::DolbyVision,
             VideoCodecSpecific::VPxConfig(_) => Mp4parseCodec::Vp9,
             VideoCodecSpecific::AV1Config(_) => Mp4parseCodec::Av1,
             VideoCodecSpecific::AVCConfig(_) => Mp4parseCodec::Avc,
@@ -1120,9 +1622,13 @@ fn mp4parse_get_track_video_info_safe(
             VideoCodecSpecific::AV1Config(ref config) => {
                 sample_info.extra_data.set_data(&config.raw_config);
             }
-            VideoCodecSpecific::AVCConfig(ref data)
-            | VideoCodecSpecific::ESDSConfig(ref data)
-            | VideoCodecSpecific::HEVCConfig(ref data) => {
+            VideoCodecSpecific::AVCConfig(ref config) => {
+                sample_info.extra_data.set_data(&config.raw_config);
+            }
+            VideoCodecSpecific::HEVCConfig(ref config) => {
+                sample_info.extra_data.set_data(&config.raw_config);
+            }
+            VideoCodecSpecific::ESDSConfig(ref data) => {
                 sample_info.extra_data.set_data(data);
             }
             _ => {}
@@ -1189,6 +1695,60 @@ fn mp4parse_get_track_video_info_safe(
                 max_pic_average_light_level: clli.max_pic_average_light_level,
             };
         }
+        if let Some(crop) = video.crop_rectangle() {
+            sample_info.has_clean_aperture = true;
+            sample_info.clean_aperture = crop;
+        }
+        if let VideoCodecSpecific::AVCConfig(ref avcc) = video.codec_specific {
+            if avcc.parsed {
+                sample_info.has_avc_config = true;
+                sample_info.avc_config = Mp4parseAvcConfig {
+                    profile_indication: avcc.profile_indication,
+                    profile_compatibility: avcc.profile_compatibility,
+                    level_indication: avcc.level_indication,
+                    nal_unit_length_size: avcc.nal_unit_length_size,
+                    sps_count: avcc.sequence_parameter_sets.len() as u32,
+                    pps_count: avcc.picture_parameter_sets.len() as u32,
+                    sps_ext_count: avcc.sequence_parameter_set_extensions.len() as u32,
+                    ..Default::default()
+                };
+            }
+            if let Some(ref sps) = avcc.sps {
+                let config = &mut sample_info.avc_config;
+                config.has_sps = true;
+                config.chroma_format_idc = sps.chroma_format_idc;
+                config.bit_depth_luma = sps.bit_depth_luma;
+                config.bit_depth_chroma = sps.bit_depth_chroma;
+                config.coded_width = sps.coded_width;
+                config.coded_height = sps.coded_height;
+                config.crop = sps.crop;
+                if let Some((sar_width, sar_height)) = sps.sample_aspect_ratio {
+                    config.has_sample_aspect_ratio = true;
+                    config.sar_width = sar_width;
+                    config.sar_height = sar_height;
+                }
+                if let Some(ref colour) = sps.colour_description {
+                    config.has_colour_description = true;
+                    config.colour_primaries = colour.colour_primaries;
+                    config.transfer_characteristics = colour.transfer_characteristics;
+                    config.matrix_coefficients = colour.matrix_coefficients;
+                    config.full_range_flag = colour.full_range_flag;
+                }
+            }
+        }
+        if let Some(ref dovi) = video.dolby_vision_config {
+            sample_info.has_dolby_vision_config = true;
+            sample_info.dolby_vision_config = Mp4parseDolbyVisionConfig {
+                dv_version_major: dovi.dv_version_major,
+                dv_version_minor: dovi.dv_version_minor,
+                dv_profile: dovi.dv_profile,
+                dv_level: dovi.dv_level,
+                rpu_present: dovi.rpu_present,
+                el_present: dovi.el_present,
+                bl_present: dovi.bl_present,
+                dv_bl_signal_compatibility_id: dovi.dv_bl_signal_compatibility_id,
+            };
+        }
 
         video_sample_infos.push(sample_info)?;
     }
@@ -1215,6 +1775,76 @@ fn mp4parse_get_track_video_info_safe(
     Ok(())
 }
 
+/// Fill the supplied `Mp4parseByteData` with the RFC 6381 codecs parameter,
+/// e.g. "avc1.64001f" or "mp4a.40.2", of the sample description at
+/// `description_index` of `track`.
+///
+/// The string isn't null-terminated and remains valid until the parser is
+/// freed. `Mp4parseStatus::Unsupported` is returned for sample descriptions
+/// without a known codecs parameter.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences the the parser and
+/// codec_string raw pointers passed to it. Callers should ensure the parser
+/// pointer points to a valid `Mp4parseParser` and that the codec_string
+/// pointer points to a valid `Mp4parseByteData`.
+#[no_mangle]
+pub unsafe extern "C" fn mp4parse_get_track_codec_string(
+    parser: *mut Mp4parseParser,
+    track_index: u32,
+    description_index: u32,
+    codec_string: *mut Mp4parseByteData,
+) -> Mp4parseStatus {
+    if parser.is_null() || codec_string.is_null() {
+        return Mp4parseStatus::BadArg;
+    }
+
+    // Initialize fields to default values to ensure all fields are always valid.
+    *codec_string = Default::default();
+
+    get_track_codec_string(
+        &mut *parser,
+        track_index,
+        description_index,
+        &mut *codec_string,
+    )
+    .into()
+}
+
+fn get_track_codec_string(
+    parser: &mut Mp4parseParser,
+    track_index: u32,
+    description_index: u32,
+    codec_string: &mut Mp4parseByteData,
+) -> Result<(), Mp4parseStatus> {
+    let Mp4parseParser {
+        context,
+        codec_strings,
+        ..
+    } = parser;
+
+    let key = (track_index, description_index as usize);
+    if codec_strings.get(&key).is_none() {
+        let track = context
+            .tracks
+            .get(track_index as usize)
+            .ok_or(Mp4parseStatus::BadArg)?;
+        let stsd = track.stsd.as_ref().ok_or(Mp4parseStatus::Invalid)?;
+        let description = stsd
+            .descriptions
+            .get(description_index as usize)
+            .ok_or(Mp4parseStatus::BadArg)?;
+        codec_strings.insert_cache_entry(key, description.codec_string()?)?;
+    }
+
+    match codec_strings.get(&key) {
+        Some(data) => codec_string.set_data(data),
+        None => return Err(Mp4parseStatus::Invalid), // Shouldn't happen, we just inserted it!
+    }
+    Ok(())
+}
+
 /// Return a struct containing meta information read by previous
 /// `mp4parse_avif_new()` call.
 ///
@@ -1244,6 +1874,14 @@ pub unsafe extern "C" fn mp4parse_avif_get_info(
     }
 }
 
+fn get_bit_depth(data: &[u8]) -> u8 {
+    if !data.is_empty() && data.iter().all(|v| *v == data[0]) {
+        data[0]
+    } else {
+        0
+    }
+}
+
 fn mp4parse_avif_get_info_safe(context: &AvifContext) -> mp4parse::Result<Mp4parseAvifInfo> {
     let info = Mp4parseAvifInfo {
         premultiplied_alpha: context.premultiplied_alpha,
@@ -1272,15 +1910,14 @@ fn mp4parse_avif_get_info_safe(context: &AvifContext) -> mp4parse::Result<Mp4par
         color_track_bit_depth: 0,
         alpha_track_id: 0,
         alpha_track_bit_depth: 0,
+
+        has_grid: context.primary_grid().is_some(),
+        clean_aperture: context.clean_aperture_ptr(),
+        thumbnail_count: u32::try_from(context.thumbnails().count())?,
+        auxiliary_image_count: u32::try_from(context.auxiliary_images().count())?,
+        has_gain_map: context.gain_map_metadata().is_some(),
     };
 
-    fn get_bit_depth(data: &[u8]) -> u8 {
-        if !data.is_empty() && data.iter().all(|v| *v == data[0]) {
-            data[0]
-        } else {
-            0
-        }
-    }
     let primary_item_bit_depth =
         get_bit_depth(context.primary_item_bits_per_channel().unwrap_or(Ok(&[]))?);
     let alpha_item_bit_depth =
@@ -1438,33 +2075,393 @@ pub fn mp4parse_avif_get_image_safe(
     })
 }
 
-/// Fill the supplied `Mp4parseByteData` with index information from `track`.
+/// Fill the supplied `Mp4parseAvifMetadata` with the Exif and XMP metadata
+/// items which describe the primary image.
 ///
 /// # Safety
 ///
-/// This function is unsafe because it dereferences the the parser and indices
-/// raw pointers passed to it. Callers should ensure the parser pointer points
-/// to a valid `Mp4parseParser` and that the indices pointer points to a valid
-/// `Mp4parseByteData`.
+/// This function is unsafe because it dereferences both the parser and
+/// metadata raw pointers passed into it. Callers should ensure the parser
+/// pointer points to a valid `Mp4parseAvifParser`, and that the metadata
+/// pointer points to a valid `Mp4parseAvifMetadata`.
 #[no_mangle]
-pub unsafe extern "C" fn mp4parse_get_indice_table(
-    parser: *mut Mp4parseParser,
-    track_id: u32,
-    indices: *mut Mp4parseByteData,
+pub unsafe extern "C" fn mp4parse_avif_get_metadata(
+    parser: *const Mp4parseAvifParser,
+    metadata: *mut Mp4parseAvifMetadata,
 ) -> Mp4parseStatus {
-    if parser.is_null() {
+    if parser.is_null() || metadata.is_null() {
         return Mp4parseStatus::BadArg;
     }
 
-    // Initialize fields to default values to ensure all fields are always valid.
-    *indices = Default::default();
+    let context = (*parser).context();
+    *metadata = Mp4parseAvifMetadata {
+        exif: Mp4parseByteData::with_data(context.exif_data().unwrap_or(&[])),
+        xmp: Mp4parseByteData::with_data(context.xmp_data().unwrap_or(&[])),
+    };
+    Mp4parseStatus::Ok
+}
 
-    get_indice_table(
-        &(*parser).context,
-        &mut (*parser).sample_table,
-        track_id,
-        &mut *indices,
-    )
+/// Fill the supplied `Mp4parseAvifGrid` with the layout of the primary item,
+/// if it's a 'grid' derived image.
+///
+/// `Mp4parseStatus::Invalid` is returned if the primary item isn't a grid.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences both the parser and
+/// grid raw pointers passed into it. Callers should ensure the parser
+/// pointer points to a valid `Mp4parseAvifParser`, and that the grid
+/// pointer points to a valid `Mp4parseAvifGrid`.
+#[no_mangle]
+pub unsafe extern "C" fn mp4parse_avif_get_grid(
+    parser: *const Mp4parseAvifParser,
+    grid: *mut Mp4parseAvifGrid,
+) -> Mp4parseStatus {
+    if parser.is_null() || grid.is_null() {
+        return Mp4parseStatus::BadArg;
+    }
+
+    // Initialize fields to default values to ensure all fields are always valid.
+    *grid = Default::default();
+
+    let context = (*parser).context();
+    match context.primary_grid() {
+        Some(image_grid) => {
+            *grid = Mp4parseAvifGrid {
+                rows: image_grid.rows.into(),
+                columns: image_grid.columns.into(),
+                output_width: image_grid.output_width,
+                output_height: image_grid.output_height,
+                // At most 256 * 256
+                tile_count: context.grid_tiles().count() as u32,
+            };
+            Mp4parseStatus::Ok
+        }
+        None => Mp4parseStatus::Invalid,
+    }
+}
+
+/// Fill the supplied `Mp4parseAvifImageItem` with the tile at `index`, in
+/// raster order, of the primary 'grid' item.
+///
+/// `Mp4parseStatus::Invalid` is returned if the primary item isn't a grid,
+/// or `index` isn't less than its `tile_count`.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences both the parser and
+/// tile raw pointers passed into it. Callers should ensure the parser
+/// pointer points to a valid `Mp4parseAvifParser`, and that the tile
+/// pointer points to a valid `Mp4parseAvifImageItem`.
+#[no_mangle]
+pub unsafe extern "C" fn mp4parse_avif_get_grid_tile(
+    parser: *const Mp4parseAvifParser,
+    index: u32,
+    tile: *mut Mp4parseAvifImageItem,
+) -> Mp4parseStatus {
+    if parser.is_null() || tile.is_null() {
+        return Mp4parseStatus::BadArg;
+    }
+
+    // Initialize fields to default values to ensure all fields are always valid.
+    *tile = Default::default();
+
+    let context = (*parser).context();
+    match avif_image_item(context.grid_tiles().nth(index as usize)) {
+        Ok(Some(grid_tile)) => {
+            *tile = grid_tile;
+            Mp4parseStatus::Ok
+        }
+        Ok(None) => Mp4parseStatus::Invalid,
+        Err(error) => error.into(),
+    }
+}
+
+/// Fill the supplied `DisplayGeometry` with the displayed size and
+/// orientation of the primary image, combining its 'ispe', 'clap', 'irot'
+/// and 'imir' properties.
+///
+/// `Mp4parseStatus::Invalid` is returned if the primary image has no 'ispe'.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences both the parser and
+/// geometry raw pointers passed into it. Callers should ensure the parser
+/// pointer points to a valid `Mp4parseAvifParser`, and that the geometry
+/// pointer points to a valid `DisplayGeometry`.
+#[no_mangle]
+pub unsafe extern "C" fn mp4parse_avif_get_display_geometry(
+    parser: *const Mp4parseAvifParser,
+    geometry: *mut mp4parse::DisplayGeometry,
+) -> Mp4parseStatus {
+    if parser.is_null() || geometry.is_null() {
+        return Mp4parseStatus::BadArg;
+    }
+
+    // Initialize fields to default values to ensure all fields are always valid.
+    *geometry = Default::default();
+
+    match (*parser).context().display_geometry() {
+        Ok(Some(display_geometry)) => {
+            *geometry = display_geometry;
+            Mp4parseStatus::Ok
+        }
+        Ok(None) => Mp4parseStatus::Invalid,
+        Err(error) => error.into(),
+    }
+}
+
+/// Fill the supplied `Mp4parseAvifImageItem` with the thumbnail at `index`
+/// of the primary item, where `index` is less than the `thumbnail_count` of
+/// `Mp4parseAvifInfo`.
+///
+/// `Mp4parseStatus::Invalid` is returned if there is no such thumbnail.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences both the parser and
+/// thumbnail raw pointers passed into it. Callers should ensure the parser
+/// pointer points to a valid `Mp4parseAvifParser`, and that the thumbnail
+/// pointer points to a valid `Mp4parseAvifImageItem`.
+#[no_mangle]
+pub unsafe extern "C" fn mp4parse_avif_get_thumbnail(
+    parser: *const Mp4parseAvifParser,
+    index: u32,
+    thumbnail: *mut Mp4parseAvifImageItem,
+) -> Mp4parseStatus {
+    if parser.is_null() || thumbnail.is_null() {
+        return Mp4parseStatus::BadArg;
+    }
+
+    // Initialize fields to default values to ensure all fields are always valid.
+    *thumbnail = Default::default();
+
+    let context = (*parser).context();
+    match avif_image_item(context.thumbnails().nth(index as usize)) {
+        Ok(Some(avif_thumbnail)) => {
+            *thumbnail = avif_thumbnail;
+            Mp4parseStatus::Ok
+        }
+        Ok(None) => Mp4parseStatus::Invalid,
+        Err(error) => error.into(),
+    }
+}
+
+/// Fill the supplied `Mp4parseAvifAuxiliaryImage` with the auxiliary image at
+/// `index` of the primary item, where `index` is less than the
+/// `auxiliary_image_count` of `Mp4parseAvifInfo`.
+///
+/// `Mp4parseStatus::Invalid` is returned if there is no such image.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences both the parser and
+/// auxiliary_image raw pointers passed into it. Callers should ensure the
+/// parser pointer points to a valid `Mp4parseAvifParser`, and that the
+/// auxiliary_image pointer points to a valid `Mp4parseAvifAuxiliaryImage`.
+#[no_mangle]
+pub unsafe extern "C" fn mp4parse_avif_get_auxiliary_image(
+    parser: *const Mp4parseAvifParser,
+    index: u32,
+    auxiliary_image: *mut Mp4parseAvifAuxiliaryImage,
+) -> Mp4parseStatus {
+    if parser.is_null() || auxiliary_image.is_null() {
+        return Mp4parseStatus::BadArg;
+    }
+
+    // Initialize fields to default values to ensure all fields are always valid.
+    *auxiliary_image = Default::default();
+
+    match mp4parse_avif_get_auxiliary_image_safe((*parser).context(), index as usize) {
+        Ok(Some(image)) => {
+            *auxiliary_image = image;
+            Mp4parseStatus::Ok
+        }
+        Ok(None) => Mp4parseStatus::Invalid,
+        Err(error) => error.into(),
+    }
+}
+
+fn mp4parse_avif_get_auxiliary_image_safe(
+    context: &AvifContext,
+    index: usize,
+) -> mp4parse::Result<Option<Mp4parseAvifAuxiliaryImage>> {
+    let item = context.auxiliary_images().nth(index);
+    let image = match avif_image_item(item)? {
+        Some(image) => image,
+        None => return Ok(None),
+    };
+    let auxc = item.map_or(Ok(None), |item| item.auxiliary_type())?;
+    Ok(Some(Mp4parseAvifAuxiliaryImage {
+        aux_type: Mp4parseByteData::with_data(auxc.map_or(&[][..], |auxc| auxc.aux_type())),
+        aux_subtype: Mp4parseByteData::with_data(auxc.map_or(&[][..], |auxc| auxc.aux_subtype())),
+        image,
+    }))
+}
+
+/// Fill the supplied `Mp4parseAvifGainMap` with the gain map of the primary
+/// item.
+///
+/// `Mp4parseStatus::Invalid` is returned if there is no gain map.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences both the parser and
+/// gain_map raw pointers passed into it. Callers should ensure the parser
+/// pointer points to a valid `Mp4parseAvifParser`, and that the gain_map
+/// pointer points to a valid `Mp4parseAvifGainMap`.
+#[no_mangle]
+pub unsafe extern "C" fn mp4parse_avif_get_gain_map(
+    parser: *const Mp4parseAvifParser,
+    gain_map: *mut Mp4parseAvifGainMap,
+) -> Mp4parseStatus {
+    if parser.is_null() || gain_map.is_null() {
+        return Mp4parseStatus::BadArg;
+    }
+
+    // Initialize fields to default values to ensure all fields are always valid.
+    *gain_map = Default::default();
+
+    match mp4parse_avif_get_gain_map_safe((*parser).context()) {
+        Ok(Some(avif_gain_map)) => {
+            *gain_map = avif_gain_map;
+            Mp4parseStatus::Ok
+        }
+        Ok(None) => Mp4parseStatus::Invalid,
+        Err(error) => error.into(),
+    }
+}
+
+fn mp4parse_avif_get_gain_map_safe(
+    context: &AvifContext,
+) -> mp4parse::Result<Option<Mp4parseAvifGainMap>> {
+    let (metadata, image) = match (
+        context.gain_map_metadata(),
+        avif_image_item(context.gain_map_image())?,
+    ) {
+        (Some(metadata), Some(image)) => (metadata, image),
+        _ => return Ok(None),
+    };
+    let tmap = context.tone_map_item();
+    Ok(Some(Mp4parseAvifGainMap {
+        metadata,
+        image,
+        alternate_nclx_colour_information: tmap
+            .and_then(|tmap| tmap.nclx_colour_information())
+            .transpose()?
+            .map_or(std::ptr::null(), |nclx| nclx),
+        alternate_icc_colour_information: Mp4parseByteData::with_data(
+            tmap.and_then(|tmap| tmap.icc_colour_information())
+                .unwrap_or(Ok(&[]))?,
+        ),
+        alternate_bit_depth: get_bit_depth(
+            tmap.map_or(Ok(&[][..]), |tmap| tmap.bits_per_channel())?,
+        ),
+    }))
+}
+
+/// Fill an `Mp4parseAvifImageItem` with `item`, or return None if there is
+/// no such item or its coded data wasn't read.
+fn avif_image_item(item: Option<HeifItem>) -> mp4parse::Result<Option<Mp4parseAvifImageItem>> {
+    let (item, coded_data) = match item.and_then(|item| Some((item, item.data()?))) {
+        Some(item) => item,
+        None => return Ok(None),
+    };
+    Ok(Some(Mp4parseAvifImageItem {
+        coded_data: Mp4parseByteData::with_data(coded_data),
+        spatial_extents: item
+            .spatial_extents()?
+            .map_or(std::ptr::null(), |ispe| ispe),
+        av1_config: Mp4parseByteData::with_data(
+            item.av1_config()?
+                .map_or(&[][..], |av1c| av1c.raw_config.as_slice()),
+        ),
+        nclx_colour_information: item
+            .nclx_colour_information()
+            .transpose()?
+            .map_or(std::ptr::null(), |nclx| nclx),
+        icc_colour_information: Mp4parseByteData::with_data(
+            item.icc_colour_information().unwrap_or(Ok(&[]))?,
+        ),
+        bit_depth: get_bit_depth(item.bits_per_channel()?),
+    }))
+}
+
+/// Fill the supplied `Mp4parseAvifLayers` with the layers of the primary
+/// image, so it can be decoded progressively as its data arrives.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences both the parser and
+/// layers raw pointers passed into it. Callers should ensure the parser
+/// pointer points to a valid `Mp4parseAvifParser`, and that the layers
+/// pointer points to a valid `Mp4parseAvifLayers`.
+#[no_mangle]
+pub unsafe extern "C" fn mp4parse_avif_get_layers(
+    parser: *const Mp4parseAvifParser,
+    layers: *mut Mp4parseAvifLayers,
+) -> Mp4parseStatus {
+    if parser.is_null() || layers.is_null() {
+        return Mp4parseStatus::BadArg;
+    }
+
+    // Initialize fields to default values to ensure all fields are always valid.
+    *layers = Default::default();
+
+    match mp4parse_avif_get_layers_safe((*parser).context()) {
+        Ok(avif_layers) => {
+            *layers = avif_layers;
+            Mp4parseStatus::Ok
+        }
+        Err(error) => error.into(),
+    }
+}
+
+fn mp4parse_avif_get_layers_safe(context: &AvifContext) -> mp4parse::Result<Mp4parseAvifLayers> {
+    let layer_ranges = context.primary_item_layer_ranges();
+    let operating_point = context.primary_item_operating_point()?;
+    let layer_id = context.primary_item_layer_selection()?;
+    Ok(Mp4parseAvifLayers {
+        layer_count: layer_ranges.len(),
+        layers: if layer_ranges.is_empty() {
+            std::ptr::null()
+        } else {
+            layer_ranges.as_ptr()
+        },
+        has_operating_point: operating_point.is_some(),
+        operating_point: operating_point.unwrap_or_default(),
+        has_layer_selection: layer_id.is_some(),
+        layer_id: layer_id.unwrap_or_default(),
+    })
+}
+
+/// Fill the supplied `Mp4parseByteData` with index information from `track`.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences the the parser and indices
+/// raw pointers passed to it. Callers should ensure the parser pointer points
+/// to a valid `Mp4parseParser` and that the indices pointer points to a valid
+/// `Mp4parseByteData`.
+#[no_mangle]
+pub unsafe extern "C" fn mp4parse_get_indice_table(
+    parser: *mut Mp4parseParser,
+    track_id: u32,
+    indices: *mut Mp4parseByteData,
+) -> Mp4parseStatus {
+    if parser.is_null() {
+        return Mp4parseStatus::BadArg;
+    }
+
+    // Initialize fields to default values to ensure all fields are always valid.
+    *indices = Default::default();
+
+    get_indice_table(
+        &(*parser).context,
+        &mut (*parser).sample_table,
+        track_id,
+        &mut *indices,
+    )
     .into()
 }
 
@@ -1569,7 +2566,16 @@ fn get_indice_table(
         _ => 0.into(),
     };
 
-    if let Some(v) = create_sample_table(track, offset_time) {
+    // Fragmented tracks have empty sample tables in 'moov'; their samples
+    // are described by the 'moof' boxes instead.
+    let sample_table = match create_sample_table(track, offset_time) {
+        Some(v) if v.is_empty() && !context.moofs.is_empty() => {
+            create_fragmented_sample_table(context, track, offset_time)
+        }
+        v => v,
+    };
+
+    if let Some(v) = sample_table {
         indices.set_indices(&v);
         sample_table_cache.insert_cache_entry(track_id, v)?;
         return Ok(());
@@ -1578,6 +2584,112 @@ fn get_indice_table(
     Err(Mp4parseStatus::Invalid)
 }
 
+/// Fill the supplied `Mp4parseSampleEncryptionTable` with the per-sample
+/// Common Encryption parameters of `track`, from its 'senc' boxes and its
+/// 'tenc' as overridden by any 'seig' sample groups.
+///
+/// `Mp4parseStatus::Invalid` is returned if the track isn't protected. The
+/// table remains valid until the parser is freed.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences the the parser and table
+/// raw pointers passed to it. Callers should ensure the parser pointer points
+/// to a valid `Mp4parseParser` and that the table pointer points to a valid
+/// `Mp4parseSampleEncryptionTable`.
+#[no_mangle]
+pub unsafe extern "C" fn mp4parse_get_sample_encryption_table(
+    parser: *mut Mp4parseParser,
+    track_id: u32,
+    table: *mut Mp4parseSampleEncryptionTable,
+) -> Mp4parseStatus {
+    if parser.is_null() || table.is_null() {
+        return Mp4parseStatus::BadArg;
+    }
+
+    // Initialize fields to default values to ensure all fields are always valid.
+    *table = Default::default();
+
+    get_sample_encryption_table(&mut *parser, track_id, &mut *table).into()
+}
+
+fn get_sample_encryption_table(
+    parser: &mut Mp4parseParser,
+    track_id: u32,
+    table: &mut Mp4parseSampleEncryptionTable,
+) -> Result<(), Mp4parseStatus> {
+    if parser.sample_encryption_table.get(&track_id).is_none() {
+        let context = parser.context();
+        let track = match context
+            .tracks
+            .iter()
+            .find(|track| track.track_id == Some(track_id))
+        {
+            Some(track) => track,
+            None => return Err(Mp4parseStatus::Invalid),
+        };
+        let parameters = match create_sample_encryption_parameters_table(context, track) {
+            Some(parameters) => parameters?,
+            None => return Err(Mp4parseStatus::Invalid),
+        };
+        let entries = match create_sample_encryption_table(context, track) {
+            Some(entries) => entries?,
+            None => TryVec::new(),
+        };
+
+        // Gather the subsamples first, so their storage doesn't move once
+        // the samples point into it.
+        let subsample_count = entries.iter().map(|entry| entry.subsamples.len()).sum();
+        let mut subsamples = TryVec::with_capacity(subsample_count)?;
+        for subsample in entries.iter().flat_map(|entry| entry.subsamples.iter()) {
+            subsamples.push(Mp4parseSubsample {
+                bytes_of_clear_data: subsample.bytes_of_clear_data,
+                bytes_of_protected_data: subsample.bytes_of_protected_data,
+            })?;
+        }
+        let mut samples = TryVec::with_capacity(parameters.len())?;
+        let mut first_subsample = 0;
+        for (i, tenc) in parameters.iter().enumerate() {
+            let (iv, count) = match entries.get(i) {
+                Some(entry) => (entry.iv.as_slice(), entry.subsamples.len()),
+                None => (&[][..], 0),
+            };
+            samples.push(Mp4parseSampleEncryption {
+                iv: Mp4parseByteData::with_data(iv),
+                // At most u16::MAX, per the 'senc' layout.
+                subsample_count: count as u32,
+                subsamples: if count == 0 {
+                    std::ptr::null()
+                } else {
+                    subsamples[first_subsample..].as_ptr()
+                },
+                is_encrypted: tenc.is_encrypted,
+                iv_size: tenc.iv_size,
+                kid: Mp4parseByteData::with_data(&tenc.kid),
+                crypt_byte_block: tenc.crypt_byte_block_count.unwrap_or(0),
+                skip_byte_block: tenc.skip_byte_block_count.unwrap_or(0),
+                constant_iv: Mp4parseByteData::with_data(
+                    tenc.constant_iv.as_deref().unwrap_or(&[]),
+                ),
+            })?;
+            first_subsample += count;
+        }
+
+        parser.sample_encryption_table.insert_cache_entry(
+            track_id,
+            SampleEncryptionTable {
+                entries: samples,
+                _storage: (entries, subsamples),
+            },
+        )?;
+    }
+
+    if let Some(cached) = parser.sample_encryption_table.get(&track_id) {
+        (table.sample_count, table.samples) = cached.as_raw_parts();
+    }
+    Ok(())
+}
+
 /// Fill the supplied `Mp4parseFragmentInfo` with metadata from fragmented file.
 ///
 /// # Safety
@@ -1616,8 +2728,119 @@ pub unsafe extern "C" fn mp4parse_get_fragment_info(
     Mp4parseStatus::Invalid
 }
 
+/// Fill the supplied `Mp4parseSegmentIndex` with the subsegments described by
+/// the segment index ('sidx') boxes of a track.
+///
+/// This lets a player build its segment index from the initialization and
+/// index ranges of a file alone. `Mp4parseStatus::Invalid` is returned if no
+/// 'sidx' describes the track. The segments remain valid until the parser is
+/// freed.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences the the parser and
+/// index raw pointers passed to it. Callers should ensure the parser
+/// pointer points to a valid `Mp4parseParser` and that the index pointer
+/// points to a valid `Mp4parseSegmentIndex`.
+#[no_mangle]
+pub unsafe extern "C" fn mp4parse_get_segment_index(
+    parser: *mut Mp4parseParser,
+    track_id: u32,
+    index: *mut Mp4parseSegmentIndex,
+) -> Mp4parseStatus {
+    if parser.is_null() || index.is_null() {
+        return Mp4parseStatus::BadArg;
+    }
+
+    // Initialize fields to default values to ensure all fields are always valid.
+    *index = Default::default();
+
+    get_segment_index(&mut *parser, track_id, &mut *index).into()
+}
+
+fn get_segment_index(
+    parser: &mut Mp4parseParser,
+    track_id: u32,
+    index: &mut Mp4parseSegmentIndex,
+) -> Result<(), Mp4parseStatus> {
+    if parser.segment_indexes.get(&track_id).is_none() {
+        let segment_index = match parser.context().segment_index(track_id) {
+            Some(segment_index) => segment_index?,
+            None => return Err(Mp4parseStatus::Invalid),
+        };
+        let mut segments = TryVec::with_capacity(segment_index.segments.len())?;
+        for segment in segment_index.segments.iter() {
+            segments.push(Mp4parseSegment {
+                offset: segment.offset,
+                size: segment.size,
+                start_time: segment.start_time,
+                duration: segment.duration,
+                starts_with_sap: segment.starts_with_sap,
+            })?;
+        }
+        parser
+            .segment_indexes
+            .insert_cache_entry(track_id, (segment_index.timescale.into(), segments))?;
+    }
+
+    if let Some((timescale, segments)) = parser.segment_indexes.get(&track_id) {
+        index.timescale = *timescale;
+        index.segment_count = segments.len();
+        index.segments = if segments.is_empty() {
+            std::ptr::null()
+        } else {
+            segments.as_ptr()
+        };
+    }
+    Ok(())
+}
+
+/// Fill the supplied `Mp4parseTrackExtends` with the fragment sample
+/// defaults of a track, from the 'trex' box in 'mvex'.
+///
+/// `Mp4parseStatus::Invalid` is returned if the file has no 'trex' box for
+/// the track.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences the the parser and
+/// info raw pointers passed to it. Callers should ensure the parser
+/// pointer points to a valid `Mp4parseParser` and that the info pointer points
+/// to a valid `Mp4parseTrackExtends`.
+#[no_mangle]
+pub unsafe extern "C" fn mp4parse_get_track_extends(
+    parser: *mut Mp4parseParser,
+    track_id: u32,
+    info: *mut Mp4parseTrackExtends,
+) -> Mp4parseStatus {
+    if parser.is_null() || info.is_null() {
+        return Mp4parseStatus::BadArg;
+    }
+
+    // Initialize fields to default values to ensure all fields are always valid.
+    *info = Default::default();
+
+    let context = (*parser).context();
+    let trex = match context
+        .mvex
+        .as_ref()
+        .and_then(|mvex| mvex.track_extends(track_id))
+    {
+        Some(trex) => trex,
+        None => return Mp4parseStatus::Invalid,
+    };
+
+    let info: &mut Mp4parseTrackExtends = &mut *info;
+    info.default_sample_description_index = trex.default_sample_description_index;
+    info.default_sample_duration = trex.default_sample_duration;
+    info.default_sample_size = trex.default_sample_size;
+    info.default_sample_flags = trex.default_sample_flags;
+    Mp4parseStatus::Ok
+}
+
 /// Determine if an mp4 file is fragmented. A fragmented file needs mvex table
-/// and contains no data in stts, stsc, and stco boxes.
+/// and either a trex box for the track or no data in stts, stsc, and stco
+/// boxes.
 ///
 /// # Safety
 ///
@@ -1639,14 +2862,19 @@ pub unsafe extern "C" fn mp4parse_is_fragmented(
     let tracks = &context.tracks;
     (*fragmented) = false as u8;
 
-    if context.mvex.is_none() {
-        return Mp4parseStatus::Ok;
-    }
+    let mvex = match context.mvex {
+        Some(ref mvex) => mvex,
+        None => return Mp4parseStatus::Ok,
+    };
 
-    // check sample tables.
+    // check trex, then sample tables.
     let mut iter = tracks.iter();
     iter.find(|track| track.track_id == Some(track_id))
         .map_or(Mp4parseStatus::BadArg, |track| {
+            if mvex.track_extends(track_id).is_some() {
+                (*fragmented) = true as u8;
+                return Mp4parseStatus::Ok;
+            }
             match (&track.stsc, &track.stco, &track.stts) {
                 (Some(stsc), Some(stco), Some(stts))
                     if stsc.samples.is_empty()
@@ -1723,6 +2951,95 @@ fn get_pssh_info(
     Ok(())
 }
 
+/// Fill the supplied `Mp4parsePsshTable` with every 'pssh' box of the file,
+/// including those delivered in 'moof' boxes.
+///
+/// Unlike `mp4parse_get_pssh_info`, the fields of each box are returned
+/// separately, so callers needn't decode the box themselves. The table
+/// remains valid until the parser is freed.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences the the parser and table
+/// raw pointers passed to it. Callers should ensure the parser pointer points
+/// to a valid `Mp4parseParser` and that the table pointer points to a valid
+/// `Mp4parsePsshTable`.
+#[no_mangle]
+pub unsafe extern "C" fn mp4parse_get_pssh_table(
+    parser: *mut Mp4parseParser,
+    table: *mut Mp4parsePsshTable,
+) -> Mp4parseStatus {
+    if parser.is_null() || table.is_null() {
+        return Mp4parseStatus::BadArg;
+    }
+
+    // Initialize fields to default values to ensure all fields are always valid.
+    *table = Default::default();
+
+    get_pssh_table(&mut *parser, &mut *table).into()
+}
+
+fn get_pssh_table(
+    parser: &mut Mp4parseParser,
+    table: &mut Mp4parsePsshTable,
+) -> Result<(), Mp4parseStatus> {
+    let Mp4parseParser {
+        context,
+        pssh_table,
+        ..
+    } = parser;
+
+    if pssh_table.is_none() {
+        let psshs = || {
+            let moov = context.psshs.iter().map(|pssh| (pssh, None));
+            let moofs = context
+                .moofs
+                .iter()
+                .flat_map(|moof| moof.psshs.iter().map(move |pssh| (pssh, Some(moof.offset))));
+            moov.chain(moofs)
+        };
+
+        // Gather the key IDs first, so their storage doesn't move once the
+        // boxes point into it.
+        let kid_count: usize = psshs().map(|(pssh, _)| pssh.kid.len()).sum();
+        let mut kids = TryVec::with_capacity(kid_count * 16)?;
+        for kid in psshs().flat_map(|(pssh, _)| pssh.kid.iter()) {
+            kids.extend_from_slice(kid)?;
+        }
+        let mut boxes = TryVec::with_capacity(psshs().count())?;
+        let mut first_kid = 0;
+        for (pssh, moof_offset) in psshs() {
+            let mut system_id = [0; 16];
+            system_id.copy_from_slice(&pssh.system_id);
+            let kids_len = pssh.kid.len() * 16;
+            boxes.push(Mp4parsePssh {
+                system_id,
+                version: pssh.version,
+                kid_count: pssh
+                    .kid
+                    .len()
+                    .try_into()
+                    .map_err(|_| Mp4parseStatus::Invalid)?,
+                kids: Mp4parseByteData::with_data(&kids[first_kid..first_kid + kids_len]),
+                data: Mp4parseByteData::with_data(&pssh.data),
+                in_moof: moof_offset.is_some(),
+                moof_offset: moof_offset.unwrap_or(0),
+            })?;
+            first_kid += kids_len;
+        }
+        *pssh_table = Some(PsshTable {
+            entries: boxes,
+            _storage: kids,
+        });
+    }
+
+    if let Some(cached) = pssh_table {
+        (table.pssh_count, table.psshs) = cached.as_raw_parts();
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 extern "C" fn error_read(_: *mut u8, _: usize, _: *mut std::os::raw::c_void) -> isize {
     -1
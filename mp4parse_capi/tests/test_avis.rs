@@ -23,8 +23,13 @@ fn default_avif_info() -> Mp4parseAvifInfo {
         image_rotation: mp4parse::ImageRotation::D0,
         image_mirror: std::ptr::null(),
         pixel_aspect_ratio: std::ptr::null(),
+        clean_aperture: std::ptr::null(),
         has_primary_item: Default::default(),
         primary_item_bit_depth: Default::default(),
+        has_grid: Default::default(),
+        thumbnail_count: Default::default(),
+        auxiliary_image_count: Default::default(),
+        has_gain_map: Default::default(),
         has_alpha_item: Default::default(),
         alpha_item_bit_depth: Default::default(),
         has_sequence: Default::default(),
@@ -273,3 +278,241 @@ fn empty_avif_byte_slices_use_null_pointers() {
         mp4parse_avif_free(parser);
     }
 }
+
+#[test]
+fn get_grid_tiles() {
+    let (parser, info) = unsafe { parse_file_and_get_info("tests/grid.avif") };
+    assert!(info.has_grid);
+    assert!(!info.has_primary_item);
+    assert!(info.clean_aperture.is_null());
+    let ispe = unsafe { &*info.spatial_extents };
+    assert_eq!(
+        format!("{:?}", ispe),
+        "ImageSpatialExtentsProperty { image_width: 48, image_height: 48 }"
+    );
+
+    let mut grid = Mp4parseAvifGrid::default();
+    let rv = unsafe { mp4parse_avif_get_grid(parser, &mut grid) };
+    assert_eq!(rv, Mp4parseStatus::Ok);
+    assert_eq!(grid.rows, 2);
+    assert_eq!(grid.columns, 2);
+    assert_eq!(grid.output_width, 48);
+    assert_eq!(grid.output_height, 48);
+    assert_eq!(grid.tile_count, 4);
+
+    for index in 0..grid.tile_count {
+        let mut tile = Mp4parseAvifImageItem::default();
+        let rv = unsafe { mp4parse_avif_get_grid_tile(parser, index, &mut tile) };
+        assert_eq!(rv, Mp4parseStatus::Ok);
+        let data =
+            unsafe { std::slice::from_raw_parts(tile.coded_data.data, tile.coded_data.length) };
+        let id = index as u8 + 2;
+        assert_eq!(data, [id, id]);
+        assert!(!tile.spatial_extents.is_null());
+        assert_eq!(tile.bit_depth, 8);
+    }
+
+    let mut tile = Mp4parseAvifImageItem::default();
+    let rv = unsafe { mp4parse_avif_get_grid_tile(parser, grid.tile_count, &mut tile) };
+    assert_eq!(rv, Mp4parseStatus::Invalid);
+    assert!(tile.coded_data.data.is_null());
+
+    unsafe { mp4parse_avif_free(parser) };
+
+    let (parser, info) = unsafe { parse_file_and_get_info("tests/loop_1.avif") };
+    assert!(!info.has_grid);
+    let rv = unsafe { mp4parse_avif_get_grid(parser, &mut grid) };
+    assert_eq!(rv, Mp4parseStatus::Invalid);
+    unsafe { mp4parse_avif_free(parser) };
+}
+
+#[test]
+fn get_display_geometry() {
+    let (parser, _) = unsafe { parse_file_and_get_info("tests/grid.avif") };
+    let mut geometry = mp4parse::DisplayGeometry::default();
+    let rv = unsafe { mp4parse_avif_get_display_geometry(parser, &mut geometry) };
+    assert_eq!(rv, Mp4parseStatus::Ok);
+    assert_eq!(
+        geometry.crop,
+        mp4parse::CropRectangle {
+            x: 0,
+            y: 0,
+            width: 48,
+            height: 48,
+        }
+    );
+    assert_eq!((geometry.width, geometry.height), (48, 48));
+    assert_eq!(geometry.matrix, [1, 0, 0, 0, 1, 0]);
+    unsafe { mp4parse_avif_free(parser) };
+}
+
+#[test]
+fn get_layers() {
+    let (parser, info) = unsafe { parse_file_and_get_info("tests/layered.avif") };
+    assert!(info.has_primary_item);
+
+    let mut layers = Mp4parseAvifLayers::default();
+    let rv = unsafe { mp4parse_avif_get_layers(parser, &mut layers) };
+    assert_eq!(rv, Mp4parseStatus::Ok);
+    let ranges = unsafe { std::slice::from_raw_parts(layers.layers, layers.layer_count) };
+    let range = |offset, length| mp4parse::LayerRange { offset, length };
+    assert_eq!(ranges, [range(0, 2), range(2, 5), range(7, 3)]);
+    assert!(layers.has_operating_point);
+    assert_eq!(layers.operating_point, 1);
+    assert!(layers.has_layer_selection);
+    assert_eq!(layers.layer_id, 1);
+    unsafe { mp4parse_avif_free(parser) };
+
+    let (parser, _) = unsafe { parse_file_and_get_info("tests/loop_1.avif") };
+    let rv = unsafe { mp4parse_avif_get_layers(parser, &mut layers) };
+    assert_eq!(rv, Mp4parseStatus::Ok);
+    assert_eq!(layers.layer_count, 0);
+    assert!(layers.layers.is_null());
+    assert!(!layers.has_operating_point);
+    assert!(!layers.has_layer_selection);
+    unsafe { mp4parse_avif_free(parser) };
+}
+
+#[test]
+fn repeated_get_layers_returns_stable_pointer() {
+    let parser = unsafe { parse_file("tests/layered.avif") };
+    let mut first = Mp4parseAvifLayers::default();
+    let mut second = Mp4parseAvifLayers::default();
+    unsafe {
+        assert_eq!(
+            mp4parse_avif_get_layers(parser, &mut first),
+            Mp4parseStatus::Ok
+        );
+        assert_eq!(
+            mp4parse_avif_get_layers(parser, &mut second),
+            Mp4parseStatus::Ok
+        );
+        assert_eq!(first.layers, second.layers);
+        mp4parse_avif_free(parser);
+    }
+}
+
+#[test]
+fn get_metadata() {
+    let (parser, _) = unsafe { parse_file_and_get_info("tests/metadata.avif") };
+    let mut metadata = Mp4parseAvifMetadata::default();
+    let rv = unsafe { mp4parse_avif_get_metadata(parser, &mut metadata) };
+    assert_eq!(rv, Mp4parseStatus::Ok);
+    let exif = unsafe { std::slice::from_raw_parts(metadata.exif.data, metadata.exif.length) };
+    assert_eq!(exif, b"MM\0\x2a");
+    let xmp = unsafe { std::slice::from_raw_parts(metadata.xmp.data, metadata.xmp.length) };
+    assert_eq!(xmp, b"<x:xmpmeta/>");
+    unsafe { mp4parse_avif_free(parser) };
+
+    let (parser, _) = unsafe { parse_file_and_get_info("tests/loop_1.avif") };
+    let rv = unsafe { mp4parse_avif_get_metadata(parser, &mut metadata) };
+    assert_eq!(rv, Mp4parseStatus::Ok);
+    assert!(metadata.exif.data.is_null());
+    assert!(metadata.xmp.data.is_null());
+    unsafe { mp4parse_avif_free(parser) };
+}
+
+#[test]
+fn get_thumbnail() {
+    let (parser, info) = unsafe { parse_file_and_get_info("tests/thumbnail.avif") };
+    assert!(info.has_primary_item);
+    assert_eq!(info.thumbnail_count, 1);
+
+    let mut thumbnail = Mp4parseAvifImageItem::default();
+    let rv = unsafe { mp4parse_avif_get_thumbnail(parser, 0, &mut thumbnail) };
+    assert_eq!(rv, Mp4parseStatus::Ok);
+    let data = unsafe {
+        std::slice::from_raw_parts(thumbnail.coded_data.data, thumbnail.coded_data.length)
+    };
+    assert_eq!(data, [2]);
+    let ispe = unsafe { &*thumbnail.spatial_extents };
+    assert_eq!(
+        format!("{:?}", ispe),
+        "ImageSpatialExtentsProperty { image_width: 8, image_height: 8 }"
+    );
+    let av1c = unsafe {
+        std::slice::from_raw_parts(thumbnail.av1_config.data, thumbnail.av1_config.length)
+    };
+    assert_eq!(av1c, [0x81, 0, 0, 0]);
+    let nclx = unsafe { &*thumbnail.nclx_colour_information };
+    assert_eq!(nclx.colour_primaries, 1);
+    assert!(nclx.full_range_flag);
+    assert!(thumbnail.icc_colour_information.data.is_null());
+    assert_eq!(thumbnail.bit_depth, 8);
+
+    let rv = unsafe { mp4parse_avif_get_thumbnail(parser, 1, &mut thumbnail) };
+    assert_eq!(rv, Mp4parseStatus::Invalid);
+    assert!(thumbnail.coded_data.data.is_null());
+    unsafe { mp4parse_avif_free(parser) };
+}
+
+#[test]
+fn get_auxiliary_image() {
+    let (parser, info) = unsafe { parse_file_and_get_info("tests/depth.avif") };
+    assert!(info.has_primary_item);
+    assert!(!info.has_alpha_item);
+    assert_eq!(info.auxiliary_image_count, 1);
+
+    let mut image = Mp4parseAvifAuxiliaryImage::default();
+    let rv = unsafe { mp4parse_avif_get_auxiliary_image(parser, 0, &mut image) };
+    assert_eq!(rv, Mp4parseStatus::Ok);
+    let aux_type =
+        unsafe { std::slice::from_raw_parts(image.aux_type.data, image.aux_type.length) };
+    assert_eq!(aux_type, b"urn:mpeg:hevc:2015:auxid:2");
+    let aux_subtype =
+        unsafe { std::slice::from_raw_parts(image.aux_subtype.data, image.aux_subtype.length) };
+    assert_eq!(aux_subtype, [1, 2]);
+    let data = unsafe {
+        std::slice::from_raw_parts(image.image.coded_data.data, image.image.coded_data.length)
+    };
+    assert_eq!(data, [2]);
+    let ispe = unsafe { &*image.image.spatial_extents };
+    assert_eq!(
+        format!("{:?}", ispe),
+        "ImageSpatialExtentsProperty { image_width: 16, image_height: 16 }"
+    );
+    assert_eq!(image.image.av1_config.length, 4);
+    assert_eq!(image.image.bit_depth, 8);
+
+    let rv = unsafe { mp4parse_avif_get_auxiliary_image(parser, 1, &mut image) };
+    assert_eq!(rv, Mp4parseStatus::Invalid);
+    assert!(image.aux_type.data.is_null());
+    unsafe { mp4parse_avif_free(parser) };
+}
+
+#[test]
+fn get_gain_map() {
+    let (parser, info) = unsafe { parse_file_and_get_info("tests/gain_map.avif") };
+    assert!(info.has_primary_item);
+    assert!(info.has_gain_map);
+
+    let mut gain_map = Mp4parseAvifGainMap::default();
+    let rv = unsafe { mp4parse_avif_get_gain_map(parser, &mut gain_map) };
+    assert_eq!(rv, Mp4parseStatus::Ok);
+    let metadata = unsafe { &*gain_map.metadata };
+    assert_eq!(metadata.channel_count, 3);
+    assert!(!metadata.use_base_colour_space);
+    assert_eq!(metadata.channels[2].gain_map_max.numerator, 3);
+    let data = unsafe {
+        std::slice::from_raw_parts(
+            gain_map.image.coded_data.data,
+            gain_map.image.coded_data.length,
+        )
+    };
+    assert_eq!(data, [2]);
+    assert!(!gain_map.image.spatial_extents.is_null());
+    assert_eq!(gain_map.image.av1_config.length, 4);
+    assert_eq!(gain_map.image.bit_depth, 8);
+    let nclx = unsafe { &*gain_map.alternate_nclx_colour_information };
+    assert_eq!(nclx.transfer_characteristics, 16);
+    assert!(gain_map.alternate_icc_colour_information.data.is_null());
+    assert_eq!(gain_map.alternate_bit_depth, 10);
+    unsafe { mp4parse_avif_free(parser) };
+
+    let (parser, info) = unsafe { parse_file_and_get_info("tests/loop_1.avif") };
+    assert!(!info.has_gain_map);
+    let rv = unsafe { mp4parse_avif_get_gain_map(parser, &mut gain_map) };
+    assert_eq!(rv, Mp4parseStatus::Invalid);
+    assert!(gain_map.metadata.is_null());
+    unsafe { mp4parse_avif_free(parser) };
+}
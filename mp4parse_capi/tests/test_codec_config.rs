@@ -0,0 +1,153 @@
+use mp4parse_capi::*;
+use std::io::Read;
+
+extern "C" fn buf_read(buf: *mut u8, size: usize, userdata: *mut std::os::raw::c_void) -> isize {
+    let input: &mut std::fs::File = unsafe { &mut *(userdata as *mut _) };
+    let buf = unsafe { std::slice::from_raw_parts_mut(buf, size) };
+    match input.read(buf) {
+        Ok(n) => n as isize,
+        Err(_) => -1,
+    }
+}
+
+unsafe fn open_parser(path: &str) -> *mut Mp4parseParser {
+    let mut file = std::fs::File::open(path).expect("file not found");
+    let io = Mp4parseIo {
+        read: Some(buf_read),
+        userdata: &mut file as *mut _ as *mut std::os::raw::c_void,
+    };
+    let mut parser = std::ptr::null_mut();
+    let rv = mp4parse_new(&io, &mut parser);
+    assert_eq!(rv, Mp4parseStatus::Ok);
+    assert!(!parser.is_null());
+    parser
+}
+
+unsafe fn first_video_sample_info(
+    parser: *mut Mp4parseParser,
+) -> &'static Mp4parseTrackVideoSampleInfo {
+    let mut video = Mp4parseTrackVideoInfo::default();
+    let rv = mp4parse_get_track_video_info(parser, 0, &mut video);
+    assert_eq!(rv, Mp4parseStatus::Ok);
+    assert_eq!(video.sample_info_count, 1);
+    &*video.sample_info
+}
+
+/// A 100x60 High profile track, cropped from 112x64 macroblocks
+#[test]
+fn avc_config_cropping() {
+    unsafe {
+        let parser = open_parser("tests/video_rotation_90.mp4");
+        let sample = first_video_sample_info(parser);
+        assert_eq!(sample.codec_type, Mp4parseCodec::Avc);
+        assert!(sample.has_avc_config);
+        assert!(!sample.has_dolby_vision_config);
+
+        let config = &sample.avc_config;
+        assert!(config.has_sps);
+        assert_eq!(config.profile_indication, 100);
+        assert_eq!(config.level_indication, 10);
+        assert_eq!(config.nal_unit_length_size, 4);
+        assert_eq!(config.sps_count, 1);
+        assert_eq!(config.pps_count, 1);
+        assert_eq!(config.sps_ext_count, 0);
+        assert_eq!(config.chroma_format_idc, 1);
+        assert_eq!(config.bit_depth_luma, 8);
+        assert_eq!(config.bit_depth_chroma, 8);
+        assert_eq!((config.coded_width, config.coded_height), (112, 64));
+        assert_eq!(
+            config.crop,
+            mp4parse::CropRectangle {
+                x: 0,
+                y: 0,
+                width: 100,
+                height: 60,
+            }
+        );
+        assert!(config.has_sample_aspect_ratio);
+        assert_eq!((config.sar_width, config.sar_height), (1, 1));
+        assert!(!config.has_colour_description);
+
+        mp4parse_free(parser);
+    }
+}
+
+/// The VUI colour description matches the 'colr' box: HLG, full range
+#[test]
+fn avc_config_colour_description() {
+    unsafe {
+        let parser = open_parser("tests/video_colr_nclx_hlg_full_range.mp4");
+        let sample = first_video_sample_info(parser);
+        assert!(sample.has_avc_config);
+
+        let config = &sample.avc_config;
+        assert!(config.has_sps);
+        assert!(config.has_colour_description);
+        assert_eq!(config.colour_primaries, 9);
+        assert_eq!(config.transfer_characteristics, 18);
+        assert_eq!(config.matrix_coefficients, 9);
+        assert!(config.full_range_flag);
+
+        mp4parse_free(parser);
+    }
+}
+
+/// A Main profile track without a VUI sample aspect ratio
+#[test]
+fn avc_config_main_profile() {
+    unsafe {
+        let parser = open_parser("tests/bipbop_nonfragment_header.mp4");
+        let sample = first_video_sample_info(parser);
+        assert!(sample.has_avc_config);
+
+        let config = &sample.avc_config;
+        assert!(config.has_sps);
+        assert_eq!(config.profile_indication, 77);
+        assert_eq!(config.profile_compatibility, 64);
+        assert_eq!(config.level_indication, 21);
+        assert_eq!((config.coded_width, config.coded_height), (400, 304));
+        assert_eq!((config.crop.width, config.crop.height), (400, 300));
+        assert!(!config.has_sample_aspect_ratio);
+        assert!(config.has_colour_description);
+        assert_eq!(config.colour_primaries, 6);
+
+        mp4parse_free(parser);
+    }
+}
+
+unsafe fn codec_string(
+    parser: *mut Mp4parseParser,
+    track_index: u32,
+    description_index: u32,
+) -> Result<&'static [u8], Mp4parseStatus> {
+    let mut data = Mp4parseByteData::default();
+    let rv = mp4parse_get_track_codec_string(parser, track_index, description_index, &mut data);
+    if rv != Mp4parseStatus::Ok {
+        return Err(rv);
+    }
+    Ok(std::slice::from_raw_parts(data.data, data.length))
+}
+
+#[test]
+fn track_codec_strings() {
+    unsafe {
+        let parser = open_parser("tests/bipbop_nonfragment_header.mp4");
+        assert_eq!(codec_string(parser, 0, 0), Ok(&b"avc1.4d4015"[..]));
+        assert_eq!(codec_string(parser, 1, 0), Ok(&b"mp4a.40.2"[..]));
+        // The string is cached on the parser, so repeated calls agree
+        let first = codec_string(parser, 0, 0).unwrap().as_ptr();
+        assert_eq!(codec_string(parser, 0, 0).unwrap().as_ptr(), first);
+        assert_eq!(codec_string(parser, 0, 1), Err(Mp4parseStatus::BadArg));
+        assert_eq!(codec_string(parser, 2, 0), Err(Mp4parseStatus::BadArg));
+        mp4parse_free(parser);
+
+        let parser = open_parser("tests/opus_audioinit_two_desc.mp4");
+        assert_eq!(codec_string(parser, 0, 0), Ok(&b"opus"[..]));
+        assert_eq!(codec_string(parser, 0, 1), Ok(&b"opus"[..]));
+        mp4parse_free(parser);
+
+        let mut data = Mp4parseByteData::default();
+        let rv = mp4parse_get_track_codec_string(std::ptr::null_mut(), 0, 0, &mut data);
+        assert_eq!(rv, Mp4parseStatus::BadArg);
+    }
+}
@@ -10,6 +10,15 @@ extern "C" fn buf_read(buf: *mut u8, size: usize, userdata: *mut std::os::raw::c
     }
 }
 
+extern "C" fn vec_read(buf: *mut u8, size: usize, userdata: *mut std::os::raw::c_void) -> isize {
+    let input: &mut std::io::Cursor<Vec<u8>> = unsafe { &mut *(userdata as *mut _) };
+    let buf = unsafe { std::slice::from_raw_parts_mut(buf, size) };
+    match input.read(buf) {
+        Ok(n) => n as isize,
+        Err(_) => -1,
+    }
+}
+
 #[test]
 fn parse_cenc() {
     let mut file = std::fs::File::open("tests/short-cenc.mp4").expect("Unknown file");
@@ -103,6 +112,65 @@ fn parse_cenc() {
     }
 }
 
+#[test]
+fn parse_cenc_sample_encryption() {
+    let mut file = std::fs::File::open("tests/short-cenc.mp4").expect("Unknown file");
+    let io = Mp4parseIo {
+        read: Some(buf_read),
+        userdata: &mut file as *mut _ as *mut std::os::raw::c_void,
+    };
+
+    unsafe {
+        let mut parser = std::ptr::null_mut();
+        let mut rv = mp4parse_new(&io, &mut parser);
+        assert_eq!(rv, Mp4parseStatus::Ok);
+        assert!(!parser.is_null());
+
+        let mut video_track_info = Mp4parseTrackInfo::default();
+        rv = mp4parse_get_track_info(parser, 0, &mut video_track_info);
+        assert_eq!(rv, Mp4parseStatus::Ok);
+
+        let mut indices = Mp4parseByteData::default();
+        rv = mp4parse_get_indice_table(parser, video_track_info.track_id, &mut indices);
+        assert_eq!(rv, Mp4parseStatus::Ok);
+
+        let mut table = Mp4parseSampleEncryptionTable::default();
+        rv = mp4parse_get_sample_encryption_table(parser, video_track_info.track_id, &mut table);
+        assert_eq!(rv, Mp4parseStatus::Ok);
+        assert_eq!(table.sample_count, 10);
+        assert_eq!(table.sample_count, indices.length);
+
+        let samples = std::slice::from_raw_parts(table.samples, table.sample_count);
+        for sample in samples {
+            assert_eq!(sample.is_encrypted, 1);
+            assert_eq!(sample.iv_size, 16);
+            assert_eq!(sample.iv.length, 16);
+            assert!(sample.subsample_count > 0);
+            let kid = std::slice::from_raw_parts(sample.kid.data, sample.kid.length);
+            assert_eq!(kid, [0x7e, 0x57, 0x1d, 0x01].repeat(4).as_slice());
+        }
+        assert_eq!(samples[0].subsample_count, 2);
+        let subsamples = std::slice::from_raw_parts(samples[0].subsamples, 2);
+        assert_eq!(subsamples[0].bytes_of_clear_data, 5);
+        assert_eq!(subsamples[0].bytes_of_protected_data, 686);
+        assert_eq!(subsamples[1].bytes_of_clear_data, 5);
+        assert_eq!(subsamples[1].bytes_of_protected_data, 388);
+
+        let mut audio_track_info = Mp4parseTrackInfo::default();
+        rv = mp4parse_get_track_info(parser, 1, &mut audio_track_info);
+        assert_eq!(rv, Mp4parseStatus::Ok);
+        rv = mp4parse_get_sample_encryption_table(parser, audio_track_info.track_id, &mut table);
+        assert_eq!(rv, Mp4parseStatus::Ok);
+        assert_eq!(table.sample_count, 21);
+        let sample = &*table.samples;
+        assert_eq!(sample.subsample_count, 1);
+        assert_eq!((*sample.subsamples).bytes_of_clear_data, 0);
+        assert_eq!((*sample.subsamples).bytes_of_protected_data, 371);
+
+        mp4parse_free(parser);
+    }
+}
+
 #[test]
 fn repeated_get_pssh_info_returns_stable_pointer() {
     let mut file = std::fs::File::open("tests/short-cenc.mp4").expect("Unknown file");
@@ -145,6 +213,121 @@ fn repeated_get_pssh_info_returns_stable_pointer() {
     }
 }
 
+#[test]
+fn parse_pssh_table() {
+    let mut file = std::fs::File::open("tests/short-cenc.mp4").expect("Unknown file");
+    let io = Mp4parseIo {
+        read: Some(buf_read),
+        userdata: &mut file as *mut _ as *mut std::os::raw::c_void,
+    };
+
+    unsafe {
+        let mut parser = std::ptr::null_mut();
+        let mut rv = mp4parse_new(&io, &mut parser);
+        assert_eq!(rv, Mp4parseStatus::Ok);
+        assert!(!parser.is_null());
+
+        let mut table = Mp4parsePsshTable::default();
+        rv = mp4parse_get_pssh_table(parser, &mut table);
+        assert_eq!(rv, Mp4parseStatus::Ok);
+        assert_eq!(table.pssh_count, 1);
+        let pssh = &*table.psshs;
+        let expected_system_id = [
+            0x10, 0x77, 0xef, 0xec, 0xc0, 0xb2, 0x4d, 0x02, 0xac, 0xe3, 0x3c, 0x1e, 0x52, 0xe2,
+            0xfb, 0x4b,
+        ];
+        assert_eq!(pssh.system_id, expected_system_id);
+        assert_eq!(pssh.version, 1);
+        assert_eq!(pssh.kid_count, 2);
+        let kids = std::slice::from_raw_parts(pssh.kids.data, pssh.kids.length);
+        assert_eq!(kids[..16], *[0x7e, 0x57, 0x1d, 0x01].repeat(4));
+        assert_eq!(kids[16..], *[0x7e, 0x57, 0x1d, 0x02].repeat(4));
+        assert_eq!(pssh.data.length, 0);
+        assert!(pssh.data.data.is_null());
+        assert!(!pssh.in_moof);
+
+        mp4parse_free(parser);
+    }
+}
+
+#[test]
+fn parse_moof_pssh_table() {
+    let mut data = std::fs::read("tests/bipbop_cbcs_video_init.mp4").expect("Unknown file");
+    let moof_offset = data.len() as u64;
+    #[rustfmt::skip]
+    let moof = [
+        0x00, 0x00, 0x00, 0x38, b'm', b'o', b'o', b'f',
+        0x00, 0x00, 0x00, 0x10, b'm', b'f', b'h', b'd', 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x01,
+        0x00, 0x00, 0x00, 0x20, b'p', b's', b's', b'h', 0x00, 0x00, 0x00, 0x00,
+        0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11,
+        0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11,
+        0x00, 0x00, 0x00, 0x00,
+    ];
+    data.extend_from_slice(&moof);
+    let mut cursor = std::io::Cursor::new(data);
+    let io = Mp4parseIo {
+        read: Some(vec_read),
+        userdata: &mut cursor as *mut _ as *mut std::os::raw::c_void,
+    };
+
+    unsafe {
+        let mut parser = std::ptr::null_mut();
+        let mut rv = mp4parse_new(&io, &mut parser);
+        assert_eq!(rv, Mp4parseStatus::Ok);
+        assert!(!parser.is_null());
+
+        let mut table = Mp4parsePsshTable::default();
+        rv = mp4parse_get_pssh_table(parser, &mut table);
+        assert_eq!(rv, Mp4parseStatus::Ok);
+        assert_eq!(table.pssh_count, 2);
+        let psshs = std::slice::from_raw_parts(table.psshs, table.pssh_count);
+        assert_eq!(psshs[0].version, 1);
+        assert_eq!(psshs[0].kid_count, 1);
+        assert!(!psshs[0].in_moof);
+        assert_eq!(psshs[1].system_id, [0x11; 16]);
+        assert_eq!(psshs[1].version, 0);
+        assert_eq!(psshs[1].kid_count, 0);
+        assert!(psshs[1].kids.data.is_null());
+        assert!(psshs[1].in_moof);
+        assert_eq!(psshs[1].moof_offset, moof_offset);
+
+        mp4parse_free(parser);
+    }
+}
+
+#[test]
+fn repeated_get_pssh_table_returns_stable_pointer() {
+    let mut file = std::fs::File::open("tests/short-cenc.mp4").expect("Unknown file");
+    let io = Mp4parseIo {
+        read: Some(buf_read),
+        userdata: &mut file as *mut _ as *mut std::os::raw::c_void,
+    };
+
+    unsafe {
+        let mut parser = std::ptr::null_mut();
+        let rv = mp4parse_new(&io, &mut parser);
+        assert_eq!(rv, Mp4parseStatus::Ok);
+        assert!(!parser.is_null());
+
+        let mut table1 = Mp4parsePsshTable::default();
+        let rv = mp4parse_get_pssh_table(parser, &mut table1);
+        assert_eq!(rv, Mp4parseStatus::Ok);
+        let kids1 = (*table1.psshs).kids.data;
+
+        let mut table2 = Mp4parsePsshTable::default();
+        let rv = mp4parse_get_pssh_table(parser, &mut table2);
+        assert_eq!(rv, Mp4parseStatus::Ok);
+
+        // Pointers and counts must be stable across calls.
+        assert_eq!(table1.psshs, table2.psshs);
+        assert_eq!(table1.pssh_count, table2.pssh_count);
+        assert_eq!(kids1, (*table2.psshs).kids.data);
+
+        mp4parse_free(parser);
+    }
+}
+
 #[test]
 fn parse_cbcs() {
     let mut file = std::fs::File::open("tests/bipbop_cbcs_video_init.mp4").expect("Unknown file");
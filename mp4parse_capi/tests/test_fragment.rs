@@ -61,6 +61,17 @@ fn parse_fragment() {
         assert_eq!(fragment_info.fragment_duration, 10_032);
         assert_eq!(fragment_info.time_scale, 1000);
 
+        let mut trex = Mp4parseTrackExtends::default();
+        rv = mp4parse_get_track_extends(parser, track_info.track_id, &mut trex);
+        assert_eq!(rv, Mp4parseStatus::Ok);
+        assert_eq!(trex.default_sample_description_index, 1);
+        assert_eq!(trex.default_sample_duration, 1024);
+        assert_eq!(trex.default_sample_size, 0);
+        assert_eq!(trex.default_sample_flags, 0);
+
+        rv = mp4parse_get_track_extends(parser, track_info.track_id + 1, &mut trex);
+        assert_eq!(rv, Mp4parseStatus::Invalid);
+
         mp4parse_free(parser);
     }
 }
@@ -118,3 +129,43 @@ fn parse_opus_fragment() {
         mp4parse_free(parser);
     }
 }
+
+#[test]
+fn parse_segment_index() {
+    let mut file = std::fs::File::open("tests/no_timescale.mp4").expect("Unknown file");
+    let io = Mp4parseIo {
+        read: Some(buf_read),
+        userdata: &mut file as *mut _ as *mut std::os::raw::c_void,
+    };
+
+    unsafe {
+        let mut parser = std::ptr::null_mut();
+        let mut rv = mp4parse_new(&io, &mut parser);
+        assert_eq!(rv, Mp4parseStatus::Ok);
+        assert!(!parser.is_null());
+
+        let mut index = Mp4parseSegmentIndex::default();
+        rv = mp4parse_get_segment_index(parser, 1, &mut index);
+        assert_eq!(rv, Mp4parseStatus::Ok);
+        assert_eq!(index.timescale, 2500);
+        assert_eq!(index.segment_count, 6);
+
+        let segments = std::slice::from_raw_parts(index.segments, index.segment_count);
+        assert_eq!(segments[0].offset, 942);
+        assert_eq!(segments[0].size, 328 + 12_014);
+        assert_eq!(segments[0].start_time, 0);
+        assert_eq!(segments[0].duration, 2490);
+        assert!(segments[0].starts_with_sap);
+        assert_eq!(segments[1].offset, 13_328);
+        assert_eq!(segments[5].offset, 63_205);
+        assert_eq!(segments[5].start_time, 12_450);
+        assert_eq!(segments[5].duration, 2656);
+
+        rv = mp4parse_get_segment_index(parser, 2, &mut index);
+        assert_eq!(rv, Mp4parseStatus::Invalid);
+        assert_eq!(index.segment_count, 0);
+        assert!(index.segments.is_null());
+
+        mp4parse_free(parser);
+    }
+}
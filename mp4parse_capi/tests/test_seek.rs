@@ -0,0 +1,103 @@
+use mp4parse_capi::*;
+use std::io::{Read, Seek, SeekFrom};
+
+/// A file that counts the bytes read through `buf_read`.
+struct CountingFile {
+    file: std::fs::File,
+    bytes_read: usize,
+}
+
+extern "C" fn buf_read(buf: *mut u8, size: usize, userdata: *mut std::os::raw::c_void) -> isize {
+    let input: &mut CountingFile = unsafe { &mut *(userdata as *mut _) };
+    let buf = unsafe { std::slice::from_raw_parts_mut(buf, size) };
+    match input.file.read(buf) {
+        Ok(n) => {
+            input.bytes_read += n;
+            n as isize
+        }
+        Err(_) => -1,
+    }
+}
+
+extern "C" fn buf_seek(offset: u64, userdata: *mut std::os::raw::c_void) -> i32 {
+    let input: &mut CountingFile = unsafe { &mut *(userdata as *mut _) };
+    match input.file.seek(SeekFrom::Start(offset)) {
+        Ok(_) => 0,
+        Err(_) => -1,
+    }
+}
+
+/// Parse `path`, returning the sample byte ranges of its first track and the
+/// number of bytes read.
+unsafe fn parse(path: &str, seek: bool) -> (Vec<(u64, u64)>, usize) {
+    let mut input = CountingFile {
+        file: std::fs::File::open(path).expect("Unknown file"),
+        bytes_read: 0,
+    };
+    let userdata = &mut input as *mut _ as *mut std::os::raw::c_void;
+
+    let mut parser = std::ptr::null_mut();
+    let mut rv = if seek {
+        let io = Mp4parseSeekableIo {
+            read: Some(buf_read),
+            seek: Some(buf_seek),
+            userdata,
+        };
+        mp4parse_new_seekable(&io, &mut parser)
+    } else {
+        let io = Mp4parseIo {
+            read: Some(buf_read),
+            userdata,
+        };
+        mp4parse_new(&io, &mut parser)
+    };
+    assert_eq!(rv, Mp4parseStatus::Ok);
+    assert!(!parser.is_null());
+
+    let mut track_info = Mp4parseTrackInfo::default();
+    rv = mp4parse_get_track_info(parser, 0, &mut track_info);
+    assert_eq!(rv, Mp4parseStatus::Ok);
+
+    let mut indice = Mp4parseByteData::default();
+    rv = mp4parse_get_indice_table(parser, track_info.track_id, &mut indice);
+    assert_eq!(rv, Mp4parseStatus::Ok);
+    let indices = std::slice::from_raw_parts(indice.indices, indice.length)
+        .iter()
+        .map(|i| (i.start_offset.0, i.end_offset.0))
+        .collect();
+
+    mp4parse_free(parser);
+    (indices, input.bytes_read)
+}
+
+#[test]
+fn parse_with_seek_skips_mdat() {
+    // white.mp4 has its 'mdat' ahead of the 'moov'.
+    let path = "tests/white.mp4";
+    let file_size = std::fs::metadata(path).unwrap().len() as usize;
+    unsafe {
+        let (expected, read_without_seek) = parse(path, false);
+        let (indices, read_with_seek) = parse(path, true);
+        assert_eq!(indices, expected);
+        assert_eq!(read_without_seek, file_size);
+        // The 8190 byte 'mdat' is skipped.
+        assert!(read_with_seek <= file_size - 8000);
+    }
+}
+
+#[test]
+fn parse_seekable_without_seek() {
+    let mut input = CountingFile {
+        file: std::fs::File::open("tests/white.mp4").expect("Unknown file"),
+        bytes_read: 0,
+    };
+    let io = Mp4parseSeekableIo {
+        read: Some(buf_read),
+        seek: None,
+        userdata: &mut input as *mut _ as *mut std::os::raw::c_void,
+    };
+    let mut parser = std::ptr::null_mut();
+    let rv = unsafe { mp4parse_new_seekable(&io, &mut parser) };
+    assert_eq!(rv, Mp4parseStatus::BadArg);
+    assert!(parser.is_null());
+}